@@ -0,0 +1,368 @@
+use csv::Writer;
+use serde::Serialize;
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::classification::{BinaryClass, Labelled};
+use crate::describe::quantile;
+use crate::report::REPORT_BINS;
+use crate::{Config, Feature, FeatureValue, PassengerFeatures};
+
+#[cfg(test)]
+mod tests;
+
+// The name of the label in the tables, next to the feature names
+pub const SURVIVED: &str = "survived";
+
+// Iterations and relative precision of the incomplete gamma function
+const GAMMA_ITERATIONS: usize = 500;
+const GAMMA_EPSILON: f64 = 1e-15;
+
+// A feature, or the label, read two ways: as a number for the correlations and as a level for the contingency tables.
+struct Variable {
+    name: String,
+    // None where the value is missing, so that the correlations leave the passenger out
+    numbers: Vec<Option<f64>>,
+    // Level 0 holds the missing values and continuous features are cut into quantile bins.
+    levels: Vec<usize>,
+    // Categories without an order, whose numbers mean nothing to a correlation
+    nominal: bool,
+}
+
+// The port of embarkation is the only feature whose categories have no order.
+fn is_nominal(feature: &Feature) -> bool {
+    matches!(feature, Feature::PortOfEmbarkation)
+}
+
+// Names, tickets and cabins only record whether they are present, so missing counts as 0 rather than being left out.
+fn feature_number(feature: &Feature, value: &FeatureValue) -> Option<f64> {
+    match value {
+        FeatureValue::Missing => match feature {
+            Feature::Name | Feature::TicketId | Feature::CabinId => Some(0_f64),
+            _ => None,
+        },
+        FeatureValue::Present => Some(1_f64),
+        FeatureValue::Category(category) => Some(*category as f64),
+        FeatureValue::Number(number) => Some(*number),
+    }
+}
+
+fn feature_variable<R: PassengerFeatures>(records: &[R], feature: &Feature, bins: usize) -> Variable {
+    let values: Vec<FeatureValue> = records.iter().map(|record| record.get_feature(feature)).collect();
+    let levels = if feature.is_continuous() {
+        let mut sorted: Vec<f64> = values.iter().filter_map(|value| match value {
+            FeatureValue::Number(number) => Some(*number),
+            _ => None,
+        }).collect();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let edges: Vec<f64> = (1..bins.max(1)).map(|bin| quantile(&sorted, (bin as f64).div(bins.max(1) as f64))).collect();
+        values.iter().map(|value| match value {
+            FeatureValue::Number(number) => edges.iter().filter(|edge| number > edge).count().add(1),
+            _ => 0,
+        }).collect()
+    } else {
+        let mut seen: Vec<FeatureValue> = Vec::new();
+        values.iter().map(|value| {
+            if *value == FeatureValue::Missing {
+                return 0
+            }
+            match seen.iter().position(|other| other == value) {
+                Some(index) => index.add(1),
+                None => {
+                    seen.push(value.clone());
+                    seen.len()
+                },
+            }
+        }).collect()
+    };
+    Variable {
+        name: feature.get_name().to_string(),
+        numbers: values.iter().map(|value| feature_number(feature, value)).collect(),
+        levels,
+        nominal: is_nominal(feature),
+    }
+}
+
+fn survived_variable<R: Labelled>(records: &[R]) -> Variable {
+    let levels: Vec<usize> = records.iter().map(|record| match record.label() {
+        BinaryClass::Yes => 1,
+        BinaryClass::No => 0,
+    }).collect();
+    Variable {
+        name: SURVIVED.to_string(),
+        numbers: levels.iter().map(|level| Some(*level as f64)).collect(),
+        levels,
+        nominal: false,
+    }
+}
+
+// Pearson's correlation coefficient, NaN when there are fewer than two pairs or either side is constant.
+pub fn pearson(pairs: &[(f64, f64)]) -> f64 {
+    if pairs.len() < 2 {
+        return f64::NAN
+    }
+    let count = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|(x, _y)| x).sum::<f64>().div(count);
+    let mean_y = pairs.iter().map(|(_x, y)| y).sum::<f64>().div(count);
+    let (covariance, variance_x, variance_y) = pairs.iter().fold((0_f64, 0_f64, 0_f64), |(covariance, variance_x, variance_y), (x, y)| {
+        let (dx, dy) = (x.sub(mean_x), y.sub(mean_y));
+        (covariance.add(dx.mul(dy)), variance_x.add(dx.powi(2)), variance_y.add(dy.powi(2)))
+    });
+    if variance_x == 0_f64 || variance_y == 0_f64 {
+        return f64::NAN
+    }
+    covariance.div(variance_x.mul(variance_y).sqrt())
+}
+
+// Ranks from 1, with tied values sharing the mean of their ranks
+pub fn ranks(numbers: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..numbers.len()).collect();
+    order.sort_by(|a, b| numbers[*a].total_cmp(&numbers[*b]));
+    let mut ranks = vec![0_f64; numbers.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start.add(1);
+        while end < order.len() && numbers[order[end]] == numbers[order[start]] {
+            end = end.add(1);
+        }
+        let rank = ((start.add(end).add(1)) as f64).div(2_f64);
+        for index in &order[start..end] {
+            ranks[*index] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+// Spearman's rank correlation: Pearson's correlation of the ranks
+pub fn spearman(pairs: &[(f64, f64)]) -> f64 {
+    let ranks_x = ranks(&pairs.iter().map(|(x, _y)| *x).collect::<Vec<f64>>());
+    let ranks_y = ranks(&pairs.iter().map(|(_x, y)| *y).collect::<Vec<f64>>());
+    pearson(&ranks_x.into_iter().zip(ranks_y).collect::<Vec<(f64, f64)>>())
+}
+
+// The statistics of a contingency table of counts. Empty rows and columns are left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContingencyStatistics {
+    pub chi_square: f64,
+    pub degrees_of_freedom: usize,
+    pub cramers_v: f64,
+    // In bits
+    pub mutual_information: f64,
+}
+
+pub fn contingency_statistics(table: &[Vec<f64>]) -> ContingencyStatistics {
+    let table: Vec<&Vec<f64>> = table.iter().filter(|row| row.iter().sum::<f64>() > 0_f64).collect();
+    let columns = table.iter().map(|row| row.len()).max().unwrap_or(0);
+    let column_totals: Vec<f64> = (0..columns).map(|column| table.iter().map(|row| row.get(column).copied().unwrap_or(0_f64)).sum()).collect();
+    let total = column_totals.iter().sum::<f64>();
+    let mut chi_square = 0_f64;
+    let mut mutual_information = 0_f64;
+    for row in &table {
+        let row_total = row.iter().sum::<f64>();
+        for (column_total, observed) in column_totals.iter().zip(row.iter()).filter(|(column_total, _observed)| **column_total > 0_f64) {
+            let expected = row_total.mul(*column_total).div(total);
+            chi_square = chi_square.add(observed.sub(expected).powi(2).div(expected));
+            if *observed > 0_f64 {
+                mutual_information = mutual_information.add(observed.div(total).mul(observed.div(expected).log2()));
+            }
+        }
+    }
+    let rows = table.len();
+    let columns = column_totals.iter().filter(|column_total| **column_total > 0_f64).count();
+    let smaller = rows.min(columns).saturating_sub(1);
+    ContingencyStatistics {
+        chi_square,
+        degrees_of_freedom: rows.saturating_sub(1).mul(columns.saturating_sub(1)),
+        cramers_v: if smaller == 0 { f64::NAN } else { chi_square.div(total.mul(smaller as f64)).sqrt() },
+        mutual_information,
+    }
+}
+
+// The natural logarithm of the gamma function, by the Lanczos approximation
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5_f64 {
+        return std::f64::consts::PI.div(std::f64::consts::PI.mul(x).sin()).ln().sub(ln_gamma(1_f64.sub(x)))
+    }
+    let x = x.sub(1_f64);
+    let sum = COEFFICIENTS.iter().enumerate().skip(1).fold(COEFFICIENTS[0], |sum, (index, coefficient)| sum.add(coefficient.div(x.add(index as f64))));
+    let t = x.add(7.5_f64);
+    0.5_f64.mul(2_f64.mul(std::f64::consts::PI).ln()).add(x.add(0.5_f64).mul(t.ln())).sub(t).add(sum.ln())
+}
+
+// The regularized upper incomplete gamma function Q(a, x), by its series below a + 1 and its continued fraction above.
+fn upper_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0_f64 {
+        return 1_f64
+    }
+    let scale = x.ln().mul(a).sub(x).sub(ln_gamma(a)).exp();
+    if x < a.add(1_f64) {
+        let mut term = 1_f64.div(a);
+        let mut sum = term;
+        for step in 1..GAMMA_ITERATIONS {
+            term = term.mul(x).div(a.add(step as f64));
+            sum = sum.add(term);
+            if term.abs() < sum.abs().mul(GAMMA_EPSILON) {
+                break
+            }
+        }
+        return 1_f64.sub(sum.mul(scale)).max(0_f64)
+    }
+    let tiny = f64::MIN_POSITIVE.div(GAMMA_EPSILON);
+    let mut b = x.add(1_f64).sub(a);
+    let mut c = 1_f64.div(tiny);
+    let mut d = 1_f64.div(b);
+    let mut fraction = d;
+    for step in 1..GAMMA_ITERATIONS {
+        let step = step as f64;
+        let an = step.mul(a.sub(step));
+        b = b.add(2_f64);
+        d = an.mul(d).add(b);
+        if d.abs() < tiny {
+            d = tiny;
+        }
+        c = b.add(an.div(c));
+        if c.abs() < tiny {
+            c = tiny;
+        }
+        d = 1_f64.div(d);
+        fraction = fraction.mul(d.mul(c));
+        if d.mul(c).sub(1_f64).abs() < GAMMA_EPSILON {
+            break
+        }
+    }
+    scale.mul(fraction)
+}
+
+// The probability of a chi-square statistic at least this large when there is no association, NaN without degrees of
+// freedom. With one degree of freedom this is also the two-sided p-value of a z statistic, given its square.
+pub fn chi_square_p_value(statistic: f64, degrees_of_freedom: usize) -> f64 {
+    if degrees_of_freedom == 0 || statistic.is_nan() {
+        return f64::NAN
+    }
+    upper_gamma((degrees_of_freedom as f64).div(2_f64), statistic.div(2_f64))
+}
+
+// The association between two features, or between a feature and survival. Pearson and Spearman leave out passengers
+// missing either value and are not given for the port of embarkation, whose categories have no order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Association {
+    pub feature: String,
+    pub other: String,
+    pub pearson: Option<f64>,
+    pub spearman: Option<f64>,
+    pub cramers_v: f64,
+    pub mutual_information: f64,
+    pub chi_square: f64,
+    pub degrees_of_freedom: usize,
+    pub p_value: f64,
+}
+
+fn associate(first: &Variable, second: &Variable) -> Association {
+    let levels = |variable: &Variable| variable.levels.iter().max().map_or(0, |level| level.add(1));
+    let mut table = vec![vec![0_f64; levels(second)]; levels(first)];
+    for (row, column) in first.levels.iter().zip(second.levels.iter()) {
+        table[*row][*column] = table[*row][*column].add(1_f64);
+    }
+    let statistics = contingency_statistics(&table);
+    let pairs: Vec<(f64, f64)> = first.numbers.iter().zip(second.numbers.iter()).filter_map(|pair| match pair {
+        (Some(x), Some(y)) => Some((*x, *y)),
+        _ => None,
+    }).collect();
+    let ordered = !first.nominal && !second.nominal;
+    Association {
+        feature: first.name.clone(),
+        other: second.name.clone(),
+        pearson: if ordered { Some(pearson(&pairs)) } else { None },
+        spearman: if ordered { Some(spearman(&pairs)) } else { None },
+        cramers_v: statistics.cramers_v,
+        mutual_information: statistics.mutual_information,
+        chi_square: statistics.chi_square,
+        degrees_of_freedom: statistics.degrees_of_freedom,
+        p_value: chi_square_p_value(statistics.chi_square, statistics.degrees_of_freedom),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureAnalysis {
+    // Each feature against survival, most mutual information first
+    pub survival: Vec<Association>,
+    // Each pair of features, in the order of the features
+    pub pairs: Vec<Association>,
+}
+
+// Continuous features are cut into that many quantile bins for the contingency tables, as in the report command.
+pub fn analyse<R: PassengerFeatures + Labelled>(records: &[R], features: &[Feature], bins: usize) -> FeatureAnalysis {
+    let variables: Vec<Variable> = features.iter().map(|feature| feature_variable(records, feature, bins)).collect();
+    let survived = survived_variable(records);
+    let mut survival: Vec<Association> = variables.iter().map(|variable| associate(variable, &survived)).collect();
+    survival.sort_by(|a, b| b.mutual_information.total_cmp(&a.mutual_information));
+    let mut pairs = Vec::new();
+    for (index, first) in variables.iter().enumerate() {
+        for second in &variables[index.add(1)..] {
+            pairs.push(associate(first, second));
+        }
+    }
+    FeatureAnalysis { survival, pairs }
+}
+
+fn format_correlation(correlation: &Option<f64>) -> String {
+    correlation.map_or_else(|| "-".to_string(), |correlation| format!("{:.4}", correlation))
+}
+
+fn print_associations(associations: &[Association]) {
+    println!("{:>20} {:>20} {:>9} {:>9} {:>9} {:>9} {:>11} {:>4} {:>10}", "feature", "other", "pearson", "spearman", "cramers_v", "mi_bits", "chi_square", "dof", "p_value");
+    for association in associations {
+        println!("{:>20} {:>20} {:>9} {:>9} {:>9.4} {:>9.4} {:>11.3} {:>4} {:>10.3e}", association.feature, association.other, format_correlation(&association.pearson), format_correlation(&association.spearman), association.cramers_v, association.mutual_information, association.chi_square, association.degrees_of_freedom, association.p_value);
+    }
+}
+
+fn write_associations(filename: &str, analysis: &FeatureAnalysis) -> Result<(), String> {
+    match Writer::from_path(filename) {
+        Ok(mut writer) => {
+            for association in analysis.survival.iter().chain(analysis.pairs.iter()) {
+                if let Err(e2) = writer.serialize(association) {
+                    let message = format!("analysis::write_associations Failed to serialize Association of {} and {}. Serde: {}", association.feature, association.other, e2);
+                    return Err(message)
+                }
+            }
+            eprintln!("analysis::write_associations Completed writing the feature analysis to {}", filename);
+            Ok(())
+        },
+        Err(e1) => {
+            let message = format!("analysis::write_associations Failed to create writer to {}. Serde: {}", filename, e1);
+            Err(message)
+        },
+    }
+}
+
+// Prints how strongly each configured feature goes with survival and with every other feature, to guide which
+// features are worth weights.
+pub fn run(config: &Config) -> Result<(), String> {
+    let training_passengers = config.get_training_passengers()?;
+    if training_passengers.is_empty() {
+        return Err("analysis::run needs at least one training passenger".to_string())
+    }
+    let analysis = analyse(&training_passengers, config.get_features(), REPORT_BINS);
+    println!("analysis::run {} training passengers from {}", training_passengers.len(), config.get_training_data_filename());
+    println!();
+    println!("Against survival, most informative first");
+    print_associations(&analysis.survival);
+    println!();
+    println!("Between features");
+    print_associations(&analysis.pairs);
+    if let Some(filename) = config.get_analysis_filename() {
+        write_associations(filename, &analysis)?;
+    }
+    Ok(())
+}
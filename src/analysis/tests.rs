@@ -0,0 +1,60 @@
+use super::*;
+use crate::test_support::passengers;
+
+#[test]
+fn when_monotonic_but_not_linear_then_spearman_one_and_pearson_below() {
+    let pairs = [(1_f64, 1_f64), (2_f64, 4_f64), (3_f64, 9_f64), (4_f64, 16_f64)];
+
+    assert_abs_diff_eq!(spearman(&pairs), 1_f64, epsilon = 1e-12);
+    assert!(pearson(&pairs) < 0.99_f64);
+    assert_abs_diff_eq!(pearson(&[(1_f64, 2_f64), (2_f64, 4_f64), (3_f64, 6_f64)]), 1_f64, epsilon = 1e-12);
+    assert!(pearson(&[(1_f64, 2_f64), (2_f64, 2_f64)]).is_nan());
+    assert_eq!(ranks(&[10_f64, 20_f64, 20_f64, 30_f64]), vec![1_f64, 2.5_f64, 2.5_f64, 4_f64]);
+}
+
+#[test]
+fn when_contingency_table_then_chi_square_cramers_v_and_p_value() {
+    let statistics = contingency_statistics(&[vec![10_f64, 20_f64], vec![30_f64, 40_f64]]);
+    let independent = contingency_statistics(&[vec![10_f64, 20_f64, 0_f64], vec![0_f64, 0_f64, 0_f64], vec![20_f64, 40_f64, 0_f64]]);
+
+    assert_abs_diff_eq!(statistics.chi_square, 0.7936507936507936_f64, epsilon = 1e-12);
+    assert_eq!(statistics.degrees_of_freedom, 1);
+    assert_abs_diff_eq!(statistics.cramers_v, 0.0890870806374748_f64, epsilon = 1e-12);
+    assert_abs_diff_eq!(chi_square_p_value(statistics.chi_square, 1), 0.37299848361348714_f64, epsilon = 1e-9);
+    assert_abs_diff_eq!(independent.chi_square, 0_f64, epsilon = 1e-12);
+    assert_abs_diff_eq!(independent.mutual_information, 0_f64, epsilon = 1e-12);
+    assert_eq!(independent.degrees_of_freedom, 1);
+}
+
+#[test]
+fn when_chi_square_critical_value_then_p_value_five_percent() {
+    assert_abs_diff_eq!(chi_square_p_value(3.841458820694124_f64, 1), 0.05_f64, epsilon = 1e-9);
+    assert_abs_diff_eq!(chi_square_p_value(5.991464547107979_f64, 2), 0.05_f64, epsilon = 1e-9);
+    assert_abs_diff_eq!(chi_square_p_value(0.5_f64, 1), 0.4795001221869535_f64, epsilon = 1e-9);
+    assert_abs_diff_eq!(chi_square_p_value(0_f64, 3), 1_f64);
+    assert!(chi_square_p_value(1_f64, 0).is_nan());
+}
+
+#[test]
+fn when_feature_decides_survival_then_most_informative_with_one_bit() {
+    let records = passengers(&[
+        "1,1,1,A,female,30,1,0,T,10,,S",
+        "2,1,3,B,female,40,0,0,T,20,,C",
+        "3,0,3,C,male,30,1,0,T,10,,S",
+        "4,0,1,D,male,40,0,0,T,20,,C",
+    ]);
+
+    let analysis = analyse(&records, &[Feature::PassengerClass, Feature::Sex, Feature::PortOfEmbarkation], REPORT_BINS);
+
+    assert_eq!(analysis.survival[0].feature, "sex");
+    assert_eq!(analysis.survival[0].other, SURVIVED);
+    assert_abs_diff_eq!(analysis.survival[0].mutual_information, 1_f64, epsilon = 1e-12);
+    assert_abs_diff_eq!(analysis.survival[0].cramers_v, 1_f64, epsilon = 1e-12);
+    assert_abs_diff_eq!(analysis.survival[0].pearson.unwrap().abs(), 1_f64, epsilon = 1e-12);
+    assert_eq!(analysis.pairs.len(), 3);
+    assert_eq!((analysis.pairs[0].feature.as_str(), analysis.pairs[0].other.as_str()), ("passenger_class", "sex"));
+    assert_abs_diff_eq!(analysis.pairs[0].cramers_v, 0_f64, epsilon = 1e-12);
+    let embarkation = analysis.survival.iter().find(|association| association.feature == "port_of_embarkation").unwrap();
+    assert!(embarkation.pearson.is_none());
+    assert!(embarkation.spearman.is_none());
+}
@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::ops::{Add, Div, Mul, Sub};
+
+use serde::{Deserialize, Serialize};
+
+use super::{quick_convert, BinaryClass, Classifier, Labelled};
+use crate::{Feature, FeatureValue, PassengerFeatures};
+
+#[cfg(test)]
+mod tests;
+
+// Share of the largest variance added to every variance, so a class whose numbers are all equal keeps a finite density.
+const VARIANCE_SMOOTHING: f64 = 1e-9;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BayesSettings {
+	// Laplace smoothing: pseudo-count added to the records of every value, including missing and unseen values
+	pub alpha: f64
+}
+
+impl Default for BayesSettings {
+	fn default() -> Self {
+		BayesSettings {
+			alpha: 1_f64
+		}
+	}
+}
+
+// Records answering (Yes, No)
+pub type Counts = (usize, usize);
+
+fn add_label(counts: &mut Counts, label: &BinaryClass) {
+	match label {
+		BinaryClass::Yes => counts.0 = counts.0.add(1),
+		BinaryClass::No => counts.1 = counts.1.add(1),
+	}
+}
+
+fn of_class(counts: &Counts, label: &BinaryClass) -> usize {
+	match label {
+		BinaryClass::Yes => counts.0,
+		BinaryClass::No => counts.1,
+	}
+}
+
+// Normal distribution of the numbers one class has for a continuous feature
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Gaussian {
+	pub count: usize,
+	pub mean: f64,
+	pub variance: f64
+}
+
+impl Gaussian {
+	fn fit(numbers: &[f64]) -> Gaussian {
+		if numbers.is_empty() {
+			return Gaussian { count: 0, mean: 0_f64, variance: 0_f64 }
+		}
+		let count = quick_convert(&numbers.len());
+		let mean = numbers.iter().fold(0_f64, |sum, number| sum.add(number)).div(count);
+		let variance = numbers.iter().fold(0_f64, |sum, number| sum.add(number.sub(mean).powi(2))).div(count);
+		Gaussian { count: numbers.len(), mean, variance }
+	}
+
+	fn log_density(&self, number: f64) -> f64 {
+		let deviation = number.sub(self.mean);
+		-(2_f64.mul(PI).mul(self.variance).ln().add(deviation.mul(deviation).div(self.variance))).div(2_f64)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Likelihood {
+	// Records of each class per value, keyed by Feature::format_value
+	Categorical { counts: BTreeMap<String, Counts> },
+	// Missing numbers are counted apart from the normal distribution of the present ones
+	Gaussian { missing: Counts, present: Counts, yes: Gaussian, no: Gaussian }
+}
+
+impl Likelihood {
+	fn fit(feature: &Feature, values: &[FeatureValue], labels: &[BinaryClass]) -> Likelihood {
+		if feature.is_continuous() {
+			let mut missing = (0_usize, 0_usize);
+			let mut present = (0_usize, 0_usize);
+			let mut numbers = (Vec::new(), Vec::new());
+			for (value, label) in values.iter().zip(labels) {
+				match (value, label) {
+					(FeatureValue::Number(number), BinaryClass::Yes) => numbers.0.push(*number),
+					(FeatureValue::Number(number), BinaryClass::No) => numbers.1.push(*number),
+					_ => {
+						add_label(&mut missing, label);
+						continue
+					},
+				}
+				add_label(&mut present, label);
+			}
+			let (mut yes, mut no) = (Gaussian::fit(&numbers.0), Gaussian::fit(&numbers.1));
+			let mut all = numbers.0;
+			all.extend(numbers.1);
+			let pooled = Gaussian::fit(&all);
+			// A class without any number borrows the distribution of both classes together.
+			for gaussian in [&mut yes, &mut no].iter_mut() {
+				if gaussian.count == 0 {
+					**gaussian = Gaussian { count: 0, ..pooled.clone() };
+				}
+			}
+			let smoothing = VARIANCE_SMOOTHING.mul(yes.variance.max(no.variance).max(1_f64));
+			yes.variance = yes.variance.add(smoothing);
+			no.variance = no.variance.add(smoothing);
+			Likelihood::Gaussian { missing, present, yes, no }
+		} else {
+			let mut counts = BTreeMap::new();
+			for (value, label) in values.iter().zip(labels) {
+				add_label(counts.entry(feature.format_value(value)).or_insert((0, 0)), label);
+			}
+			Likelihood::Categorical { counts }
+		}
+	}
+
+	// ln P(value | label) with Laplace smoothing alpha
+	fn log_probability(&self, feature: &Feature, value: &FeatureValue, label: &BinaryClass, classes: &Counts, alpha: f64) -> f64 {
+		let total = quick_convert(&of_class(classes, label));
+		match self {
+			Likelihood::Categorical { counts } => {
+				let count = counts.get(&feature.format_value(value)).map_or(0, |counts| of_class(counts, label));
+				// One more value than seen leaves room for values only met when predicting.
+				let values = quick_convert(&counts.len().add(1));
+				quick_convert(&count).add(alpha).div(total.add(alpha.mul(values))).ln()
+			},
+			Likelihood::Gaussian { missing, present, yes, no } => match value {
+				FeatureValue::Number(number) => {
+					let gaussian = match label {
+						BinaryClass::Yes => yes,
+						BinaryClass::No => no,
+					};
+					let share = quick_convert(&of_class(present, label)).add(alpha).div(total.add(2_f64.mul(alpha)));
+					share.ln().add(gaussian.log_density(*number))
+				},
+				_ => quick_convert(&of_class(missing, label)).add(alpha).div(total.add(2_f64.mul(alpha))).ln(),
+			},
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NaiveBayes {
+	alpha: f64,
+	classes: Counts,
+	likelihoods: Vec<(Feature, Likelihood)>
+}
+
+impl NaiveBayes {
+	// Mixed naive Bayes: P(Survived | passenger) is proportional to P(Survived) times P(value | Survived) of every feature,
+	// taken from smoothed counts for discrete features and from a normal distribution per class for age and fare.
+	pub fn fit<R: PassengerFeatures + Labelled>(records: &[R], features: &[Feature], settings: &BayesSettings) -> Result<NaiveBayes, String> {
+		if records.is_empty() || features.is_empty() {
+			return Err("NaiveBayes::fit needs at least one record and feature".to_string())
+		}
+		if settings.alpha <= 0_f64 {
+			return Err(format!("NaiveBayes::fit alpha {} should be positive", settings.alpha))
+		}
+		let labels: Vec<BinaryClass> = records.iter().map(|record| record.label()).collect();
+		let mut classes = (0_usize, 0_usize);
+		for label in &labels {
+			add_label(&mut classes, label);
+		}
+		let likelihoods = features.iter().map(|feature| {
+			let values: Vec<FeatureValue> = records.iter().map(|record| record.get_feature(feature)).collect();
+			(*feature, Likelihood::fit(feature, &values, &labels))
+		}).collect();
+		eprintln!("NaiveBayes::fit Counted {} records ({} survived) over {} features with Laplace smoothing {}", records.len(), classes.0, features.len(), settings.alpha);
+		Ok(NaiveBayes { alpha: settings.alpha, classes, likelihoods })
+	}
+
+	pub fn get_alpha(&self) -> &f64 {
+		&self.alpha
+	}
+
+	pub fn get_classes(&self) -> &Counts {
+		&self.classes
+	}
+
+	pub fn get_likelihoods(&self) -> &Vec<(Feature, Likelihood)> {
+		&self.likelihoods
+	}
+
+	// ln P(label) + the sum of ln P(value | label) over the features
+	pub fn log_joint<R: PassengerFeatures>(&self, record: &R, label: &BinaryClass) -> f64 {
+		let total = quick_convert(&self.classes.0.add(self.classes.1));
+		let prior = quick_convert(&of_class(&self.classes, label)).add(self.alpha).div(total.add(2_f64.mul(self.alpha))).ln();
+		self.likelihoods.iter().fold(prior, |sum, (feature, likelihood)| {
+			sum.add(likelihood.log_probability(feature, &record.get_feature(feature), label, &self.classes, self.alpha))
+		})
+	}
+
+	// Probability of BinaryClass::Yes
+	pub fn probability<R: PassengerFeatures>(&self, record: &R) -> f64 {
+		let difference = self.log_joint(record, &BinaryClass::No).sub(self.log_joint(record, &BinaryClass::Yes));
+		1_f64.div(difference.exp().add(1_f64))
+	}
+}
+
+// fit counts the records again, over the features and with the smoothing it was fitted with.
+impl<R: PassengerFeatures + Labelled> Classifier<R> for NaiveBayes {
+	fn fit(&mut self, records: &[R]) -> Result<(), String> {
+		let features: Vec<Feature> = self.likelihoods.iter().map(|(feature, _likelihood)| *feature).collect();
+		*self = NaiveBayes::fit(records, &features, &BayesSettings { alpha: self.alpha })?;
+		Ok(())
+	}
+
+	fn predict_proba(&self, record: &R) -> Result<f64, String> {
+		Ok(self.probability(record))
+	}
+}
@@ -0,0 +1,53 @@
+use super::*;
+use crate::test_support::passengers;
+
+#[test]
+fn when_categorical_feature_then_laplace_smoothed_posterior() {
+	let records = passengers(&[
+		"1,1,1,A,female,30,0,0,T,10,,S",
+		"2,1,3,B,female,40,0,0,T,10,,S",
+		"3,0,1,C,female,30,0,0,T,10,,S",
+		"4,0,3,D,male,40,0,0,T,10,,S",
+	]);
+	let bayes = NaiveBayes::fit(&records, &[Feature::Sex], &BayesSettings::default()).unwrap();
+
+	// P(female | Yes) = (2 + 1) / (2 + 3) and P(female | No) = (1 + 1) / (2 + 3) with even priors
+	assert_abs_diff_eq!(bayes.probability(&records[0]), 0.6_f64, epsilon = 1e-12);
+	// P(male | Yes) = (0 + 1) / 5 and P(male | No) = (1 + 1) / 5
+	assert_abs_diff_eq!(bayes.probability(&records[3]), 1_f64 / 3_f64, epsilon = 1e-12);
+	let unseen = passengers(&["5,0,2,E,,50,0,0,T,10,,C"]);
+	assert_abs_diff_eq!(bayes.probability(&unseen[0]), 0.5_f64, epsilon = 1e-12);
+}
+
+#[test]
+fn when_continuous_feature_then_normal_distribution_per_class() {
+	let records = passengers(&[
+		"1,1,3,A,male,10,0,0,T,10,,S",
+		"2,1,3,B,male,20,0,0,T,10,,S",
+		"3,1,3,C,male,,0,0,T,10,,S",
+		"4,0,3,D,male,50,0,0,T,10,,S",
+		"5,0,3,E,male,60,0,0,T,10,,S",
+	]);
+	let bayes = NaiveBayes::fit(&records, &[Feature::Age], &BayesSettings::default()).unwrap();
+
+	match &bayes.get_likelihoods()[0].1 {
+		Likelihood::Gaussian { missing, yes, no, .. } => {
+			assert_eq!(*missing, (1, 0));
+			assert_abs_diff_eq!(yes.mean, 15_f64);
+			assert_abs_diff_eq!(yes.variance, 25_f64, epsilon = 1e-6);
+			assert_abs_diff_eq!(no.mean, 55_f64);
+		},
+		other => panic!("bayes::tests::when_continuous_feature_then_normal_distribution_per_class expected a Gaussian, got {:?}", other),
+	}
+	assert!(bayes.probability(&records[0]) > 0.99_f64);
+	assert!(bayes.probability(&records[4]) < 0.01_f64);
+	// (1 + 1) / (3 + 2) of survivors and (0 + 1) / (2 + 2) of the others are missing their age, with priors 4 / 7 and 3 / 7
+	let expected = (4_f64 / 7_f64 * 0.4_f64) / (4_f64 / 7_f64 * 0.4_f64 + 3_f64 / 7_f64 * 0.25_f64);
+	assert_abs_diff_eq!(bayes.probability(&records[2]), expected, epsilon = 1e-12);
+}
+
+#[test]
+fn when_alpha_not_positive_then_error() {
+	let records = passengers(&["1,1,1,A,female,30,0,0,T,10,,S"]);
+	assert!(NaiveBayes::fit(&records, &Feature::ALL, &BayesSettings { alpha: 0_f64 }).is_err());
+}
@@ -0,0 +1,418 @@
+use std::collections::BTreeMap;
+use std::ops::{Add, Div, Mul, Sub};
+
+use serde::{Deserialize, Serialize};
+
+use super::{metrics, quick_convert, random::Rng, split_holdout_shuffled, tree::{category, Split}, BinaryClass, Classifier, Labelled};
+use crate::{Feature, FeatureValue, PassengerFeatures};
+
+#[cfg(test)]
+mod tests;
+
+// A split must lower the squared error of the residuals by more than this to be kept.
+const MIN_GAIN: f64 = 1e-12;
+
+// Keeps the Newton step finite in leaves whose records are all predicted with near certainty.
+const MIN_HESSIAN: f64 = 1e-12;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BoostingSettings {
+	pub rounds: usize,
+	// Every tree's contribution is multiplied by the shrinkage before it is added
+	pub shrinkage: f64,
+	pub max_depth: usize,
+	pub min_samples_leaf: usize,
+	// Share of the training records, drawn without replacement, that each tree is fitted on
+	pub subsample: f64,
+	// Stop after this many rounds without a lower validation log loss, None trains every round
+	pub early_stopping_rounds: Option<usize>,
+	// Share of the training records held out to measure the validation log loss for early stopping
+	pub validation_fraction: f64
+}
+
+impl Default for BoostingSettings {
+	fn default() -> Self {
+		BoostingSettings {
+			rounds: 100,
+			shrinkage: 0.1_f64,
+			max_depth: 3,
+			min_samples_leaf: 1,
+			subsample: 1_f64,
+			early_stopping_rounds: Some(10),
+			validation_fraction: 0.1_f64
+		}
+	}
+}
+
+impl BoostingSettings {
+	pub fn validate(&self) -> Result<(), String> {
+		if self.rounds == 0 || self.max_depth == 0 || self.min_samples_leaf == 0 || self.early_stopping_rounds == Some(0) {
+			return Err("BoostingSettings::validate rounds, max_depth, min_samples_leaf and early_stopping_rounds should be at least 1".to_string())
+		}
+		if self.shrinkage <= 0_f64 || self.subsample <= 0_f64 || self.subsample > 1_f64 {
+			return Err(format!("BoostingSettings::validate shrinkage {} should be positive and subsample {} between 0 and 1", self.shrinkage, self.subsample))
+		}
+		if self.validation_fraction <= 0_f64 || self.validation_fraction >= 1_f64 {
+			return Err(format!("BoostingSettings::validate validation_fraction {} should be strictly between 0 and 1", self.validation_fraction))
+		}
+		Ok(())
+	}
+}
+
+// The same logistic function as LogisticBinaryClassificationTestable::logistic, whose argument is the negated log-odds of BinaryClass::Yes.
+fn probability(score: f64) -> f64 {
+	1_f64.div((-score).exp().add(1_f64))
+}
+
+fn target(label: &BinaryClass) -> f64 {
+	match label {
+		BinaryClass::Yes => 1_f64,
+		BinaryClass::No => 0_f64,
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegressionNode {
+	// value is added to the log-odds of BinaryClass::Yes
+	Leaf { count: usize, value: f64 },
+	// gain is the drop in the squared error of the residuals that the split achieved
+	Branch { count: usize, gain: f64, split: Split, left: Box<RegressionNode>, right: Box<RegressionNode> }
+}
+
+impl RegressionNode {
+	pub fn get_count(&self) -> usize {
+		match self {
+			RegressionNode::Leaf { count, .. } => *count,
+			RegressionNode::Branch { count, .. } => *count,
+		}
+	}
+
+	pub fn get_depth(&self) -> usize {
+		match self {
+			RegressionNode::Leaf { .. } => 0,
+			RegressionNode::Branch { left, right, .. } => 1_usize.add(left.get_depth().max(right.get_depth())),
+		}
+	}
+
+	fn value<R: PassengerFeatures>(&self, record: &R) -> f64 {
+		match self {
+			RegressionNode::Leaf { value, .. } => *value,
+			RegressionNode::Branch { split, left, right, .. } => {
+				let goes_left = split.goes_left(&record.get_feature(split.get_feature())).unwrap_or(left.get_count() >= right.get_count());
+				if goes_left {
+					left.value(record)
+				} else {
+					right.value(record)
+				}
+			},
+		}
+	}
+
+	fn add_gains(&self, gains: &mut [(Feature, f64)]) {
+		if let RegressionNode::Branch { gain, split, left, right, .. } = self {
+			for (feature, total) in gains.iter_mut() {
+				if feature == split.get_feature() {
+					*total = total.add(gain);
+				}
+			}
+			left.add_gains(gains);
+			right.add_gains(gains);
+		}
+	}
+}
+
+// Record count and residual sum of a group of records
+type Sums = (usize, f64);
+
+fn add_residual(sums: &mut Sums, residual: &f64) {
+	sums.0 = sums.0.add(1);
+	sums.1 = sums.1.add(residual);
+}
+
+// Drop in the squared error when the parent's records are predicted by the mean residual of each side instead of
+// their common mean, or None when a side would hold fewer than min_samples_leaf records.
+fn split_gain(settings: &BoostingSettings, parent: Sums, left: Sums) -> Option<f64> {
+	let right = (parent.0.sub(left.0), parent.1.sub(left.1));
+	if left.0 < settings.min_samples_leaf || right.0 < settings.min_samples_leaf {
+		return None
+	}
+	let explained = |sums: Sums| sums.1.mul(sums.1).div(quick_convert(&sums.0));
+	Some(explained(left).add(explained(right)).sub(explained(parent)))
+}
+
+// Sweeps the thresholds between consecutive distinct numbers, trying missing numbers on either side.
+fn best_numeric_split(settings: &BoostingSettings, feature: &Feature, values: &[FeatureValue], residuals: &[f64]) -> Option<(f64, Split)> {
+	let mut parent = (0_usize, 0_f64);
+	let mut numbers = Vec::new();
+	let mut missing = (0_usize, 0_f64);
+	for (value, residual) in values.iter().zip(residuals) {
+		add_residual(&mut parent, residual);
+		match value {
+			FeatureValue::Number(number) => numbers.push((*number, *residual)),
+			_ => add_residual(&mut missing, residual),
+		}
+	}
+	numbers.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+	let mut best: Option<(f64, Split)> = None;
+	let mut below = (0_usize, 0_f64);
+	for index in 0..numbers.len().saturating_sub(1) {
+		add_residual(&mut below, &numbers[index].1);
+		if numbers[index].0 >= numbers[index.add(1)].0 {
+			continue
+		}
+		let threshold = numbers[index].0.add(numbers[index.add(1)].0).div(2_f64);
+		for missing_left in [false, true].iter() {
+			let left = if *missing_left { (below.0.add(missing.0), below.1.add(missing.1)) } else { below };
+			if let Some(gain) = split_gain(settings, parent, left) {
+				if best.as_ref().is_none_or(|(best_gain, _split)| gain > *best_gain) {
+					best = Some((gain, Split::Numeric { feature: *feature, threshold, missing_left: *missing_left }));
+				}
+			}
+		}
+	}
+	best
+}
+
+// Orders the categories by their mean residual and sweeps the cut points of that order, which finds the best
+// grouping of categories into two sides for a squared error.
+fn best_categorical_split(settings: &BoostingSettings, feature: &Feature, values: &[FeatureValue], residuals: &[f64]) -> Option<(f64, Split)> {
+	let mut parent = (0_usize, 0_f64);
+	let mut sums: BTreeMap<usize, Sums> = BTreeMap::new();
+	for (value, residual) in values.iter().zip(residuals) {
+		add_residual(&mut parent, residual);
+		add_residual(sums.entry(category(value)?).or_insert((0, 0_f64)), residual);
+	}
+	let mut categories: Vec<(usize, Sums)> = sums.into_iter().collect();
+	categories.sort_by(|a, b| {
+		let mean = |sums: &Sums| sums.1.div(quick_convert(&sums.0));
+		mean(&a.1).partial_cmp(&mean(&b.1)).unwrap_or(std::cmp::Ordering::Equal).then(a.0.cmp(&b.0))
+	});
+
+	let mut best: Option<(f64, Split)> = None;
+	let mut left = (0_usize, 0_f64);
+	for cut in 1..categories.len() {
+		let sums = categories[cut.sub(1)].1;
+		left = (left.0.add(sums.0), left.1.add(sums.1));
+		if let Some(gain) = split_gain(settings, parent, left) {
+			if best.as_ref().is_none_or(|(best_gain, _split)| gain > *best_gain) {
+				let mut left_categories: Vec<usize> = categories[..cut].iter().map(|(category, _sums)| *category).collect();
+				let mut right_categories: Vec<usize> = categories[cut..].iter().map(|(category, _sums)| *category).collect();
+				left_categories.sort_unstable();
+				right_categories.sort_unstable();
+				best = Some((gain, Split::Categorical { feature: *feature, left: left_categories, right: right_categories }));
+			}
+		}
+	}
+	best
+}
+
+// Grows a regression tree on the residuals of the records at indices. Splits minimise the squared error of the
+// residuals and every leaf takes one Newton step on the log loss: the sum of its residuals over the sum of p (1 - p).
+fn grow<R: PassengerFeatures>(records: &[R], indices: &[usize], residuals: &[f64], hessians: &[f64], features: &[Feature], settings: &BoostingSettings, depth: usize) -> RegressionNode {
+	let node_residuals: Vec<f64> = indices.iter().map(|index| residuals[*index]).collect();
+	let leaf = || {
+		let hessian = indices.iter().fold(0_f64, |sum, index| sum.add(hessians[*index]));
+		let residual = node_residuals.iter().fold(0_f64, |sum, residual| sum.add(residual));
+		RegressionNode::Leaf { count: indices.len(), value: residual.div(hessian.max(MIN_HESSIAN)) }
+	};
+	if depth >= settings.max_depth || indices.len() < 2_usize.mul(settings.min_samples_leaf) {
+		return leaf()
+	}
+
+	let mut best: Option<(f64, Split)> = None;
+	for feature in features {
+		let values: Vec<FeatureValue> = indices.iter().map(|index| records[*index].get_feature(feature)).collect();
+		let numeric = values.iter().any(|value| matches!(value, FeatureValue::Number(_)));
+		let candidate = if numeric {
+			best_numeric_split(settings, feature, &values, &node_residuals)
+		} else {
+			best_categorical_split(settings, feature, &values, &node_residuals)
+		};
+		if let Some((gain, split)) = candidate {
+			if gain > MIN_GAIN && best.as_ref().is_none_or(|(best_gain, _split)| gain > *best_gain) {
+				best = Some((gain, split));
+			}
+		}
+	}
+
+	match best {
+		None => leaf(),
+		Some((gain, split)) => {
+			let (left, right): (Vec<usize>, Vec<usize>) = indices.iter()
+				.partition(|index| split.goes_left(&records[**index].get_feature(split.get_feature())).unwrap_or(true));
+			RegressionNode::Branch {
+				count: indices.len(),
+				gain,
+				left: Box::new(grow(records, &left, residuals, hessians, features, settings, depth.add(1))),
+				right: Box::new(grow(records, &right, residuals, hessians, features, settings, depth.add(1))),
+				split,
+			}
+		},
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct GradientBoosting {
+	features: Vec<Feature>,
+	settings: BoostingSettings,
+	seed: u64,
+	// Log-odds of BinaryClass::Yes among the training records, the score before any tree
+	base_score: f64,
+	trees: Vec<RegressionNode>,
+	// Validation log loss after the last kept round, when early stopping held records out
+	validation_loss: Option<f64>,
+	// Share of the total split gain of the kept trees that went to each feature
+	importances: Vec<(Feature, f64)>
+}
+
+impl GradientBoosting {
+	// Gradient boosting on the log loss: every round fits a shallow regression tree to the residuals y - p of the
+	// current scores, the negative gradient of the log loss, on a subsample of the records, and adds it with shrinkage.
+	// With early stopping, a share of the records is held out and only the rounds up to the lowest validation loss are kept.
+	pub fn fit<R: PassengerFeatures + Labelled>(records: &[R], features: &[Feature], settings: &BoostingSettings, seed: u64) -> Result<GradientBoosting, String> {
+		if records.is_empty() || features.is_empty() {
+			return Err("GradientBoosting::fit needs at least one record and feature".to_string())
+		}
+		settings.validate()?;
+		let mut rng = Rng::new(seed);
+		let indices: Vec<usize> = (0..records.len()).collect();
+		let (training, validation) = match settings.early_stopping_rounds {
+			Some(_rounds) => split_holdout_shuffled(indices, &settings.validation_fraction, &mut rng)?,
+			None => (indices, Vec::new()),
+		};
+		let labels: Vec<BinaryClass> = records.iter().map(|record| record.label()).collect();
+
+		let yes = training.iter().filter(|index| labels[**index] == BinaryClass::Yes).count();
+		let rate = quick_convert(&yes).div(quick_convert(&training.len())).clamp(MIN_HESSIAN, 1_f64.sub(MIN_HESSIAN));
+		let base_score = rate.div(1_f64.sub(rate)).ln();
+		let mut scores = vec![base_score; records.len()];
+		let sample_size = (quick_convert(&training.len()).mul(settings.subsample).round() as usize).max(1);
+
+		let mut trees = Vec::new();
+		let mut best: Option<(f64, usize)> = None;
+		for round in 0..settings.rounds {
+			let mut residuals = vec![0_f64; records.len()];
+			let mut hessians = vec![0_f64; records.len()];
+			for index in &training {
+				let p = probability(scores[*index]);
+				residuals[*index] = target(&labels[*index]).sub(p);
+				hessians[*index] = p.mul(1_f64.sub(p));
+			}
+			let mut sample = training.clone();
+			if sample_size < sample.len() {
+				rng.shuffle(&mut sample);
+				sample.truncate(sample_size);
+			}
+
+			let tree = grow(records, &sample, &residuals, &hessians, features, settings, 0);
+			for (score, record) in scores.iter_mut().zip(records) {
+				*score = score.add(settings.shrinkage.mul(tree.value(record)));
+			}
+			trees.push(tree);
+
+			if let Some(patience) = settings.early_stopping_rounds {
+				let validation_scores: Vec<(f64, BinaryClass)> = validation.iter().map(|index| (probability(scores[*index]), labels[*index])).collect();
+				let loss = metrics::log_loss(&validation_scores)?;
+				if best.is_none_or(|(best_loss, _rounds)| loss < best_loss) {
+					best = Some((loss, round.add(1)));
+				} else if best.is_some_and(|(_loss, rounds)| round.add(1).sub(rounds) >= patience) {
+					break
+				}
+			}
+		}
+		let fitted_rounds = trees.len();
+		if let Some((_loss, rounds)) = best {
+			trees.truncate(rounds);
+		}
+
+		let mut importances: Vec<(Feature, f64)> = features.iter().map(|feature| (*feature, 0_f64)).collect();
+		for tree in &trees {
+			tree.add_gains(&mut importances);
+		}
+		let total_gain = importances.iter().fold(0_f64, |sum, (_feature, gain)| sum.add(gain));
+		if total_gain > 0_f64 {
+			for (_feature, gain) in importances.iter_mut() {
+				*gain = gain.div(total_gain);
+			}
+		}
+
+		let boosting = GradientBoosting {
+			features: features.to_vec(),
+			settings: settings.clone(),
+			seed,
+			base_score,
+			trees,
+			validation_loss: best.map(|(loss, _rounds)| loss),
+			importances
+		};
+		boosting.print_report(fitted_rounds, training.len(), validation.len());
+		Ok(boosting)
+	}
+
+	fn print_report(&self, fitted_rounds: usize, training: usize, validation: usize) {
+		eprintln!("GradientBoosting::fit Kept {} of {} rounds fitted on {} records with shrinkage {}", self.trees.len(), fitted_rounds, training, self.settings.shrinkage);
+		match self.validation_loss {
+			Some(validation_loss) => eprintln!("GradientBoosting::fit Validation log loss on {} held out records: {}", validation, validation_loss),
+			None => eprintln!("GradientBoosting::fit Early stopping is off, so no records were held out"),
+		}
+		let mut importances = self.importances.clone();
+		importances.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+		eprintln!("{:>20}  share of split gain", "feature");
+		for (feature, importance) in importances {
+			eprintln!("{:>20}  {:.4}", feature.get_name(), importance);
+		}
+	}
+
+	pub fn get_base_score(&self) -> &f64 {
+		&self.base_score
+	}
+
+	pub fn get_settings(&self) -> &BoostingSettings {
+		&self.settings
+	}
+
+	pub fn get_seed(&self) -> &u64 {
+		&self.seed
+	}
+
+	pub fn get_shrinkage(&self) -> &f64 {
+		&self.settings.shrinkage
+	}
+
+	pub fn get_trees(&self) -> &Vec<RegressionNode> {
+		&self.trees
+	}
+
+	pub fn get_validation_loss(&self) -> &Option<f64> {
+		&self.validation_loss
+	}
+
+	pub fn get_importances(&self) -> &Vec<(Feature, f64)> {
+		&self.importances
+	}
+
+	// Log-odds of BinaryClass::Yes
+	pub fn decision_function<R: PassengerFeatures>(&self, record: &R) -> f64 {
+		self.trees.iter().fold(self.base_score, |score, tree| score.add(self.settings.shrinkage.mul(tree.value(record))))
+	}
+
+	// Probability of BinaryClass::Yes
+	pub fn probability<R: PassengerFeatures>(&self, record: &R) -> f64 {
+		probability(self.decision_function(record))
+	}
+}
+
+// fit boosts again on the records, over the features and with the settings and seed it was fitted with.
+impl<R: PassengerFeatures + Labelled> Classifier<R> for GradientBoosting {
+	fn fit(&mut self, records: &[R]) -> Result<(), String> {
+		*self = GradientBoosting::fit(records, &self.features, &self.settings, self.seed)?;
+		Ok(())
+	}
+
+	fn predict_proba(&self, record: &R) -> Result<f64, String> {
+		Ok(self.probability(record))
+	}
+}
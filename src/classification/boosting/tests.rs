@@ -0,0 +1,69 @@
+use super::*;
+use crate::test_support::passengers;
+use crate::TrainingPassenger;
+
+// Survival follows sex, except for one passenger in every five whose answer is flipped.
+fn noisy_passengers(count: u64) -> Vec<TrainingPassenger> {
+	let rows: Vec<String> = (1..=count).map(|id| {
+		let female = id % 2 == 0;
+		let survived = female != (id % 5 == 0);
+		format!("{},{},{},P{},{},{},0,0,T,{},,S", id, survived as u8, 1 + id % 3, id, if female { "female" } else { "male" }, 10 + (id * 7) % 60, 5 + (id * 13) % 50)
+	}).collect();
+	passengers(&rows.iter().map(|row| row.as_str()).collect::<Vec<&str>>())
+}
+
+#[test]
+fn when_one_round_then_leaves_take_newton_step_from_base_rate() {
+	let records = passengers(&[
+		"1,1,1,A,female,30,0,0,T,10,,S",
+		"2,1,3,B,female,40,0,0,T,10,,S",
+		"3,1,1,C,female,30,0,0,T,10,,S",
+		"4,0,3,D,male,40,0,0,T,10,,S",
+	]);
+	let settings = BoostingSettings { rounds: 1, max_depth: 1, early_stopping_rounds: None, ..BoostingSettings::default() };
+	let boosting = GradientBoosting::fit(&records, &[Feature::Sex], &settings, 1_u64).unwrap();
+
+	assert_abs_diff_eq!(*boosting.get_base_score(), 3_f64.ln(), epsilon = 1e-12);
+	// Every score starts at p = 0.75, so a female leaf moves by 0.25 / (0.75 * 0.25) and a male leaf by -0.75 / (0.75 * 0.25)
+	assert_abs_diff_eq!(boosting.decision_function(&records[0]), 3_f64.ln() + 0.1_f64 * 4_f64 / 3_f64, epsilon = 1e-12);
+	assert_abs_diff_eq!(boosting.decision_function(&records[3]), 3_f64.ln() - 0.1_f64 * 4_f64, epsilon = 1e-12);
+	assert_eq!(*boosting.get_importances(), vec![(Feature::Sex, 1_f64)]);
+}
+
+#[test]
+fn when_trained_on_noisy_labels_then_probabilities_follow_sex() {
+	let records = noisy_passengers(60);
+	let settings = BoostingSettings { rounds: 50, subsample: 0.8_f64, early_stopping_rounds: None, ..BoostingSettings::default() };
+	let boosting = GradientBoosting::fit(&records, &Feature::ALL, &settings, 3_u64).unwrap();
+
+	assert_eq!(boosting.get_trees().len(), 50);
+	assert!(boosting.get_trees().iter().all(|tree| tree.get_depth() <= 3));
+	let (female, male) = (&records[1], &records[0]);
+	assert!(boosting.probability(female) > 0.5_f64);
+	assert!(boosting.probability(male) < 0.5_f64);
+	let importance_sum = boosting.get_importances().iter().fold(0_f64, |sum, (_feature, importance)| sum + importance);
+	assert_abs_diff_eq!(importance_sum, 1_f64, epsilon = 1e-9);
+	assert_eq!(boosting, GradientBoosting::fit(&records, &Feature::ALL, &settings, 3_u64).unwrap());
+}
+
+#[test]
+fn when_validation_loss_stops_improving_then_later_rounds_dropped() {
+	let records = noisy_passengers(60);
+	let settings = BoostingSettings { rounds: 500, shrinkage: 0.5_f64, max_depth: 4, early_stopping_rounds: Some(5), validation_fraction: 0.25_f64, ..BoostingSettings::default() };
+	let boosting = GradientBoosting::fit(&records, &Feature::ALL, &settings, 3_u64).unwrap();
+
+	assert!(boosting.get_trees().len() < 500);
+	assert!(boosting.get_validation_loss().is_some());
+}
+
+#[test]
+fn when_settings_out_of_range_then_error() {
+	let records = noisy_passengers(10);
+	for settings in [
+		BoostingSettings { subsample: 0_f64, ..BoostingSettings::default() },
+		BoostingSettings { shrinkage: -0.1_f64, ..BoostingSettings::default() },
+		BoostingSettings { max_depth: 0, ..BoostingSettings::default() },
+	].iter() {
+		assert!(GradientBoosting::fit(&records, &Feature::ALL, settings, 1_u64).is_err());
+	}
+}
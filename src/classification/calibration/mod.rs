@@ -0,0 +1,224 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::BinaryClass;
+
+#[cfg(test)]
+mod tests;
+
+// Probabilities are kept this far away from 0 and 1 before taking their logit.
+const PROBABILITY_EPSILON: f64 = 1e-15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalibrationMethod {
+	Platt,
+	Isotonic
+}
+
+impl FromStr for CalibrationMethod {
+	type Err = String;
+	
+	fn from_str(name: &str) -> Result<CalibrationMethod, String> {
+		match name {
+			"platt" => Ok(CalibrationMethod::Platt),
+			"isotonic" => Ok(CalibrationMethod::Isotonic),
+			_ => Err(format!("CalibrationMethod::from_str unknown method {}, expected one of platt, isotonic", name)),
+		}
+	}
+}
+
+impl fmt::Display for CalibrationMethod {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			CalibrationMethod::Platt => write!(f, "platt"),
+			CalibrationMethod::Isotonic => write!(f, "isotonic"),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct IsotonicBlock {
+	pub lower: f64,
+	pub upper: f64,
+	pub value: f64
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Calibrator {
+	// calibrated = 1 / (1 + exp(a * logit(probability) + b)), the same form as LogisticBinaryClassificationTestable::logistic
+	Platt { a: f64, b: f64 },
+	// Non-decreasing step function over probability, interpolated linearly between blocks
+	Isotonic { blocks: Vec<IsotonicBlock> }
+}
+
+fn logit(probability: f64) -> f64 {
+	let clamped = probability.max(PROBABILITY_EPSILON).min(1_f64.sub(PROBABILITY_EPSILON));
+	clamped.div(1_f64.sub(clamped)).ln()
+}
+
+fn platt_probability(a: f64, b: f64, score: f64) -> f64 {
+	1_f64.div(a.mul(score).add(b).exp().add(1_f64))
+}
+
+impl Calibrator {
+	pub fn fit(method: &CalibrationMethod, scores: &[(f64, BinaryClass)]) -> Result<Calibrator, String> {
+		if scores.is_empty() {
+			return Err("Calibrator::fit cannot calibrate without scores".to_string())
+		}
+		if scores.iter().any(|(probability, _answer)| probability.is_nan()) {
+			return Err("Calibrator::fit found a score which is not a number".to_string())
+		}
+		match method {
+			CalibrationMethod::Platt => Calibrator::fit_platt(scores),
+			CalibrationMethod::Isotonic => Ok(Calibrator::fit_isotonic(scores)),
+		}
+	}
+	
+	// Newton's method with backtracking on Platt's regularized targets, following Lin, Lin and Weng (2007).
+	fn fit_platt(scores: &[(f64, BinaryClass)]) -> Result<Calibrator, String> {
+		let positives = scores.iter().filter(|(_probability, answer)| *answer == BinaryClass::Yes).count() as f64;
+		let negatives = (scores.len() as f64).sub(positives);
+		let high_target = positives.add(1_f64).div(positives.add(2_f64));
+		let low_target = 1_f64.div(negatives.add(2_f64));
+		
+		let data: Vec<(f64, f64)> = scores.iter().map(|(probability, answer)| {
+			match answer {
+				BinaryClass::Yes => (logit(*probability), high_target),
+				BinaryClass::No => (logit(*probability), low_target),
+			}
+		}).collect();
+		
+		let objective = |a: f64, b: f64| -> f64 {
+			let mut value = 0_f64;
+			for (score, target) in &data {
+				let z = a.mul(score).add(b);
+				// -t*ln(p) - (1-t)*ln(1-p) with p = 1 / (1 + e^z), written to avoid overflow
+				if z >= 0_f64 {
+					value = value.add(target.mul(z)).add((-z).exp().ln_1p());
+				} else {
+					value = value.add((target.sub(1_f64)).mul(z)).add(z.exp().ln_1p());
+				}
+			}
+			value
+		};
+		
+		// Start from the identity mapping logit -> probability, i.e. a = -1, b = 0.
+		let mut a = -1_f64;
+		let mut b = 0_f64;
+		let mut value = objective(a, b);
+		for _iteration in 0..100 {
+			let (mut gradient_a, mut gradient_b) = (0_f64, 0_f64);
+			let (mut hessian_aa, mut hessian_ab, mut hessian_bb) = (1e-12_f64, 0_f64, 1e-12_f64);
+			for (score, target) in &data {
+				let probability = platt_probability(a, b, *score);
+				let residual = target.sub(probability);
+				let weight = probability.mul(1_f64.sub(probability));
+				gradient_a = gradient_a.add(score.mul(residual));
+				gradient_b = gradient_b.add(residual);
+				hessian_aa = hessian_aa.add(score.mul(score).mul(weight));
+				hessian_ab = hessian_ab.add(score.mul(weight));
+				hessian_bb = hessian_bb.add(weight);
+			}
+			if gradient_a.abs() < 1e-10 && gradient_b.abs() < 1e-10 {
+				break;
+			}
+			
+			let determinant = hessian_aa.mul(hessian_bb).sub(hessian_ab.mul(hessian_ab));
+			let step_a = -(hessian_bb.mul(gradient_a).sub(hessian_ab.mul(gradient_b))).div(determinant);
+			let step_b = -(hessian_aa.mul(gradient_b).sub(hessian_ab.mul(gradient_a))).div(determinant);
+			let descent = gradient_a.mul(step_a).add(gradient_b.mul(step_b));
+			
+			let mut step_size = 1_f64;
+			let mut improved = false;
+			while step_size >= 1e-10 {
+				let new_a = a.add(step_size.mul(step_a));
+				let new_b = b.add(step_size.mul(step_b));
+				let new_value = objective(new_a, new_b);
+				if new_value < value.add(1e-4_f64.mul(step_size).mul(descent)) {
+					a = new_a;
+					b = new_b;
+					value = new_value;
+					improved = true;
+					break;
+				}
+				step_size = step_size.div(2_f64);
+			}
+			if !improved {
+				break;
+			}
+		}
+		
+		if a.is_finite() && b.is_finite() {
+			Ok(Calibrator::Platt { a, b })
+		} else {
+			Err("Calibrator::fit_platt did not converge to finite parameters".to_string())
+		}
+	}
+	
+	// Pool adjacent violators over the scores sorted by probability.
+	fn fit_isotonic(scores: &[(f64, BinaryClass)]) -> Calibrator {
+		let mut sorted: Vec<(f64, f64)> = scores.iter().map(|(probability, answer)| {
+			match answer {
+				BinaryClass::Yes => (*probability, 1_f64),
+				BinaryClass::No => (*probability, 0_f64),
+			}
+		}).collect();
+		sorted.sort_by(|left, right| left.0.partial_cmp(&right.0).unwrap());
+		
+		// (lower, upper, sum of answers, count)
+		let mut pools: Vec<(f64, f64, f64, f64)> = Vec::new();
+		for (probability, answer) in sorted {
+			match pools.last_mut() {
+				// Equal probabilities must share a calibrated value
+				Some(last) if last.1 == probability => {
+					last.2 = last.2.add(answer);
+					last.3 = last.3.add(1_f64);
+				},
+				_ => pools.push((probability, probability, answer, 1_f64)),
+			}
+			while pools.len() > 1 {
+				let last = pools[pools.len() - 1];
+				let previous = pools[pools.len() - 2];
+				if previous.2.div(previous.3) < last.2.div(last.3) {
+					break;
+				}
+				pools.pop();
+				let merged = pools.last_mut().unwrap();
+				merged.1 = last.1;
+				merged.2 = merged.2.add(last.2);
+				merged.3 = merged.3.add(last.3);
+			}
+		}
+		
+		let blocks = pools.into_iter().map(|(lower, upper, sum, count)| IsotonicBlock { lower, upper, value: sum.div(count) }).collect();
+		Calibrator::Isotonic { blocks }
+	}
+	
+	pub fn calibrate(&self, probability: f64) -> f64 {
+		match self {
+			Calibrator::Platt { a, b } => platt_probability(*a, *b, logit(probability)),
+			Calibrator::Isotonic { blocks } => {
+				let index = blocks.partition_point(|block| block.upper < probability);
+				if index == blocks.len() {
+					return blocks[blocks.len() - 1].value
+				}
+				let block = &blocks[index];
+				if probability >= block.lower || index == 0 {
+					return block.value
+				}
+				let previous = &blocks[index - 1];
+				let position = probability.sub(previous.upper).div(block.lower.sub(previous.upper));
+				previous.value.add(position.mul(block.value.sub(previous.value)))
+			},
+		}
+	}
+	
+	pub fn calibrate_scores(&self, scores: &[(f64, BinaryClass)]) -> Vec<(f64, BinaryClass)> {
+		scores.iter().map(|(probability, answer)| (self.calibrate(*probability), *answer)).collect()
+	}
+}
@@ -0,0 +1,50 @@
+use super::*;
+
+fn scores() -> Vec<(f64, BinaryClass)> {
+	vec![
+		(0.1_f64, BinaryClass::No),
+		(0.2_f64, BinaryClass::Yes),
+		(0.3_f64, BinaryClass::No),
+		(0.4_f64, BinaryClass::No),
+		(0.6_f64, BinaryClass::Yes),
+		(0.7_f64, BinaryClass::No),
+		(0.8_f64, BinaryClass::Yes),
+		(0.9_f64, BinaryClass::Yes)
+	]
+}
+
+#[test]
+fn when_isotonic_then_violators_pooled() {
+	let calibrator = Calibrator::fit(&CalibrationMethod::Isotonic, &scores()).unwrap();
+	
+	assert_eq!(calibrator, Calibrator::Isotonic { blocks: vec![
+		IsotonicBlock { lower: 0.1_f64, upper: 0.1_f64, value: 0_f64 },
+		IsotonicBlock { lower: 0.2_f64, upper: 0.4_f64, value: 1_f64.div(3_f64) },
+		IsotonicBlock { lower: 0.6_f64, upper: 0.7_f64, value: 0.5_f64 },
+		IsotonicBlock { lower: 0.8_f64, upper: 0.9_f64, value: 1_f64 }
+	]});
+	assert_abs_diff_eq!(calibrator.calibrate(0.3_f64), 1_f64.div(3_f64));
+	assert_abs_diff_eq!(calibrator.calibrate(0.5_f64), 1_f64.div(3_f64).add(0.5_f64).div(2_f64));
+	assert_abs_diff_eq!(calibrator.calibrate(0.05_f64), 0_f64);
+	assert_abs_diff_eq!(calibrator.calibrate(0.95_f64), 1_f64);
+}
+
+#[test]
+fn when_platt_then_calibrated_probabilities_are_monotone_and_centred() {
+	let calibrator = Calibrator::fit(&CalibrationMethod::Platt, &scores()).unwrap();
+	
+	let mut previous = 0_f64;
+	for (probability, _answer) in scores() {
+		let calibrated = calibrator.calibrate(probability);
+		assert!(calibrated > previous);
+		previous = calibrated;
+	}
+	// At the maximum likelihood solution the mean prediction matches the mean (smoothed) target
+	let mean: f64 = scores().iter().map(|(probability, _answer)| calibrator.calibrate(*probability)).sum::<f64>().div(8_f64);
+	assert_abs_diff_eq!(mean, 0.5_f64, epsilon = 1e-6);
+}
+
+#[test]
+fn when_no_scores_then_calibration_fails() {
+	assert!(Calibrator::fit(&CalibrationMethod::Platt, &[]).is_err());
+}
@@ -0,0 +1,223 @@
+use std::ops::{Add, Div, Sub};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use super::{quick_convert, random::Rng, tree::{DecisionTree, TreeSettings}, BinaryClass, Classifier, Labelled};
+use crate::{Feature, FeatureValue, PassengerFeatures};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ForestSettings {
+	pub trees: usize,
+	// None uses every available core
+	pub threads: Option<usize>
+}
+
+impl Default for ForestSettings {
+	fn default() -> Self {
+		ForestSettings {
+			trees: 100,
+			threads: None
+		}
+	}
+}
+
+// A record with the value of one feature replaced, used to measure how much the forest relies on that feature.
+struct Permuted<'a, R> {
+	record: &'a R,
+	feature: Feature,
+	value: FeatureValue
+}
+
+impl<'a, R: PassengerFeatures> PassengerFeatures for Permuted<'a, R> {
+	fn get_passenger_id(&self) -> &u64 {
+		self.record.get_passenger_id()
+	}
+
+	fn get_feature(&self, feature: &Feature) -> FeatureValue {
+		if *feature == self.feature {
+			self.value.clone()
+		} else {
+			self.record.get_feature(feature)
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RandomForest {
+	features: Vec<Feature>,
+	tree_settings: TreeSettings,
+	settings: ForestSettings,
+	seed: u64,
+	trees: Vec<DecisionTree>,
+	// Share of training records, among those left out of at least one bootstrap sample, that the trees without them classify correctly
+	oob_accuracy: Option<f64>,
+	// Drop in out-of-bag accuracy when the values of a feature are shuffled between records
+	importances: Vec<(Feature, f64)>
+}
+
+fn threads(settings: &ForestSettings) -> usize {
+	settings.threads.unwrap_or_else(|| thread::available_parallelism().map(|threads| threads.get()).unwrap_or(1)).max(1)
+}
+
+// Bootstrap sample and tree of one member of the forest, both drawn from seed.
+fn fit_member<R: PassengerFeatures + Labelled>(records: &[R], features: &[Feature], settings: &TreeSettings, seed: u64) -> Result<(DecisionTree, Vec<bool>), String> {
+	let mut rng = Rng::new(seed);
+	let indices: Vec<usize> = (0..records.len()).map(|_draw| rng.below(records.len())).collect();
+	let mut in_bag = vec![false; records.len()];
+	for index in &indices {
+		in_bag[*index] = true;
+	}
+	Ok((DecisionTree::fit_sample(records, &indices, features, settings, rng.next_u64())?, in_bag))
+}
+
+// Mean out-of-bag probability of every record, or None for records that were in every bootstrap sample.
+fn oob_probabilities<R: PassengerFeatures>(trees: &[DecisionTree], in_bags: &[Vec<bool>], records: &[R]) -> Vec<Option<f64>> {
+	records.iter().enumerate().map(|(index, record)| {
+		let mut sum = 0_f64;
+		let mut count = 0_usize;
+		for (tree, in_bag) in trees.iter().zip(in_bags) {
+			if !in_bag[index] {
+				sum = sum.add(tree.probability(record));
+				count = count.add(1);
+			}
+		}
+		if count == 0 {
+			None
+		} else {
+			Some(sum.div(quick_convert(&count)))
+		}
+	}).collect()
+}
+
+fn accuracy(probabilities: &[Option<f64>], labels: &[BinaryClass]) -> Option<f64> {
+	let mut correct = 0_usize;
+	let mut covered = 0_usize;
+	for (probability, label) in probabilities.iter().zip(labels) {
+		if let Some(probability) = probability {
+			covered = covered.add(1);
+			if (*probability > 0.5_f64) == (*label == BinaryClass::Yes) {
+				correct = correct.add(1);
+			}
+		}
+	}
+	if covered == 0 {
+		None
+	} else {
+		Some(quick_convert(&correct).div(quick_convert(&covered)))
+	}
+}
+
+impl RandomForest {
+	// Bagging with feature subsampling: every tree is grown on its own bootstrap sample and considers
+	// tree_settings.max_features features per split, the square root of the feature count unless set.
+	// Trees are grown in parallel, each from a seed drawn up front, so the forest only depends on seed.
+	pub fn fit<R>(records: &[R], features: &[Feature], tree_settings: &TreeSettings, settings: &ForestSettings, seed: u64) -> Result<RandomForest, String>
+	where
+		R: PassengerFeatures + Labelled + Sync,
+	{
+		if records.is_empty() || features.is_empty() || settings.trees == 0 {
+			return Err("RandomForest::fit needs at least one record, feature and tree".to_string())
+		}
+		let mut member_settings = tree_settings.clone();
+		if member_settings.max_features.is_none() {
+			member_settings.max_features = Some((quick_convert(&features.len()).sqrt().round() as usize).max(1));
+		}
+
+		let mut rng = Rng::new(seed);
+		let seeds: Vec<u64> = (0..settings.trees).map(|_tree| rng.next_u64()).collect();
+		let chunk_size = seeds.len().add(threads(settings)).sub(1).div(threads(settings));
+		let members: Vec<Result<(DecisionTree, Vec<bool>), String>> = thread::scope(|scope| {
+			let handles: Vec<_> = seeds.chunks(chunk_size).map(|chunk| {
+				let member_settings = &member_settings;
+				scope.spawn(move || chunk.iter().map(|seed| fit_member(records, features, member_settings, *seed)).collect::<Vec<_>>())
+			}).collect();
+			handles.into_iter().flat_map(|handle| handle.join().unwrap_or_else(|_panic| vec![Err("RandomForest::fit a training thread panicked".to_string())])).collect()
+		});
+		let mut trees = Vec::new();
+		let mut in_bags = Vec::new();
+		for member in members {
+			let (tree, in_bag) = member?;
+			trees.push(tree);
+			in_bags.push(in_bag);
+		}
+
+		let labels: Vec<BinaryClass> = records.iter().map(|record| record.label()).collect();
+		let oob_accuracy = accuracy(&oob_probabilities(&trees, &in_bags, records), &labels);
+
+		let mut importances = Vec::new();
+		if let Some(baseline) = oob_accuracy {
+			for feature in features {
+				let mut order: Vec<usize> = (0..records.len()).collect();
+				rng.shuffle(&mut order);
+				let permuted: Vec<Permuted<R>> = records.iter().zip(&order).map(|(record, other)| Permuted {
+					record,
+					feature: *feature,
+					value: records[*other].get_feature(feature)
+				}).collect();
+				let permuted_accuracy = accuracy(&oob_probabilities(&trees, &in_bags, &permuted), &labels).unwrap_or(baseline);
+				importances.push((*feature, baseline.sub(permuted_accuracy)));
+			}
+		}
+
+		let forest = RandomForest {
+			features: features.to_vec(),
+			tree_settings: tree_settings.clone(),
+			settings: settings.clone(),
+			seed,
+			trees,
+			oob_accuracy,
+			importances
+		};
+		forest.print_report(settings, &member_settings);
+		Ok(forest)
+	}
+
+	fn print_report(&self, settings: &ForestSettings, tree_settings: &TreeSettings) {
+		eprintln!("RandomForest::fit Grew {} trees on {} threads with {} features per split", self.trees.len(), threads(settings), tree_settings.max_features.unwrap_or(0));
+		match self.oob_accuracy {
+			Some(oob_accuracy) => eprintln!("RandomForest::fit Out-of-bag accuracy: {}", oob_accuracy),
+			None => eprintln!("RandomForest::fit Every record was in every bootstrap sample, so there is no out-of-bag accuracy"),
+		}
+		let mut importances = self.importances.clone();
+		importances.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+		eprintln!("{:>20}  permutation importance", "feature");
+		for (feature, importance) in importances {
+			eprintln!("{:>20}  {:.4}", feature.get_name(), importance);
+		}
+	}
+
+	pub fn get_trees(&self) -> &Vec<DecisionTree> {
+		&self.trees
+	}
+
+	pub fn get_oob_accuracy(&self) -> &Option<f64> {
+		&self.oob_accuracy
+	}
+
+	pub fn get_importances(&self) -> &Vec<(Feature, f64)> {
+		&self.importances
+	}
+
+	// Mean probability of BinaryClass::Yes over the trees
+	pub fn probability<R: PassengerFeatures>(&self, record: &R) -> f64 {
+		let sum = self.trees.iter().fold(0_f64, |sum, tree| sum.add(tree.probability(record)));
+		sum.div(quick_convert(&self.trees.len()))
+	}
+}
+
+// fit grows the forest again on the records, with the features, settings and seed it was grown with.
+impl<R: PassengerFeatures + Labelled + Sync> Classifier<R> for RandomForest {
+	fn fit(&mut self, records: &[R]) -> Result<(), String> {
+		*self = RandomForest::fit(records, &self.features, &self.tree_settings, &self.settings, self.seed)?;
+		Ok(())
+	}
+
+	fn predict_proba(&self, record: &R) -> Result<f64, String> {
+		Ok(self.probability(record))
+	}
+}
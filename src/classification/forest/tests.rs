@@ -0,0 +1,43 @@
+use super::*;
+use crate::{test_support, TrainingPassenger};
+
+// Survival follows sex, except for one boy in every six passengers, and the ages carry no signal.
+fn passengers() -> Vec<TrainingPassenger> {
+	let rows: Vec<String> = (0..60).map(|id| {
+		let female = id % 2 == 0;
+		let survived = female || id % 6 == 1;
+		format!("{},{},{},N,{},{},0,0,T,{},,S", id, survived as u8, 1 + id % 3, if female { "female" } else { "male" }, 20 + (id * 7) % 30, 10 + id % 4)
+	}).collect();
+	test_support::passengers(&rows.iter().map(|row| row.as_str()).collect::<Vec<&str>>())
+}
+
+#[test]
+fn when_same_seed_then_same_forest_on_any_number_of_threads() {
+	let records = passengers();
+	let features = [Feature::Sex, Feature::Age, Feature::PassengerClass];
+	let one_thread = RandomForest::fit(&records, &features, &TreeSettings::default(), &ForestSettings { trees: 12, threads: Some(1) }, 5_u64).unwrap();
+	let three_threads = RandomForest::fit(&records, &features, &TreeSettings::default(), &ForestSettings { trees: 12, threads: Some(3) }, 5_u64).unwrap();
+	let other_seed = RandomForest::fit(&records, &features, &TreeSettings::default(), &ForestSettings { trees: 12, threads: Some(3) }, 6_u64).unwrap();
+
+	assert_eq!(one_thread.get_trees().len(), 12);
+	// The forests only differ in the threads they were grown on.
+	assert_eq!(one_thread.get_trees(), three_threads.get_trees());
+	assert_eq!(one_thread.get_oob_accuracy(), three_threads.get_oob_accuracy());
+	assert_eq!(one_thread.get_importances(), three_threads.get_importances());
+	assert_ne!(one_thread.get_trees(), other_seed.get_trees());
+}
+
+#[test]
+fn when_sex_decides_survival_then_sex_most_important() {
+	let records = passengers();
+	let features = [Feature::Sex, Feature::Age, Feature::PassengerClass];
+	let forest = RandomForest::fit(&records, &features, &TreeSettings { max_depth: Some(1), ..TreeSettings::default() }, &ForestSettings { trees: 30, threads: None }, 1_u64).unwrap();
+
+	let oob_accuracy = forest.get_oob_accuracy().unwrap();
+	assert!(oob_accuracy > 0.8_f64);
+	let (most_important, importance) = forest.get_importances().iter().cloned().fold((Feature::Age, f64::MIN), |best, next| if next.1 > best.1 { next } else { best });
+	assert_eq!(most_important, Feature::Sex);
+	assert!(importance > 0.2_f64);
+	assert!(forest.probability(&records[0]) > 0.5_f64);
+	assert!(forest.probability(&records[3]) < 0.5_f64);
+}
@@ -0,0 +1,308 @@
+use std::fmt;
+use std::ops::{Add, Div, Sub};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{quick_convert, BinaryClass, Classifier, Labelled};
+use crate::{Feature, FeatureValue, PassengerFeatures};
+
+#[cfg(test)]
+mod tests;
+
+// Slack in the ball tree's pruning test, so rounding never drops a neighbour that brute force would keep.
+const PRUNING_SLACK: f64 = 1e-12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Weighting {
+	// Every neighbour has one vote
+	Uniform,
+	// Neighbours vote with the inverse of their distance, and exact matches outvote everything else
+	Distance
+}
+
+impl FromStr for Weighting {
+	type Err = String;
+
+	fn from_str(name: &str) -> Result<Weighting, String> {
+		match name {
+			"uniform" => Ok(Weighting::Uniform),
+			"distance" => Ok(Weighting::Distance),
+			_ => Err(format!("Weighting::from_str unknown weighting {}, expected one of uniform, distance", name)),
+		}
+	}
+}
+
+impl fmt::Display for Weighting {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Weighting::Uniform => write!(f, "uniform"),
+			Weighting::Distance => write!(f, "distance"),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NeighbourSearch {
+	// Measures the distance to every training record
+	Brute,
+	// Skips whole balls of training records that cannot hold a nearer neighbour
+	BallTree
+}
+
+impl FromStr for NeighbourSearch {
+	type Err = String;
+
+	fn from_str(name: &str) -> Result<NeighbourSearch, String> {
+		match name {
+			"brute" => Ok(NeighbourSearch::Brute),
+			"ball_tree" => Ok(NeighbourSearch::BallTree),
+			_ => Err(format!("NeighbourSearch::from_str unknown neighbour search {}, expected one of brute, ball_tree", name)),
+		}
+	}
+}
+
+impl fmt::Display for NeighbourSearch {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			NeighbourSearch::Brute => write!(f, "brute"),
+			NeighbourSearch::BallTree => write!(f, "ball_tree"),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KnnSettings {
+	pub neighbours: usize,
+	pub weighting: Weighting,
+	pub search: NeighbourSearch,
+	// A ball with at most this many training records is not split further
+	pub leaf_size: usize
+}
+
+impl Default for KnnSettings {
+	fn default() -> Self {
+		KnnSettings {
+			neighbours: 5,
+			weighting: Weighting::Uniform,
+			search: NeighbourSearch::BallTree,
+			leaf_size: 20
+		}
+	}
+}
+
+// Distance of one feature between two records, between 0 and 1. Numbers are scaled by the range seen in training and
+// capped at 1, every other pair of values is 0 apart when equal and 1 apart otherwise. A missing value is a value of
+// its own, which keeps the Gower distance a metric so that the ball tree can rely on the triangle inequality.
+fn feature_distance(range: &Option<(f64, f64)>, a: &FeatureValue, b: &FeatureValue) -> f64 {
+	match (a, b) {
+		(FeatureValue::Number(x), FeatureValue::Number(y)) => match range {
+			Some((low, high)) if high > low => x.sub(y).abs().div(high.sub(low)).min(1_f64),
+			_ => if x == y { 0_f64 } else { 1_f64 },
+		},
+		_ => if a == b { 0_f64 } else { 1_f64 },
+	}
+}
+
+// Gower distance: the mean over the features of their distance
+pub fn gower_distance(ranges: &[Option<(f64, f64)>], a: &[FeatureValue], b: &[FeatureValue]) -> f64 {
+	let sum = ranges.iter().zip(a.iter().zip(b)).fold(0_f64, |sum, (range, (a, b))| sum.add(feature_distance(range, a, b)));
+	sum.div(quick_convert(&ranges.len()))
+}
+
+// A ball around the training record pivot holding every record of the node within radius.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BallNode {
+	Leaf { pivot: usize, radius: f64, indices: Vec<usize> },
+	Branch { pivot: usize, radius: f64, left: Box<BallNode>, right: Box<BallNode> }
+}
+
+// The k nearest records found so far as (distance, index), nearest first
+struct Nearest {
+	k: usize,
+	found: Vec<(f64, usize)>
+}
+
+impl Nearest {
+	fn consider(&mut self, distance: f64, index: usize) {
+		let position = self.found.iter().position(|(other, other_index)| distance < *other || (distance == *other && index < *other_index)).unwrap_or(self.found.len());
+		if position < self.k {
+			self.found.insert(position, (distance, index));
+			self.found.truncate(self.k);
+		}
+	}
+
+	fn farthest(&self) -> Option<f64> {
+		if self.found.len() < self.k {
+			None
+		} else {
+			self.found.last().map(|(distance, _index)| *distance)
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NearestNeighbours {
+	features: Vec<Feature>,
+	settings: KnnSettings,
+	// Smallest and largest number of each feature among the training records, None for features without numbers
+	ranges: Vec<Option<(f64, f64)>>,
+	points: Vec<Vec<FeatureValue>>,
+	survived: Vec<bool>,
+	ball_tree: Option<BallNode>
+}
+
+impl NearestNeighbours {
+	// Stores the training records, and with NeighbourSearch::BallTree arranges them in a ball tree.
+	pub fn fit<R: PassengerFeatures + Labelled>(records: &[R], features: &[Feature], settings: &KnnSettings) -> Result<NearestNeighbours, String> {
+		if records.is_empty() || features.is_empty() {
+			return Err("NearestNeighbours::fit needs at least one record and feature".to_string())
+		}
+		if settings.neighbours == 0 || settings.leaf_size == 0 {
+			return Err("NearestNeighbours::fit neighbours and leaf_size should be at least 1".to_string())
+		}
+		let points: Vec<Vec<FeatureValue>> = records.iter().map(|record| features.iter().map(|feature| record.get_feature(feature)).collect()).collect();
+		let ranges = (0..features.len()).map(|column| {
+			points.iter().fold(None, |range: Option<(f64, f64)>, point| match point[column] {
+				FeatureValue::Number(number) => Some(range.map_or((number, number), |(low, high)| (low.min(number), high.max(number)))),
+				_ => range,
+			})
+		}).collect();
+		let mut model = NearestNeighbours {
+			features: features.to_vec(),
+			settings: settings.clone(),
+			ranges,
+			points,
+			survived: records.iter().map(|record| record.label() == BinaryClass::Yes).collect(),
+			ball_tree: None
+		};
+		if settings.search == NeighbourSearch::BallTree {
+			model.ball_tree = Some(model.build((0..records.len()).collect()));
+		}
+		eprintln!("NearestNeighbours::fit Stored {} records over {} features for {} nearest neighbours with {} weighting and {} search", records.len(), features.len(), settings.neighbours, settings.weighting, settings.search);
+		Ok(model)
+	}
+
+	fn distance(&self, a: &[FeatureValue], b: &[FeatureValue]) -> f64 {
+		gower_distance(&self.ranges, a, b)
+	}
+
+	fn farthest_from(&self, from: usize, indices: &[usize]) -> (usize, f64) {
+		indices.iter().fold((from, 0_f64), |(farthest, distance), index| {
+			let other = self.distance(&self.points[from], &self.points[*index]);
+			if other > distance { (*index, other) } else { (farthest, distance) }
+		})
+	}
+
+	// Splits the records between the two that lie farthest apart, each record joining the nearer of them.
+	fn build(&self, indices: Vec<usize>) -> BallNode {
+		let pivot = indices[0];
+		let (_farthest, radius) = self.farthest_from(pivot, &indices);
+		if indices.len() <= self.settings.leaf_size {
+			return BallNode::Leaf { pivot, radius, indices }
+		}
+		let (a, _distance) = self.farthest_from(pivot, &indices);
+		let (b, _distance) = self.farthest_from(a, &indices);
+		let (left, right): (Vec<usize>, Vec<usize>) = indices.iter().partition(|index| {
+			self.distance(&self.points[**index], &self.points[a]) <= self.distance(&self.points[**index], &self.points[b])
+		});
+		if left.is_empty() || right.is_empty() {
+			return BallNode::Leaf { pivot, radius, indices }
+		}
+		BallNode::Branch { pivot, radius, left: Box::new(self.build(left)), right: Box::new(self.build(right)) }
+	}
+
+	fn search(&self, node: &BallNode, query: &[FeatureValue], nearest: &mut Nearest) {
+		let (pivot, radius) = match node {
+			BallNode::Leaf { pivot, radius, .. } => (*pivot, *radius),
+			BallNode::Branch { pivot, radius, .. } => (*pivot, *radius),
+		};
+		// By the triangle inequality no record in the ball is nearer than this.
+		let closest = self.distance(query, &self.points[pivot]).sub(radius);
+		if nearest.farthest().is_some_and(|farthest| closest > farthest.add(PRUNING_SLACK)) {
+			return
+		}
+		match node {
+			BallNode::Leaf { indices, .. } => {
+				for index in indices {
+					nearest.consider(self.distance(query, &self.points[*index]), *index);
+				}
+			},
+			BallNode::Branch { left, right, .. } => {
+				let pivot_distance = |child: &BallNode| match child {
+					BallNode::Leaf { pivot, .. } => self.distance(query, &self.points[*pivot]),
+					BallNode::Branch { pivot, .. } => self.distance(query, &self.points[*pivot]),
+				};
+				if pivot_distance(left) <= pivot_distance(right) {
+					self.search(left, query, nearest);
+					self.search(right, query, nearest);
+				} else {
+					self.search(right, query, nearest);
+					self.search(left, query, nearest);
+				}
+			},
+		}
+	}
+
+	// The settings.neighbours nearest training records as (distance, index), nearest first, ties broken by the lower index
+	pub fn neighbours<R: PassengerFeatures>(&self, record: &R) -> Vec<(f64, usize)> {
+		let query: Vec<FeatureValue> = self.features.iter().map(|feature| record.get_feature(feature)).collect();
+		let mut nearest = Nearest { k: self.settings.neighbours, found: Vec::new() };
+		match &self.ball_tree {
+			Some(root) => self.search(root, &query, &mut nearest),
+			None => {
+				for (index, point) in self.points.iter().enumerate() {
+					nearest.consider(self.distance(&query, point), index);
+				}
+			},
+		}
+		nearest.found
+	}
+
+	pub fn get_features(&self) -> &Vec<Feature> {
+		&self.features
+	}
+
+	pub fn get_settings(&self) -> &KnnSettings {
+		&self.settings
+	}
+
+	pub fn get_ranges(&self) -> &Vec<Option<(f64, f64)>> {
+		&self.ranges
+	}
+
+	// Probability of BinaryClass::Yes: the weighted share of survivors among the nearest neighbours
+	pub fn probability<R: PassengerFeatures>(&self, record: &R) -> f64 {
+		let neighbours = self.neighbours(record);
+		let exact = neighbours.iter().any(|(distance, _index)| *distance == 0_f64);
+		let weight = |distance: f64| match self.settings.weighting {
+			Weighting::Uniform => 1_f64,
+			Weighting::Distance if exact => if distance == 0_f64 { 1_f64 } else { 0_f64 },
+			Weighting::Distance => 1_f64.div(distance),
+		};
+		let (mut yes, mut total) = (0_f64, 0_f64);
+		for (distance, index) in neighbours {
+			total = total.add(weight(distance));
+			if self.survived[index] {
+				yes = yes.add(weight(distance));
+			}
+		}
+		yes.div(total)
+	}
+}
+
+// fit stores the records instead, over the features and with the settings it was fitted with.
+impl<R: PassengerFeatures + Labelled> Classifier<R> for NearestNeighbours {
+	fn fit(&mut self, records: &[R]) -> Result<(), String> {
+		*self = NearestNeighbours::fit(records, &self.features, &self.settings)?;
+		Ok(())
+	}
+
+	fn predict_proba(&self, record: &R) -> Result<f64, String> {
+		Ok(self.probability(record))
+	}
+}
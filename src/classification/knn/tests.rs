@@ -0,0 +1,56 @@
+use super::*;
+use crate::test_support::passengers;
+use crate::TrainingPassenger;
+
+fn many_passengers(count: u64) -> Vec<TrainingPassenger> {
+	let rows: Vec<String> = (1..=count).map(|id| {
+		let age = if id % 7 == 0 { String::new() } else { ((id * 37) % 70).to_string() };
+		format!("{},{},{},P{},{},{},{},0,T,{},,{}", id, (id * 11) % 3 % 2, 1 + (id * 5) % 3, id, if id % 2 == 0 { "female" } else { "male" }, age, id % 4, (id * 53) % 200, ["S", "C", "Q", ""][(id % 4) as usize])
+	}).collect();
+	passengers(&rows.iter().map(|row| row.as_str()).collect::<Vec<&str>>())
+}
+
+#[test]
+fn when_mixed_values_then_gower_distance_averages_scaled_differences() {
+	let ranges = vec![None, Some((0_f64, 40_f64)), None];
+	let a = vec![FeatureValue::Category(1), FeatureValue::Number(10_f64), FeatureValue::Missing];
+	let b = vec![FeatureValue::Category(2), FeatureValue::Number(20_f64), FeatureValue::Missing];
+	let c = vec![FeatureValue::Category(1), FeatureValue::Missing, FeatureValue::Category(3)];
+
+	assert_abs_diff_eq!(gower_distance(&ranges, &a, &b), (1_f64 + 0.25_f64 + 0_f64) / 3_f64);
+	assert_abs_diff_eq!(gower_distance(&ranges, &a, &c), (0_f64 + 1_f64 + 1_f64) / 3_f64);
+	assert_abs_diff_eq!(gower_distance(&ranges, &a, &a), 0_f64);
+}
+
+#[test]
+fn when_ball_tree_then_same_neighbours_as_brute_force() {
+	let records = many_passengers(120);
+	let queries = many_passengers(150);
+	let features = [Feature::PassengerClass, Feature::Sex, Feature::Age, Feature::SiblingsSpouses, Feature::Fare, Feature::PortOfEmbarkation];
+	let settings = KnnSettings { neighbours: 7, leaf_size: 4, ..KnnSettings::default() };
+	let ball_tree = NearestNeighbours::fit(&records, &features, &settings).unwrap();
+	let brute = NearestNeighbours::fit(&records, &features, &KnnSettings { search: NeighbourSearch::Brute, ..settings }).unwrap();
+
+	for query in &queries {
+		assert_eq!(ball_tree.neighbours(query), brute.neighbours(query));
+	}
+}
+
+#[test]
+fn when_distance_weighting_then_exact_match_decides() {
+	let records = passengers(&[
+		"1,1,1,A,female,30,0,0,T,10,,S",
+		"2,0,1,B,female,31,0,0,T,10,,S",
+		"3,0,1,C,female,32,0,0,T,10,,S",
+		"4,0,3,D,male,60,0,0,T,90,,S",
+	]);
+	let features = [Feature::Sex, Feature::Age];
+	let uniform = NearestNeighbours::fit(&records, &features, &KnnSettings { neighbours: 3, ..KnnSettings::default() }).unwrap();
+	let weighted = NearestNeighbours::fit(&records, &features, &KnnSettings { neighbours: 3, weighting: Weighting::Distance, ..KnnSettings::default() }).unwrap();
+
+	assert_abs_diff_eq!(uniform.probability(&records[0]), 1_f64 / 3_f64);
+	assert_abs_diff_eq!(weighted.probability(&records[0]), 1_f64);
+	let between = passengers(&["5,0,1,E,female,31.5,0,0,T,10,,S"]);
+	// Ages 30, 31 and 32 lie 1.5, 0.5 and 0.5 years away on a range of 30 years, while the sexes agree.
+	assert_abs_diff_eq!(weighted.probability(&between[0]), (1_f64 / 1.5_f64) / (1_f64 / 1.5_f64 + 2_f64 / 0.5_f64), epsilon = 1e-12);
+}
@@ -0,0 +1,232 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::BinaryClass;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdMetric {
+	Accuracy,
+	F1,
+	#[serde(rename = "youden")]
+	YoudenJ
+}
+
+impl FromStr for ThresholdMetric {
+	type Err = String;
+	
+	fn from_str(name: &str) -> Result<ThresholdMetric, String> {
+		match name {
+			"accuracy" => Ok(ThresholdMetric::Accuracy),
+			"f1" => Ok(ThresholdMetric::F1),
+			"youden" => Ok(ThresholdMetric::YoudenJ),
+			_ => Err(format!("ThresholdMetric::from_str unknown metric {}, expected one of accuracy, f1, youden", name)),
+		}
+	}
+}
+
+impl fmt::Display for ThresholdMetric {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ThresholdMetric::Accuracy => write!(f, "accuracy"),
+			ThresholdMetric::F1 => write!(f, "f1"),
+			ThresholdMetric::YoudenJ => write!(f, "youden"),
+		}
+	}
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ConfusionMatrix {
+	pub true_positives: u64,
+	pub false_positives: u64,
+	pub true_negatives: u64,
+	pub false_negatives: u64
+}
+
+fn ratio(numerator: u64, denominator: u64) -> f64 {
+	if denominator == 0 {
+		0_f64
+	} else {
+		(numerator as f64).div(denominator as f64)
+	}
+}
+
+impl ConfusionMatrix {
+	// A score counts as a positive prediction when it is strictly greater than the threshold, as in classification::classify.
+	pub fn from_scores(scores: &[(f64, BinaryClass)], threshold: &f64) -> ConfusionMatrix {
+		let mut matrix = ConfusionMatrix::default();
+		for (probability, answer) in scores {
+			match (probability.gt(threshold), answer) {
+				(true, BinaryClass::Yes) => matrix.true_positives += 1,
+				(true, BinaryClass::No) => matrix.false_positives += 1,
+				(false, BinaryClass::No) => matrix.true_negatives += 1,
+				(false, BinaryClass::Yes) => matrix.false_negatives += 1,
+			}
+		}
+		matrix
+	}
+	
+	pub fn total(&self) -> u64 {
+		self.true_positives + self.false_positives + self.true_negatives + self.false_negatives
+	}
+	
+	pub fn accuracy(&self) -> f64 {
+		ratio(self.true_positives + self.true_negatives, self.total())
+	}
+	
+	pub fn precision(&self) -> f64 {
+		ratio(self.true_positives, self.true_positives + self.false_positives)
+	}
+	
+	pub fn recall(&self) -> f64 {
+		ratio(self.true_positives, self.true_positives + self.false_negatives)
+	}
+	
+	pub fn specificity(&self) -> f64 {
+		ratio(self.true_negatives, self.true_negatives + self.false_positives)
+	}
+	
+	pub fn f1(&self) -> f64 {
+		let precision = self.precision();
+		let recall = self.recall();
+		if precision.add(recall).eq(&0_f64) {
+			0_f64
+		} else {
+			2_f64.mul(precision).mul(recall).div(precision.add(recall))
+		}
+	}
+	
+	pub fn youden_j(&self) -> f64 {
+		self.recall().add(self.specificity()).sub(1_f64)
+	}
+	
+	pub fn score(&self, metric: &ThresholdMetric) -> f64 {
+		match metric {
+			ThresholdMetric::Accuracy => self.accuracy(),
+			ThresholdMetric::F1 => self.f1(),
+			ThresholdMetric::YoudenJ => self.youden_j(),
+		}
+	}
+}
+
+// Returns the threshold maximizing the metric, and the metric at that threshold.
+// Candidates are 0 and every distinct score; ties are broken towards the threshold closest to 0.5.
+pub fn best_threshold(scores: &[(f64, BinaryClass)], metric: &ThresholdMetric) -> Result<(f64, f64), String> {
+	if scores.is_empty() {
+		return Err("metrics::best_threshold cannot choose a threshold without scores".to_string())
+	}
+	
+	let mut candidates = vec![0_f64];
+	for (probability, _answer) in scores {
+		if probability.is_nan() {
+			return Err("metrics::best_threshold found a score which is not a number".to_string())
+		}
+		candidates.push(*probability);
+	}
+	candidates.sort_by(|a, b| a.partial_cmp(b).unwrap());
+	candidates.dedup();
+	
+	let mut best: Option<(f64, f64)> = None;
+	for candidate in candidates {
+		let score = ConfusionMatrix::from_scores(scores, &candidate).score(metric);
+		best = match best {
+			None => Some((candidate, score)),
+			Some((threshold, best_score)) => {
+				let closer = candidate.sub(0.5_f64).abs().lt(&threshold.sub(0.5_f64).abs());
+				if score.gt(&best_score) || (score.eq(&best_score) && closer) {
+					Some((candidate, score))
+				} else {
+					Some((threshold, best_score))
+				}
+			},
+		};
+	}
+	
+	match best {
+		Some(best) => Ok(best),
+		None => Err("metrics::best_threshold found no candidate thresholds".to_string()),
+	}
+}
+
+// Mean of -ln(p) over Yes and -ln(1 - p) over No, the same cost LogisticBinaryClassificationTrainable::cost averages.
+pub fn log_loss(scores: &[(f64, BinaryClass)]) -> Result<f64, String> {
+	if scores.is_empty() {
+		return Err("metrics::log_loss needs at least one score".to_string())
+	}
+	
+	let mut sum = 0_f64;
+	for (probability, answer) in scores {
+		let likelihood = match answer {
+			BinaryClass::Yes => *probability,
+			BinaryClass::No => 1_f64.sub(probability),
+		};
+		sum = sum.sub(likelihood.max(f64::MIN_POSITIVE).ln());
+	}
+	Ok(sum.div(scores.len() as f64))
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ReliabilityBin {
+	pub lower: f64,
+	pub upper: f64,
+	pub count: u64,
+	pub mean_probability: f64,
+	pub observed_rate: f64
+}
+
+// Splits [0, 1] into num_bins equal-width bins, and compares the mean predicted probability of each bin with the observed rate of Yes.
+// Empty bins are left out.
+pub fn reliability_bins(scores: &[(f64, BinaryClass)], num_bins: usize) -> Result<Vec<ReliabilityBin>, String> {
+	if num_bins == 0 {
+		return Err("metrics::reliability_bins needs at least one bin".to_string())
+	}
+	
+	let width = 1_f64.div(num_bins as f64);
+	let mut sums = vec![(0_u64, 0_f64, 0_f64); num_bins];
+	for (probability, answer) in scores {
+		if !(0_f64..=1_f64).contains(probability) {
+			let message = format!("metrics::reliability_bins found probability {} outside [0, 1]", probability);
+			return Err(message)
+		}
+		let index = ((probability.div(width)).floor() as usize).min(num_bins - 1);
+		let sum = &mut sums[index];
+		sum.0 += 1;
+		sum.1 = sum.1.add(probability);
+		if *answer == BinaryClass::Yes {
+			sum.2 = sum.2.add(1_f64);
+		}
+	}
+	
+	let mut bins = Vec::new();
+	for (index, (count, probability_sum, yes_sum)) in sums.into_iter().enumerate() {
+		if count > 0 {
+			bins.push(ReliabilityBin {
+				lower: (index as f64).div(num_bins as f64),
+				upper: ((index + 1) as f64).div(num_bins as f64),
+				count,
+				mean_probability: probability_sum.div(count as f64),
+				observed_rate: yes_sum.div(count as f64)
+			});
+		}
+	}
+	Ok(bins)
+}
+
+pub fn expected_calibration_error(scores: &[(f64, BinaryClass)], num_bins: usize) -> Result<f64, String> {
+	if scores.is_empty() {
+		return Err("metrics::expected_calibration_error needs at least one score".to_string())
+	}
+	
+	let total = scores.len() as f64;
+	let mut error = 0_f64;
+	for bin in reliability_bins(scores, num_bins)? {
+		error = error.add((bin.count as f64).div(total).mul(bin.mean_probability.sub(bin.observed_rate).abs()));
+	}
+	Ok(error)
+}
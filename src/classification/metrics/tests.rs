@@ -0,0 +1,59 @@
+use super::*;
+
+fn scores() -> Vec<(f64, BinaryClass)> {
+	vec![
+		(0.9_f64, BinaryClass::Yes),
+		(0.8_f64, BinaryClass::Yes),
+		(0.7_f64, BinaryClass::No),
+		(0.4_f64, BinaryClass::Yes),
+		(0.2_f64, BinaryClass::No),
+		(0.1_f64, BinaryClass::No)
+	]
+}
+
+#[test]
+fn when_scores_and_threshold_then_confusion_matrix_correct() {
+	let matrix = ConfusionMatrix::from_scores(&scores(), &0.5_f64);
+	
+	assert_eq!(matrix, ConfusionMatrix {
+		true_positives: 2,
+		false_positives: 1,
+		true_negatives: 2,
+		false_negatives: 1
+	});
+	assert_abs_diff_eq!(matrix.accuracy(), 4_f64.div(6_f64));
+	assert_abs_diff_eq!(matrix.f1(), 2_f64.div(3_f64));
+	assert_abs_diff_eq!(matrix.youden_j(), 1_f64.div(3_f64));
+}
+
+#[test]
+fn when_scores_then_best_threshold_maximizes_metric() {
+	let (threshold, accuracy) = best_threshold(&scores(), &ThresholdMetric::Accuracy).unwrap();
+	assert_abs_diff_eq!(threshold, 0.7_f64);
+	assert_abs_diff_eq!(accuracy, 5_f64.div(6_f64));
+	
+	let (threshold, f1) = best_threshold(&scores(), &ThresholdMetric::F1).unwrap();
+	assert_abs_diff_eq!(threshold, 0.2_f64);
+	assert_abs_diff_eq!(f1, 6_f64.div(7_f64));
+}
+
+#[test]
+fn when_metric_name_then_parsed() {
+	assert_eq!("youden".parse::<ThresholdMetric>().unwrap(), ThresholdMetric::YoudenJ);
+	assert!("auc".parse::<ThresholdMetric>().is_err());
+}
+
+#[test]
+fn when_scores_then_reliability_bins_and_expected_calibration_error_correct() {
+	let bins = reliability_bins(&scores(), 2).unwrap();
+	
+	assert_eq!(bins.len(), 2);
+	assert_eq!(bins[0].count, 3);
+	assert_abs_diff_eq!(bins[0].mean_probability, 0.7_f64.div(3_f64));
+	assert_abs_diff_eq!(bins[0].observed_rate, 1_f64.div(3_f64));
+	assert_abs_diff_eq!(bins[1].mean_probability, 0.8_f64);
+	assert_abs_diff_eq!(bins[1].observed_rate, 2_f64.div(3_f64));
+	
+	let expected = 0.5_f64.mul(1_f64.div(3_f64).sub(0.7_f64.div(3_f64))).add(0.5_f64.mul(0.8_f64.sub(2_f64.div(3_f64))));
+	assert_abs_diff_eq!(expected_calibration_error(&scores(), 2).unwrap(), expected);
+}
@@ -0,0 +1,477 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{cost_slope, metrics, quick_convert, random::Rng, BinaryClass, Classifier, Labelled, LogisticBinaryClassificationTestable, LogisticBinaryClassificationTrainable, Regularizable};
+use crate::{Feature, FeatureValue, PassengerFeatures};
+
+#[cfg(test)]
+mod tests;
+
+// Decay rates of Adam's running means of the gradient and its square, and the term keeping its division finite.
+const ADAM_BETA1: f64 = 0.9;
+const ADAM_BETA2: f64 = 0.999;
+const ADAM_EPSILON: f64 = 1e-8;
+
+// Training prints the log loss every this many epochs.
+const REPORT_EVERY: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Activation {
+	Relu,
+	Tanh
+}
+
+impl FromStr for Activation {
+	type Err = String;
+
+	fn from_str(name: &str) -> Result<Activation, String> {
+		match name {
+			"relu" => Ok(Activation::Relu),
+			"tanh" => Ok(Activation::Tanh),
+			_ => Err(format!("Activation::from_str unknown activation {}, expected one of relu, tanh", name)),
+		}
+	}
+}
+
+impl fmt::Display for Activation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Activation::Relu => write!(f, "relu"),
+			Activation::Tanh => write!(f, "tanh"),
+		}
+	}
+}
+
+impl Activation {
+	fn apply(&self, value: f64) -> f64 {
+		match self {
+			Activation::Relu => value.max(0_f64),
+			Activation::Tanh => value.tanh(),
+		}
+	}
+
+	// Derivative at the value before activation
+	fn derivative(&self, value: f64) -> f64 {
+		match self {
+			Activation::Relu => if value > 0_f64 { 1_f64 } else { 0_f64 },
+			Activation::Tanh => 1_f64.sub(value.tanh().powi(2)),
+		}
+	}
+
+	// Standard deviation of the initial weights of a layer with this many inputs: He for ReLU, Xavier for tanh
+	fn initial_std(&self, inputs: usize) -> f64 {
+		match self {
+			Activation::Relu => 2_f64.div(quick_convert(&inputs)).sqrt(),
+			Activation::Tanh => 1_f64.div(quick_convert(&inputs)).sqrt(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MlpOptimizer {
+	// Plain stochastic gradient descent on mini-batches
+	Sgd,
+	// Adam: steps scaled by running means of the gradient and its square
+	Adam
+}
+
+impl FromStr for MlpOptimizer {
+	type Err = String;
+
+	fn from_str(name: &str) -> Result<MlpOptimizer, String> {
+		match name {
+			"sgd" => Ok(MlpOptimizer::Sgd),
+			"adam" => Ok(MlpOptimizer::Adam),
+			_ => Err(format!("MlpOptimizer::from_str unknown optimizer {}, expected one of sgd, adam", name)),
+		}
+	}
+}
+
+impl fmt::Display for MlpOptimizer {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			MlpOptimizer::Sgd => write!(f, "sgd"),
+			MlpOptimizer::Adam => write!(f, "adam"),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MlpSettings {
+	// Width of each of the one or two hidden layers
+	pub hidden: Vec<usize>,
+	pub activation: Activation,
+	// Share of the hidden units switched off at random for each record while training
+	pub dropout: f64,
+	pub optimizer: MlpOptimizer,
+	pub learning_rate: f64,
+	// Strength of the L2 penalty on every weight except the biases
+	pub l2: f64,
+	pub epochs: usize,
+	pub batch_size: usize
+}
+
+impl Default for MlpSettings {
+	fn default() -> Self {
+		MlpSettings {
+			hidden: vec![16],
+			activation: Activation::Relu,
+			dropout: 0_f64,
+			optimizer: MlpOptimizer::Adam,
+			learning_rate: 0.01_f64,
+			l2: 0.0001_f64,
+			epochs: 100,
+			batch_size: 32
+		}
+	}
+}
+
+impl MlpSettings {
+	pub fn validate(&self) -> Result<(), String> {
+		if self.hidden.is_empty() || self.hidden.len() > 2 || self.hidden.contains(&0) {
+			return Err(format!("MlpSettings::validate expected one or two hidden layers of at least one unit, got {:?}", self.hidden))
+		}
+		if !(0_f64..1_f64).contains(&self.dropout) {
+			return Err(format!("MlpSettings::validate dropout {} should be at least 0 and below 1", self.dropout))
+		}
+		if self.learning_rate <= 0_f64 || self.l2 < 0_f64 || self.epochs == 0 || self.batch_size == 0 {
+			return Err("MlpSettings::validate learning_rate should be positive, l2 at least 0 and epochs and batch_size at least 1".to_string())
+		}
+		Ok(())
+	}
+}
+
+// How one feature feeds the input layer
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+	// The number standardized with the training mean and standard deviation, 0 when missing, followed by a missing flag
+	Number { feature: Feature, mean: f64, std: f64 },
+	// One input per category, a missing value being category 0
+	OneHot { feature: Feature, categories: usize }
+}
+
+impl Column {
+	fn feature(&self) -> &Feature {
+		match self {
+			Column::Number { feature, .. } => feature,
+			Column::OneHot { feature, .. } => feature,
+		}
+	}
+
+	fn width(&self) -> usize {
+		match self {
+			Column::Number { .. } => 2,
+			Column::OneHot { categories, .. } => *categories,
+		}
+	}
+
+	fn encode(&self, record: &impl PassengerFeatures, inputs: &mut Vec<f64>) {
+		match self {
+			Column::Number { feature, mean, std } => match record.get_feature(feature) {
+				FeatureValue::Number(number) => inputs.extend([number.sub(mean).div(std), 0_f64]),
+				_ => inputs.extend([0_f64, 1_f64]),
+			},
+			Column::OneHot { feature, categories } => {
+				let category = match record.get_feature(feature) {
+					FeatureValue::Missing | FeatureValue::Number(_) => 0,
+					FeatureValue::Present => 1,
+					FeatureValue::Category(category) => category,
+				};
+				inputs.extend((0..*categories).map(|index| if index == category { 1_f64 } else { 0_f64 }));
+			},
+		}
+	}
+}
+
+fn columns<R: PassengerFeatures>(records: &[R], features: &[Feature]) -> Vec<Column> {
+	features.iter().map(|feature| {
+		let numbers: Vec<f64> = records.iter().filter_map(|record| match record.get_feature(feature) {
+			FeatureValue::Number(number) => Some(number),
+			_ => None,
+		}).collect();
+		if numbers.is_empty() {
+			return Column::OneHot { feature: *feature, categories: feature.get_weight_count() }
+		}
+		let count = quick_convert(&numbers.len());
+		let mean = numbers.iter().fold(0_f64, |sum, number| sum.add(number)).div(count);
+		let std = numbers.iter().fold(0_f64, |sum, number| sum.add(number.sub(mean).powi(2))).div(count).sqrt();
+		Column::Number { feature: *feature, mean, std: if std > 0_f64 { std } else { 1_f64 } }
+	}).collect()
+}
+
+// A fully connected layer: weights[output][input]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Layer {
+	pub weights: Vec<Vec<f64>>,
+	pub biases: Vec<f64>
+}
+
+impl Layer {
+	fn zeros_like(layers: &[Layer]) -> Vec<Layer> {
+		layers.iter().map(|layer| Layer {
+			weights: layer.weights.iter().map(|row| vec![0_f64; row.len()]).collect(),
+			biases: vec![0_f64; layer.biases.len()]
+		}).collect()
+	}
+
+	fn forward(&self, input: &[f64]) -> Vec<f64> {
+		self.weights.iter().zip(&self.biases).map(|(row, bias)| row.iter().zip(input).fold(*bias, |sum, (weight, value)| sum.add(weight.mul(value)))).collect()
+	}
+}
+
+fn parameters_mut(layers: &mut [Layer]) -> impl Iterator<Item = &mut f64> {
+	layers.iter_mut().flat_map(|layer| layer.weights.iter_mut().flatten().chain(layer.biases.iter_mut()))
+}
+
+// Everything a forward pass computed that backpropagation needs
+struct Pass {
+	// The input of every layer
+	inputs: Vec<Vec<f64>>,
+	// Hidden values before activation
+	pre_activations: Vec<Vec<f64>>,
+	// Dropout factor of every hidden unit: 0 when dropped, otherwise 1 / (1 - dropout) so the expected value is unchanged
+	masks: Vec<Vec<f64>>,
+	output: f64
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Mlp {
+	settings: MlpSettings,
+	seed: u64,
+	columns: Vec<Column>,
+	// The hidden layers followed by an output layer with one unit
+	layers: Vec<Layer>
+}
+
+impl Mlp {
+	// Minimises the mean log loss by backpropagation on mini-batches drawn in a random order, with dropout on the hidden
+	// units. The initial weights, the order and the dropout are all drawn from seed.
+	pub fn fit<R: PassengerFeatures + Labelled>(records: &[R], features: &[Feature], settings: &MlpSettings, seed: u64) -> Result<Mlp, String> {
+		if records.is_empty() || features.is_empty() {
+			return Err("Mlp::fit needs at least one record and feature".to_string())
+		}
+		settings.validate()?;
+		let mut rng = Rng::new(seed);
+		let columns = columns(records, features);
+		let mut widths = vec![columns.iter().map(|column| column.width()).sum::<usize>()];
+		widths.extend(&settings.hidden);
+		widths.push(1);
+		let layers = widths.windows(2).map(|pair| {
+			let std = settings.activation.initial_std(pair[0]);
+			Layer {
+				weights: (0..pair[1]).map(|_output| (0..pair[0]).map(|_input| rng.normal(0_f64, std)).collect()).collect(),
+				biases: vec![0_f64; pair[1]]
+			}
+		}).collect();
+		let mut mlp = Mlp { settings: settings.clone(), seed, columns, layers };
+
+		let encoded = mlp.encode_records(records);
+		let mut first_moments = Layer::zeros_like(&mlp.layers);
+		let mut second_moments = Layer::zeros_like(&mlp.layers);
+		let mut steps = 0_i32;
+		let mut order: Vec<usize> = (0..encoded.len()).collect();
+		for epoch in 1..=settings.epochs {
+			rng.shuffle(&mut order);
+			for batch in order.chunks(settings.batch_size) {
+				let mut gradients = Layer::zeros_like(&mlp.layers);
+				for index in batch {
+					let pass = mlp.forward(&encoded[*index].inputs, settings.dropout, &mut rng);
+					let probability = EncodedRecord::logistic(pass.output);
+					mlp.backward(&pass, cost_slope(probability, &encoded[*index].answer), &mut gradients);
+				}
+				let size = quick_convert(&batch.len());
+				for (gradient_layer, layer) in gradients.iter_mut().zip(&mlp.layers) {
+					for (gradient_row, row) in gradient_layer.weights.iter_mut().zip(&layer.weights) {
+						for (gradient, weight) in gradient_row.iter_mut().zip(row) {
+							*gradient = gradient.div(size).add(settings.l2.mul(weight));
+						}
+					}
+					for gradient in gradient_layer.biases.iter_mut() {
+						*gradient = gradient.div(size);
+					}
+				}
+
+				steps = steps.add(1);
+				let parameters = parameters_mut(&mut mlp.layers);
+				let moments = parameters_mut(&mut first_moments).zip(parameters_mut(&mut second_moments));
+				match settings.optimizer {
+					MlpOptimizer::Sgd => {
+						for (parameter, gradient) in parameters.zip(parameters_mut(&mut gradients)) {
+							*parameter = parameter.sub(settings.learning_rate.mul(*gradient));
+						}
+					},
+					MlpOptimizer::Adam => {
+						let step_size = settings.learning_rate.mul(1_f64.sub(ADAM_BETA2.powi(steps)).sqrt()).div(1_f64.sub(ADAM_BETA1.powi(steps)));
+						for ((parameter, gradient), (first, second)) in parameters.zip(parameters_mut(&mut gradients)).zip(moments) {
+							*first = ADAM_BETA1.mul(*first).add(1_f64.sub(ADAM_BETA1).mul(*gradient));
+							*second = ADAM_BETA2.mul(*second).add(1_f64.sub(ADAM_BETA2).mul(gradient.powi(2)));
+							*parameter = parameter.sub(step_size.mul(*first).div(second.sqrt().add(ADAM_EPSILON)));
+						}
+					},
+				}
+			}
+			if epoch % REPORT_EVERY == 0 || epoch == settings.epochs {
+				let scores: Vec<(f64, BinaryClass)> = encoded.iter().map(|record| (EncodedRecord::logistic(mlp.output(&record.inputs)), record.answer)).collect();
+				eprintln!("Mlp::fit Epoch {} of {}, training log loss {}", epoch, settings.epochs, metrics::log_loss(&scores)?);
+			}
+		}
+		eprintln!("Mlp::fit Trained hidden layers {:?} with {} activation, dropout {} and {} at learning rate {}", settings.hidden, settings.activation, settings.dropout, settings.optimizer, settings.learning_rate);
+		Ok(mlp)
+	}
+
+	// Keeps each hidden unit with probability 1 - dropout, drawn from rng when dropout is positive.
+	fn forward(&self, inputs: &[f64], dropout: f64, rng: &mut Rng) -> Pass {
+		let mut pass = Pass { inputs: vec![inputs.to_vec()], pre_activations: Vec::new(), masks: Vec::new(), output: 0_f64 };
+		let hidden = self.layers.len().sub(1);
+		for layer in &self.layers[..hidden] {
+			let pre_activation = layer.forward(&pass.inputs[pass.inputs.len().sub(1)]);
+			let mask: Vec<f64> = pre_activation.iter().map(|_unit| {
+				if dropout > 0_f64 && rng.next_f64() < dropout { 0_f64 } else { 1_f64.div(1_f64.sub(dropout)) }
+			}).collect();
+			pass.inputs.push(pre_activation.iter().zip(&mask).map(|(value, factor)| self.settings.activation.apply(*value).mul(factor)).collect());
+			pass.pre_activations.push(pre_activation);
+			pass.masks.push(mask);
+		}
+		pass.output = self.layers[hidden].forward(&pass.inputs[hidden])[0];
+		pass
+	}
+
+	// Adds slope times the derivative of the output with respect to every parameter to gradients.
+	fn backward(&self, pass: &Pass, slope: f64, gradients: &mut [Layer]) {
+		let mut deltas = vec![slope];
+		for index in (0..self.layers.len()).rev() {
+			let input = &pass.inputs[index];
+			for (output, delta) in deltas.iter().enumerate() {
+				for (gradient, value) in gradients[index].weights[output].iter_mut().zip(input) {
+					*gradient = gradient.add(delta.mul(value));
+				}
+				gradients[index].biases[output] = gradients[index].biases[output].add(delta);
+			}
+			if index > 0 {
+				deltas = (0..input.len()).map(|unit| {
+					let back = deltas.iter().zip(&self.layers[index].weights).fold(0_f64, |sum, (delta, row)| sum.add(delta.mul(row[unit])));
+					back.mul(self.settings.activation.derivative(pass.pre_activations[index.sub(1)][unit])).mul(pass.masks[index.sub(1)][unit])
+				}).collect();
+			}
+		}
+	}
+
+	// Moves every parameter by diff times the derivative of the output with respect to it, as PassengerWeights::update
+	// moves every weight by diff times its input.
+	pub fn add_gradient(&mut self, inputs: &[f64], diff: &f64) {
+		let pass = self.forward(inputs, 0_f64, &mut Rng::new(0));
+		let mut gradients = Layer::zeros_like(&self.layers);
+		self.backward(&pass, 1_f64, &mut gradients);
+		for (parameter, gradient) in parameters_mut(&mut self.layers).zip(parameters_mut(&mut gradients)) {
+			*parameter = parameter.add(diff.mul(*gradient));
+		}
+	}
+
+	pub fn encode<R: PassengerFeatures>(&self, record: &R) -> Vec<f64> {
+		let mut inputs = Vec::new();
+		for column in &self.columns {
+			column.encode(record, &mut inputs);
+		}
+		inputs
+	}
+
+	pub fn encode_records<R: PassengerFeatures + Labelled>(&self, records: &[R]) -> Vec<EncodedRecord> {
+		records.iter().map(|record| EncodedRecord {
+			record_id: *record.get_passenger_id(),
+			inputs: self.encode(record),
+			answer: record.label()
+		}).collect()
+	}
+
+	// The output unit, whose logistic 1 / (1 + exp(output)) is the probability of BinaryClass::Yes
+	pub fn output(&self, inputs: &[f64]) -> f64 {
+		self.forward(inputs, 0_f64, &mut Rng::new(0)).output
+	}
+
+	pub fn get_settings(&self) -> &MlpSettings {
+		&self.settings
+	}
+
+	pub fn get_seed(&self) -> &u64 {
+		&self.seed
+	}
+
+	pub fn get_columns(&self) -> &Vec<Column> {
+		&self.columns
+	}
+
+	pub fn get_layers(&self) -> &Vec<Layer> {
+		&self.layers
+	}
+
+	// Probability of BinaryClass::Yes
+	pub fn probability<R: PassengerFeatures>(&self, record: &R) -> f64 {
+		EncodedRecord::logistic(self.output(&self.encode(record)))
+	}
+}
+
+// fit trains the network again on the records, over the features and with the settings and seed it was trained with.
+impl<R: PassengerFeatures + Labelled> Classifier<R> for Mlp {
+	fn fit(&mut self, records: &[R]) -> Result<(), String> {
+		let features: Vec<Feature> = self.columns.iter().map(|column| *column.feature()).collect();
+		*self = Mlp::fit(records, &features, &self.settings, self.seed)?;
+		Ok(())
+	}
+
+	fn predict_proba(&self, record: &R) -> Result<f64, String> {
+		Ok(self.probability(record))
+	}
+}
+
+impl Regularizable for Mlp {
+	fn shrink(self: &mut Self, factor: &f64) {
+		for layer in self.layers.iter_mut() {
+			for weight in layer.weights.iter_mut().flatten() {
+				*weight = weight.mul(factor);
+			}
+		}
+	}
+
+	fn squared_norm(self: &Self) -> f64 {
+		self.layers.iter().flat_map(|layer| layer.weights.iter().flatten()).fold(0_f64, |sum, weight| sum.add(weight.mul(weight)))
+	}
+}
+
+// A record encoded for the network's input layer, so that an Mlp can be trained by solve and scored by predict_proba
+// like any other weights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodedRecord {
+	pub record_id: u64,
+	pub inputs: Vec<f64>,
+	pub answer: BinaryClass
+}
+
+impl LogisticBinaryClassificationTestable for EncodedRecord {
+	type Weights = Mlp;
+
+	fn hypothesis(self: &Self, weights: &Self::Weights) -> Result<f64, String> {
+		Ok(Self::logistic(weights.output(&self.inputs)))
+	}
+
+	fn get_record_id(self: &Self) -> &u64 {
+		&self.record_id
+	}
+}
+
+impl LogisticBinaryClassificationTrainable for EncodedRecord {
+	fn answer(self: &Self) -> BinaryClass {
+		self.answer
+	}
+
+	fn update_weights(self: &Self, diff: &f64, weights: &mut Self::Weights) -> Result<(), String> {
+		weights.add_gradient(&self.inputs, diff);
+		Ok(())
+	}
+}
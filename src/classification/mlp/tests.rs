@@ -0,0 +1,79 @@
+use super::*;
+use crate::classification::{self, SolverSettings};
+use crate::test_support::passengers;
+use crate::TrainingPassenger;
+
+// Survival is sex exclusive-or first class, which no sum of one weight per feature can separate.
+fn interaction_passengers() -> Vec<TrainingPassenger> {
+	passengers(&[
+		"1,1,1,A,male,30,0,0,T,10,,S",
+		"2,0,3,B,male,40,0,0,T,10,,S",
+		"3,0,1,C,female,20,0,0,T,10,,S",
+		"4,1,3,D,female,30,0,0,T,10,,S",
+		"5,1,1,E,male,25,0,0,T,10,,S",
+		"6,0,3,F,male,35,0,0,T,10,,S",
+		"7,0,1,G,female,45,0,0,T,10,,S",
+		"8,1,3,H,female,50,0,0,T,10,,S",
+	])
+}
+
+#[test]
+fn when_interaction_then_network_separates_classes() {
+	let records = interaction_passengers();
+	let features = [Feature::PassengerClass, Feature::Sex];
+	for activation in [Activation::Relu, Activation::Tanh].iter() {
+		for optimizer in [MlpOptimizer::Adam, MlpOptimizer::Sgd].iter() {
+			let settings = MlpSettings { hidden: vec![8, 4], activation: *activation, optimizer: *optimizer, learning_rate: 0.05_f64, epochs: 300, batch_size: 4, ..MlpSettings::default() };
+			let mlp = Mlp::fit(&records, &features, &settings, 1_u64).unwrap();
+
+			for record in &records {
+				assert_eq!(mlp.probability(record) > 0.5_f64, record.label() == BinaryClass::Yes, "{} {}", activation, optimizer);
+			}
+			assert_eq!(mlp, Mlp::fit(&records, &features, &settings, 1_u64).unwrap());
+		}
+	}
+}
+
+#[test]
+fn when_backpropagating_then_gradient_matches_finite_differences() {
+	let records = interaction_passengers();
+	let settings = MlpSettings { hidden: vec![3, 2], activation: Activation::Tanh, epochs: 1, ..MlpSettings::default() };
+	let mlp = Mlp::fit(&records, &[Feature::Sex, Feature::Age], &settings, 3_u64).unwrap();
+	let inputs = mlp.encode(&records[1]);
+	let mut gradients = Layer::zeros_like(&mlp.layers);
+	mlp.backward(&mlp.forward(&inputs, 0_f64, &mut Rng::new(0)), 1_f64, &mut gradients);
+
+	let step = 1e-6_f64;
+	for (index, gradient) in parameters_mut(&mut gradients).enumerate() {
+		let mut moved = mlp.clone();
+		*parameters_mut(&mut moved.layers).nth(index).unwrap() += step;
+		assert_abs_diff_eq!((moved.output(&inputs) - mlp.output(&inputs)) / step, *gradient, epsilon = 1e-5);
+	}
+}
+
+#[test]
+fn when_encoded_records_then_solver_and_predict_proba_accept_network() {
+	let records = interaction_passengers();
+	let mut mlp = Mlp::fit(&records, &[Feature::PassengerClass, Feature::Sex, Feature::Age], &MlpSettings { epochs: 1, ..MlpSettings::default() }, 1_u64).unwrap();
+	let encoded = mlp.encode_records(&records);
+	assert_eq!(encoded[2].inputs.len(), 4 + 3 + 2);
+
+	let settings = SolverSettings { max_iterations: Some(3), verbose: false, ..SolverSettings::new(0.01_f64, 1e-9_f64) };
+	let initial_loss = classification::avg_cost(&encoded, &mlp).unwrap();
+	classification::solve_with_settings(&encoded, &mut mlp, &settings).unwrap();
+	assert!(classification::avg_cost(&encoded, &mlp).unwrap() < initial_loss);
+	for (record, encoded) in records.iter().zip(&encoded) {
+		assert_abs_diff_eq!(classification::predict_proba(&mlp, encoded).unwrap(), mlp.probability(record));
+	}
+}
+
+#[test]
+fn when_settings_invalid_then_error() {
+	for settings in [
+		MlpSettings { hidden: vec![4, 4, 4], ..MlpSettings::default() },
+		MlpSettings { hidden: vec![0], ..MlpSettings::default() },
+		MlpSettings { dropout: 1_f64, ..MlpSettings::default() },
+	].iter() {
+		assert!(Mlp::fit(&interaction_passengers(), &Feature::ALL, settings, 1_u64).is_err());
+	}
+}
@@ -1,9 +1,29 @@
-use std::ops::{Add, Mul, Div};
+use std::fmt;
+use std::ops::{Add, Mul, Div, Sub};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+pub mod bayes;
+pub mod boosting;
+pub mod calibration;
+pub mod forest;
+pub mod knn;
+pub mod metrics;
+pub mod mlp;
+pub mod multiclass;
+pub mod random;
+pub mod svm;
+pub mod tree;
+
+use random::Rng;
 
 #[cfg(test)]
 mod tests;
 
-#[derive(Debug, PartialEq)]
+pub const DEFAULT_THRESHOLD: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BinaryClass {
 	Yes,
 	No
@@ -12,7 +32,85 @@ pub enum BinaryClass {
 #[derive(Debug, PartialEq)]
 pub struct Outcome {
 	pub record_id: u64,
-	pub prediction: BinaryClass
+	pub prediction: BinaryClass,
+	pub probability: f64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Optimizer {
+	// One update per iteration, summing the gradient of every record at the weights from the start of the iteration
+	Batch,
+	// One update per record, each using the weights left by the previous record
+	Online
+}
+
+impl FromStr for Optimizer {
+	type Err = String;
+	
+	fn from_str(name: &str) -> Result<Optimizer, String> {
+		match name {
+			"batch" => Ok(Optimizer::Batch),
+			"online" => Ok(Optimizer::Online),
+			_ => Err(format!("Optimizer::from_str unknown optimizer {}, expected one of batch, online", name)),
+		}
+	}
+}
+
+impl fmt::Display for Optimizer {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Optimizer::Batch => write!(f, "batch"),
+			Optimizer::Online => write!(f, "online"),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SolverSettings {
+	pub learning_rate: f64,
+	pub tolerance: f64,
+	// None keeps iterating until the tolerance is reached
+	pub max_iterations: Option<u64>,
+	// Strength of the L2 penalty l2 / 2 * |weights|^2, added to the summed cost
+	pub l2: f64,
+	pub optimizer: Optimizer,
+	// Seeds the order in which the online optimizer visits the records
+	pub seed: u64,
+	pub verbose: bool
+}
+
+impl SolverSettings {
+	pub fn new(learning_rate: f64, tolerance: f64) -> SolverSettings {
+		SolverSettings {
+			learning_rate,
+			tolerance,
+			max_iterations: None,
+			l2: 0_f64,
+			optimizer: Optimizer::Batch,
+			seed: crate::DEFAULT_SEED,
+			verbose: true
+		}
+	}
+}
+
+pub trait Regularizable {
+	// Multiplies every weight except the bias by factor
+	fn shrink(self: &mut Self, factor: &f64);
+	
+	// Sum of the squares of every weight except the bias
+	fn squared_norm(self: &Self) -> f64;
+}
+
+// Records whose true class is known, whichever learner they are used with.
+pub trait Labelled {
+	fn label(self: &Self) -> BinaryClass;
+}
+
+impl<T: LogisticBinaryClassificationTrainable> Labelled for T {
+	fn label(self: &Self) -> BinaryClass {
+		self.answer()
+	}
 }
 
 pub trait LogisticBinaryClassificationTestable {
@@ -50,13 +148,13 @@ pub trait LogisticBinaryClassificationTrainable: LogisticBinaryClassificationTes
         match self.answer() {
             BinaryClass::Yes => {
                 match self.hypothesis(weights) {
-                    Ok(hypothesis) => Ok(-(hypothesis.ln())),
+                    Ok(hypothesis) => Ok(-(hypothesis.max(f64::MIN_POSITIVE).ln())),
                     Err(error) => Err(error),
                 }
             },
             BinaryClass::No => {
                 match self.hypothesis(weights) {
-                    Ok(hypothesis) => Ok(-((1_f64 - hypothesis).ln())),
+                    Ok(hypothesis) => Ok(-((1_f64 - hypothesis).max(f64::MIN_POSITIVE).ln())),
                     Err(error) => Err(error)
                 }
             },
@@ -64,6 +162,31 @@ pub trait LogisticBinaryClassificationTrainable: LogisticBinaryClassificationTes
     }
 }
 
+// A model that learns from labelled records and then scores new ones, whatever kind of model it is.
+pub trait Classifier<T> {
+	fn fit(self: &mut Self, records: &[T]) -> Result<(), String>;
+	
+	// Probability of BinaryClass::Yes
+	fn predict_proba(self: &Self, record: &T) -> Result<f64, String>;
+	
+	// BinaryClass::Yes when the probability is above threshold
+	fn predict(self: &Self, record: &T, threshold: &f64) -> Result<BinaryClass, String> {
+		Ok(decide(self.predict_proba(record)?, threshold))
+	}
+	
+	// The probability of every record next to its label, as the metrics take them
+	fn score_batch(self: &Self, records: &[T]) -> Result<Vec<(f64, BinaryClass)>, String>
+	where
+		T: Labelled
+	{
+		let mut scores = Vec::new();
+		for record in records {
+			scores.push((self.predict_proba(record)?, record.label()));
+		}
+		Ok(scores)
+	}
+}
+
 
 	
 pub fn quick_convert(num: &usize) -> f64 {
@@ -74,7 +197,7 @@ pub fn quick_convert(num: &usize) -> f64 {
 	result
 }
 
-fn avg_cost<W, T>(training_records: &Vec<T>, weights: &W) -> Result<f64, String>
+pub(crate) fn avg_cost<W, T>(training_records: &[T], weights: &W) -> Result<f64, String>
 where
 	T: LogisticBinaryClassificationTrainable<Weights = W>
 {
@@ -82,7 +205,7 @@ where
 	let mut counter = 0_f64;
 	
 	for record in training_records {
-		match record.cost(&weights) {
+		match record.cost(weights) {
 			Ok(cost) => {
 				sum = sum.add(cost);
 				counter = counter.add(1_f64);
@@ -103,16 +226,26 @@ where
 	Ok(sum.div(counter))
 }
 
-fn gradient_descent_update<W, T>(training_records: &Vec<T>, weights: &mut W, learning_rate: &f64) -> Result<(), String>
+// Derivative of the cost of a record with respect to its weighted sum s, where its hypothesis is 1 / (1 + exp(s)):
+// 1 - hypothesis for BinaryClass::Yes and -hypothesis for BinaryClass::No. Gradient descent moves s against it, which
+// raises the hypothesis of BinaryClass::Yes and lowers that of BinaryClass::No.
+pub(crate) fn cost_slope(hypothesis: f64, answer: &BinaryClass) -> f64 {
+	match answer {
+		BinaryClass::Yes => 1_f64.sub(hypothesis),
+		BinaryClass::No => -hypothesis,
+	}
+}
+
+pub(crate) fn gradient_descent_update<W, T>(training_records: &[T], weights: &mut W, learning_rate: &f64) -> Result<(), String>
 where
 	W: std::clone::Clone,
 	T: LogisticBinaryClassificationTrainable<Weights = W>,
 {
 	let trainable_weights = weights.clone();
 	for record in training_records {
-		match record.diff_hypothesis(&trainable_weights) {
-			Ok(diff) => {
-				record.update_weights(&(diff.mul(-learning_rate)), weights)?;
+		match record.hypothesis(&trainable_weights) {
+			Ok(hypothesis) => {
+				record.update_weights(&(cost_slope(hypothesis, &record.answer()).mul(-learning_rate)), weights)?;
 			},
 			Err(error) => return Err(error),
 		}
@@ -120,28 +253,80 @@ where
 	Ok(())
 }
 
-pub fn predict<W, R>(weights: &W, record: &R) -> Result<Outcome, String>
+pub(crate) fn online_gradient_descent_update<W, T>(training_records: &[T], weights: &mut W, learning_rate: &f64, l2: &f64, rng: &mut Rng) -> Result<(), String>
+where
+	W: Regularizable,
+	T: LogisticBinaryClassificationTrainable<Weights = W>,
+{
+	// The penalty is spread evenly over the records, so a pass shrinks the weights as much as one batch update
+	let shrink = 1_f64.sub(learning_rate.mul(l2).div(quick_convert(&training_records.len())));
+	// Every pass visits the records in a fresh order drawn from rng
+	let mut order: Vec<usize> = (0..training_records.len()).collect();
+	rng.shuffle(&mut order);
+	for index in order {
+		let record = &training_records[index];
+		let hypothesis = record.hypothesis(weights)?;
+		record.update_weights(&(cost_slope(hypothesis, &record.answer()).mul(-learning_rate)), weights)?;
+		if l2.ne(&0_f64) {
+			weights.shrink(&shrink);
+		}
+	}
+	Ok(())
+}
+
+fn objective<W, T>(training_records: &[T], weights: &W, l2: &f64) -> Result<f64, String>
+where
+	W: Regularizable,
+	T: LogisticBinaryClassificationTrainable<Weights = W>,
+{
+	let avg_cost = avg_cost(training_records, weights)?;
+	if l2.eq(&0_f64) {
+		return Ok(avg_cost)
+	}
+	let penalty = l2.mul(weights.squared_norm()).div(2_f64.mul(quick_convert(&training_records.len())));
+	Ok(avg_cost.add(penalty))
+}
+
+pub fn predict_proba<W, R>(weights: &W, record: &R) -> Result<f64, String>
 where
 	R: LogisticBinaryClassificationTestable<Weights = W>,
 {
-	match record.hypothesis(weights) {
-		Ok(hypothesis) => {
-			if hypothesis > 0.5_f64 {
-				Ok(Outcome {
-					record_id: *(record.get_record_id()),
-					prediction: BinaryClass::Yes
-				})
-			} else {
-				Ok(Outcome {
-					record_id: *(record.get_record_id()),
-					prediction: BinaryClass::No
-				})
-			}
-		},
+	record.hypothesis(weights)
+}
+
+pub fn decide(probability: f64, threshold: &f64) -> BinaryClass {
+	if probability.gt(threshold) {
+		BinaryClass::Yes
+	} else {
+		BinaryClass::No
+	}
+}
+
+pub fn classify(record_id: u64, probability: f64, threshold: &f64) -> Outcome {
+	Outcome {
+		record_id,
+		prediction: decide(probability, threshold),
+		probability
+	}
+}
+
+pub fn predict_with_threshold<W, R>(weights: &W, record: &R, threshold: &f64) -> Result<Outcome, String>
+where
+	R: LogisticBinaryClassificationTestable<Weights = W>,
+{
+	match predict_proba(weights, record) {
+		Ok(probability) => Ok(classify(*(record.get_record_id()), probability, threshold)),
 		Err(error) => Err(error),
 	}
 }
 
+pub fn predict<W, R>(weights: &W, record: &R) -> Result<Outcome, String>
+where
+	R: LogisticBinaryClassificationTestable<Weights = W>,
+{
+	predict_with_threshold(weights, record, &DEFAULT_THRESHOLD)
+}
+
 fn predict_batch<W, R>(weights: &W, records: &Vec<R>) -> Result<Vec<Outcome>, String>
 where
 	R: LogisticBinaryClassificationTestable<Weights = W>,
@@ -160,29 +345,106 @@ where
 	Ok(outcome_vec)
 }
 
-pub fn solve<W, T>(training_records: &Vec<T>, weights: &mut W, mut learning_rate: f64, tolerance: &f64) -> Result<(), String>
+pub fn score_batch<W, T>(weights: &W, records: &Vec<T>) -> Result<Vec<(f64, BinaryClass)>, String>
+where
+	T: LogisticBinaryClassificationTrainable<Weights = W>,
+{
+	let outcomes = predict_batch(weights, records)?;
+	let mut scores = Vec::new();
+	for (outcome, record) in outcomes.iter().zip(records) {
+		scores.push((outcome.probability, record.answer()));
+	}
+	Ok(scores)
+}
+
+pub fn select_threshold(scores: &[(f64, BinaryClass)], metric: &metrics::ThresholdMetric) -> Result<f64, String> {
+	let (threshold, score) = metrics::best_threshold(scores, metric)?;
+	eprintln!("LogisticBinaryClassificationProblem::select_threshold Selected threshold {} with {} {}", &threshold, metric, &score);
+	Ok(threshold)
+}
+
+// Holds out the last fraction of the records in the order they are given, so the holdout follows the order of the file.
+// Training holds out passengers with split_holdout_shuffled instead.
+pub fn split_holdout<T>(mut records: Vec<T>, fraction: &f64) -> Result<(Vec<T>, Vec<T>), String> {
+	if !(fraction.gt(&0_f64) && fraction.lt(&1_f64)) {
+		let message = format!("LogisticBinaryClassificationProblem::split_holdout fraction must lie strictly between 0 and 1, got {}", fraction);
+		return Err(message)
+	}
+	
+	let holdout_len = (quick_convert(&records.len()).mul(fraction)).round() as usize;
+	if holdout_len == 0 || holdout_len == records.len() {
+		let message = format!("LogisticBinaryClassificationProblem::split_holdout fraction {} leaves an empty partition of {} records", fraction, records.len());
+		return Err(message)
+	}
+	
+	let holdout = records.split_off(records.len() - holdout_len);
+	Ok((records, holdout))
+}
+
+// Shuffles the records with rng before holding out the tail, so the holdout does not depend on the file order.
+pub fn split_holdout_shuffled<T>(mut records: Vec<T>, fraction: &f64, rng: &mut Rng) -> Result<(Vec<T>, Vec<T>), String> {
+	rng.shuffle(&mut records);
+	split_holdout(records, fraction)
+}
+
+pub fn solve<W, T>(training_records: &[T], weights: &mut W, learning_rate: f64, tolerance: &f64) -> Result<(), String>
+where
+	W: std::clone::Clone + std::fmt::Debug + Regularizable,
+	T: LogisticBinaryClassificationTrainable<Weights = W>,
+{
+	solve_with_settings(training_records, weights, &SolverSettings::new(learning_rate, *tolerance))
+}
+
+pub fn solve_with_settings<W, T>(training_records: &[T], weights: &mut W, settings: &SolverSettings) -> Result<(), String>
 where
-	W: std::clone::Clone + std::fmt::Debug,
+	W: std::clone::Clone + std::fmt::Debug + Regularizable,
 	T: LogisticBinaryClassificationTrainable<Weights = W>,
 {
-	match avg_cost(training_records, weights) {
+	let mut learning_rate = settings.learning_rate;
+	let mut rng = Rng::new(settings.seed);
+	match objective(training_records, weights, &settings.l2) {
 		Ok(initial_cost) => {
 			let mut current_avg_cost = initial_cost;
 			let mut num_iterations = 0_u64;
-			println!("LogisticBinaryClassificationProblem::solve At iteration {}, the avg_cost is {}", &num_iterations, &current_avg_cost);
+			if settings.verbose {
+				eprintln!("LogisticBinaryClassificationProblem::solve At iteration {}, the avg_cost is {}", &num_iterations, &current_avg_cost);
+			}
 			
-			while current_avg_cost.gt(tolerance) {
-				match gradient_descent_update(training_records, weights, &learning_rate) {
+			while current_avg_cost.gt(&settings.tolerance) {
+				if let Some(max_iterations) = settings.max_iterations {
+					if num_iterations >= max_iterations {
+						if settings.verbose {
+							eprintln!("LogisticBinaryClassificationProblem::solve Stopped after the maximum of {} iterations with avg_cost {}: weights={:#?}", &max_iterations, &current_avg_cost, &weights);
+						}
+						return Ok(())
+					}
+				}
+				
+				let update = match settings.optimizer {
+					Optimizer::Batch => {
+						gradient_descent_update(training_records, weights, &learning_rate).map(|_| {
+							if settings.l2.ne(&0_f64) {
+								weights.shrink(&(1_f64.sub(learning_rate.mul(settings.l2))));
+							}
+						})
+					},
+					Optimizer::Online => online_gradient_descent_update(training_records, weights, &learning_rate, &settings.l2, &mut rng),
+				};
+				match update {
 					Ok(_) => {
 						num_iterations = num_iterations.add(1_u64);
-						match avg_cost(training_records, weights) {
+						match objective(training_records, weights, &settings.l2) {
 							Ok(new_avg_cost) => {
 								if current_avg_cost.lt(&new_avg_cost) {
 									learning_rate = learning_rate.div(100_f64);
-									println!("LogisticBinaryClassificationProblem::solve Learning rate divided by 10 at iteration {}. New learning_rate: {}", &num_iterations, &learning_rate);
+									if settings.verbose {
+										eprintln!("LogisticBinaryClassificationProblem::solve Learning rate divided by 10 at iteration {}. New learning_rate: {}", &num_iterations, &learning_rate);
+									}
 								};
 								current_avg_cost = new_avg_cost;
-								println!("LogisticBinaryClassificationProblem::solve At iteration {}, the avg_cost is {}", &num_iterations, &current_avg_cost);
+								if settings.verbose {
+									eprintln!("LogisticBinaryClassificationProblem::solve At iteration {}, the avg_cost is {}", &num_iterations, &current_avg_cost);
+								}
 							},
 							Err(error) => return Err(error),
 						}
@@ -190,9 +452,113 @@ where
 					Err(error) => return Err(error),
 				}
 			}
-			println!("LogisticBinaryClassificationProblem::solve Tolerable Cost Achieved: weights={:#?}", &weights);
+			if settings.verbose {
+				eprintln!("LogisticBinaryClassificationProblem::solve Tolerable Cost Achieved: weights={:#?}", &weights);
+			}
 			Ok(())
 		},
 		Err(error) => Err(error),
 	}
-}
\ No newline at end of file
+}
+
+// Logistic regression as a Classifier: fit runs solve_with_settings starting from the weights it holds, which is how a
+// warm start is given.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogisticRegression<W> {
+	settings: SolverSettings,
+	weights: W
+}
+
+impl<W> LogisticRegression<W> {
+	pub fn new(weights: W, settings: SolverSettings) -> LogisticRegression<W> {
+		LogisticRegression {
+			settings,
+			weights
+		}
+	}
+	
+	pub fn get_settings(&self) -> &SolverSettings {
+		&self.settings
+	}
+	
+	pub fn get_weights(&self) -> &W {
+		&self.weights
+	}
+	
+	pub fn into_weights(self) -> W {
+		self.weights
+	}
+}
+
+impl<W, T> Classifier<T> for LogisticRegression<W>
+where
+	W: std::clone::Clone + std::fmt::Debug + Regularizable,
+	T: LogisticBinaryClassificationTrainable<Weights = W>,
+{
+	fn fit(self: &mut Self, records: &[T]) -> Result<(), String> {
+		solve_with_settings(records, &mut self.weights, &self.settings)
+	}
+	
+	fn predict_proba(self: &Self, record: &T) -> Result<f64, String> {
+		predict_proba(&self.weights, record)
+	}
+}
+
+// Assigns record i to fold i % folds.
+pub fn k_fold_indices(num_records: usize, folds: usize) -> Result<Vec<Vec<usize>>, String> {
+	if folds < 2 || folds > num_records {
+		let message = format!("LogisticBinaryClassificationProblem::k_fold_indices cannot split {} records into {} folds", num_records, folds);
+		return Err(message)
+	}
+	
+	let mut fold_indices = vec![Vec::new(); folds];
+	for index in 0..num_records {
+		fold_indices[index % folds].push(index);
+	}
+	Ok(fold_indices)
+}
+
+// Assigns records to folds as k_fold_indices does, after shuffling their order with rng.
+pub fn k_fold_indices_shuffled(num_records: usize, folds: usize, rng: &mut Rng) -> Result<Vec<Vec<usize>>, String> {
+	let mut order: Vec<usize> = (0..num_records).collect();
+	rng.shuffle(&mut order);
+	let mut fold_indices = k_fold_indices(num_records, folds)?;
+	for fold in fold_indices.iter_mut() {
+		for index in fold.iter_mut() {
+			*index = order[*index];
+		}
+		fold.sort_unstable();
+	}
+	Ok(fold_indices)
+}
+
+// Trains fresh weights on all but one fold and scores the held-out fold, for every fold in turn.
+pub fn cross_validate<W, T, F>(training_records: &[T], fold_indices: &[Vec<usize>], settings: &SolverSettings, new_weights: F) -> Result<Vec<Vec<(f64, BinaryClass)>>, String>
+where
+	W: std::clone::Clone + std::fmt::Debug + Regularizable,
+	T: LogisticBinaryClassificationTrainable<Weights = W> + Clone,
+	F: Fn() -> W,
+{
+	let mut fold_scores = Vec::new();
+	for fold in fold_indices {
+		let mut held_out = vec![false; training_records.len()];
+		for index in fold {
+			held_out[*index] = true;
+		}
+		
+		let mut fold_training_records = Vec::new();
+		let mut fold_validation_records = Vec::new();
+		for (index, record) in training_records.iter().enumerate() {
+			if held_out[index] {
+				fold_validation_records.push(record.clone());
+			} else {
+				fold_training_records.push(record.clone());
+			}
+		}
+		
+		let mut weights = new_weights();
+		solve_with_settings(&fold_training_records, &mut weights, settings)?;
+		fold_scores.push(score_batch(&weights, &fold_validation_records)?);
+	}
+	Ok(fold_scores)
+}
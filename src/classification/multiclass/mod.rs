@@ -0,0 +1,246 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+
+use super::{quick_convert, BinaryClass, Classifier, LogisticBinaryClassificationTestable, LogisticBinaryClassificationTrainable, Regularizable, SolverSettings};
+use crate::{Feature, FeatureValue, PassengerFeatures, PassengerWeights};
+
+#[cfg(test)]
+mod tests;
+
+// The classes in the order they first appear among the records
+fn distinct_classes<R, C: PartialEq, F: Fn(&R) -> C>(records: &[R], label: &F) -> Vec<C> {
+	let mut classes = Vec::new();
+	for record in records {
+		let class = label(record);
+		if !classes.contains(&class) {
+			classes.push(class);
+		}
+	}
+	classes
+}
+
+fn class_index<C: PartialEq + fmt::Debug>(classes: &[C], class: &C) -> Result<usize, String> {
+	classes.iter().position(|other| other == class).ok_or_else(|| format!("Multiclass::class_index unknown class {:?}", class))
+}
+
+// Softmax of the scores, shifted by their maximum so that exp cannot overflow
+fn softmax(scores: &[f64]) -> Vec<f64> {
+	let max = scores.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+	let exps: Vec<f64> = scores.iter().map(|score| score.sub(max).exp()).collect();
+	let sum: f64 = exps.iter().sum();
+	exps.iter().map(|exp| exp.div(sum)).collect()
+}
+
+// Softmax of minus the weighted sum of every class
+fn class_probabilities<R: PassengerFeatures>(weights: &[PassengerWeights], record: &R) -> Result<Vec<f64>, String> {
+	let mut scores = Vec::new();
+	for class_weights in weights {
+		scores.push(-class_weights.weighted_sum(record)?);
+	}
+	Ok(softmax(&scores))
+}
+
+// Multinomial logistic regression with one PassengerWeights per class. The probability of a class is the softmax of
+// minus the weighted sums, so as for binary logistic regression a class grows likelier as its weighted sum falls, and
+// two classes with one of them at zero weights give logistic(weighted sum) for the other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoftmaxRegression<C> {
+	classes: Vec<C>,
+	weights: Vec<PassengerWeights>
+}
+
+impl<C: Clone + PartialEq + fmt::Debug> SoftmaxRegression<C> {
+	// Minimises the mean cross-entropy plus settings.l2 / 2 * |weights|^2 by batch gradient descent, with the learning
+	// rate, tolerance and iteration limit of the binary solver. label gives the class of a record.
+	pub fn fit<R, F>(records: &[R], label: F, features: &[Feature], settings: &SolverSettings) -> Result<SoftmaxRegression<C>, String>
+	where
+		R: PassengerFeatures,
+		F: Fn(&R) -> C,
+	{
+		let classes = distinct_classes(records, &label);
+		if classes.len() < 2 {
+			return Err(format!("SoftmaxRegression::fit needs records of at least two classes, got {:?}", classes))
+		}
+		let answers = records.iter().map(|record| class_index(&classes, &label(record))).collect::<Result<Vec<usize>, String>>()?;
+		let mut model = SoftmaxRegression {
+			weights: vec![PassengerWeights::from_schema(features, 0_f64, || 0_f64); classes.len()],
+			classes
+		};
+		let mut learning_rate = settings.learning_rate;
+		let mut cost = model.objective(records, &answers, &settings.l2)?;
+		let mut iterations = 0_u64;
+		while cost.gt(&settings.tolerance) && settings.max_iterations.is_none_or(|max_iterations| iterations < max_iterations) {
+			let current = model.weights.clone();
+			for (record, answer) in records.iter().zip(&answers) {
+				let probabilities = class_probabilities(&current, record)?;
+				for (index, (weights, probability)) in model.weights.iter_mut().zip(probabilities).enumerate() {
+					let target = if index == *answer { 1_f64 } else { 0_f64 };
+					// The derivative of the cross-entropy with respect to the weighted sum of the class is target - probability.
+					weights.update(record, &target.sub(probability).mul(-learning_rate))?;
+				}
+			}
+			if settings.l2.ne(&0_f64) {
+				for weights in model.weights.iter_mut() {
+					weights.shrink(&1_f64.sub(learning_rate.mul(settings.l2)));
+				}
+			}
+			iterations = iterations.add(1);
+			let new_cost = model.objective(records, &answers, &settings.l2)?;
+			if new_cost.gt(&cost) {
+				learning_rate = learning_rate.div(100_f64);
+				if settings.verbose {
+					eprintln!("SoftmaxRegression::fit Learning rate divided by 100 at iteration {}. New learning_rate: {}", iterations, learning_rate);
+				}
+			}
+			cost = new_cost;
+		}
+		if settings.verbose {
+			eprintln!("SoftmaxRegression::fit Stopped after {} iterations with avg_cost {} over classes {:?}", iterations, cost, model.classes);
+		}
+		Ok(model)
+	}
+
+	fn objective<R: PassengerFeatures>(&self, records: &[R], answers: &[usize], l2: &f64) -> Result<f64, String> {
+		let mut sum = 0_f64;
+		for (record, answer) in records.iter().zip(answers) {
+			sum = sum.sub(class_probabilities(&self.weights, record)?[*answer].max(f64::MIN_POSITIVE).ln());
+		}
+		let count = quick_convert(&records.len());
+		let penalty = self.weights.iter().fold(0_f64, |penalty, weights| penalty.add(weights.squared_norm())).mul(l2).div(2_f64.mul(count));
+		Ok(sum.div(count).add(penalty))
+	}
+
+	pub fn get_classes(&self) -> &Vec<C> {
+		&self.classes
+	}
+
+	pub fn get_weights(&self) -> &Vec<PassengerWeights> {
+		&self.weights
+	}
+
+	// The probability of every class, in the order of get_classes
+	pub fn predict_proba<R: PassengerFeatures>(&self, record: &R) -> Result<Vec<(C, f64)>, String> {
+		Ok(self.classes.iter().cloned().zip(class_probabilities(&self.weights, record)?).collect())
+	}
+
+	// The likeliest class
+	pub fn predict<R: PassengerFeatures>(&self, record: &R) -> Result<C, String> {
+		Ok(likeliest(self.predict_proba(record)?))
+	}
+}
+
+fn likeliest<C>(probabilities: Vec<(C, f64)>) -> C {
+	probabilities.into_iter().fold(None, |best: Option<(C, f64)>, (class, probability)| match best {
+		Some((ref _best_class, best_probability)) if best_probability >= probability => best,
+		_ => Some((class, probability)),
+	}).map(|(class, _probability)| class).unwrap()
+}
+
+// A record whose answer is whether it belongs to one class, for the binary classifiers of a OneVsRest
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relabelled<R> {
+	pub record: R,
+	pub answer: BinaryClass
+}
+
+impl<R: LogisticBinaryClassificationTestable> LogisticBinaryClassificationTestable for Relabelled<R> {
+	type Weights = R::Weights;
+
+	fn hypothesis(self: &Self, weights: &Self::Weights) -> Result<f64, String> {
+		self.record.hypothesis(weights)
+	}
+
+	fn get_record_id(self: &Self) -> &u64 {
+		self.record.get_record_id()
+	}
+}
+
+impl<R: LogisticBinaryClassificationTrainable> LogisticBinaryClassificationTrainable for Relabelled<R> {
+	fn answer(self: &Self) -> BinaryClass {
+		self.answer
+	}
+
+	fn update_weights(self: &Self, diff: &f64, weights: &mut Self::Weights) -> Result<(), String> {
+		self.record.update_weights(diff, weights)
+	}
+}
+
+impl<R: PassengerFeatures> PassengerFeatures for Relabelled<R> {
+	fn get_passenger_id(&self) -> &u64 {
+		self.record.get_passenger_id()
+	}
+
+	fn get_feature(&self, feature: &Feature) -> FeatureValue {
+		self.record.get_feature(feature)
+	}
+}
+
+// One binary Classifier per class, each fitted to tell that class from all the others. Their probabilities are
+// normalised to sum to 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OneVsRest<C, M> {
+	classes: Vec<C>,
+	classifiers: Vec<M>
+}
+
+impl<C: Clone + PartialEq + fmt::Debug, M> OneVsRest<C, M> {
+	// label gives the class of a record and new_classifier an unfitted binary classifier.
+	pub fn fit<R, F, N>(records: &[R], label: F, new_classifier: N) -> Result<OneVsRest<C, M>, String>
+	where
+		R: Clone,
+		F: Fn(&R) -> C,
+		N: Fn() -> M,
+		M: Classifier<Relabelled<R>>,
+	{
+		let classes = distinct_classes(records, &label);
+		if classes.len() < 2 {
+			return Err(format!("OneVsRest::fit needs records of at least two classes, got {:?}", classes))
+		}
+		let mut classifiers = Vec::new();
+		for class in &classes {
+			let relabelled: Vec<Relabelled<R>> = records.iter().map(|record| Relabelled {
+				record: record.clone(),
+				answer: if label(record) == *class { BinaryClass::Yes } else { BinaryClass::No }
+			}).collect();
+			let mut classifier = new_classifier();
+			classifier.fit(&relabelled)?;
+			classifiers.push(classifier);
+			eprintln!("OneVsRest::fit Fitted class {:?} against the rest", class);
+		}
+		Ok(OneVsRest { classes, classifiers })
+	}
+
+	pub fn get_classes(&self) -> &Vec<C> {
+		&self.classes
+	}
+
+	pub fn get_classifiers(&self) -> &Vec<M> {
+		&self.classifiers
+	}
+
+	// The probability of every class, in the order of get_classes
+	pub fn predict_proba<R: Clone>(&self, record: &R) -> Result<Vec<(C, f64)>, String>
+	where
+		M: Classifier<Relabelled<R>>,
+	{
+		// The answer is never read when scoring.
+		let relabelled = Relabelled { record: record.clone(), answer: BinaryClass::No };
+		let mut probabilities = Vec::new();
+		for classifier in &self.classifiers {
+			probabilities.push(classifier.predict_proba(&relabelled)?);
+		}
+		let sum: f64 = probabilities.iter().sum();
+		let count = quick_convert(&probabilities.len());
+		Ok(self.classes.iter().cloned().zip(probabilities.iter().map(|probability| {
+			if sum > 0_f64 { probability.div(sum) } else { 1_f64.div(count) }
+		})).collect())
+	}
+
+	// The likeliest class
+	pub fn predict<R: Clone>(&self, record: &R) -> Result<C, String>
+	where
+		M: Classifier<Relabelled<R>>,
+	{
+		Ok(likeliest(self.predict_proba(record)?))
+	}
+}
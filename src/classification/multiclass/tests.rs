@@ -0,0 +1,71 @@
+use super::*;
+use crate::classification::LogisticRegression;
+use crate::test_support::passengers;
+use crate::TrainingPassenger;
+
+// The port is decided by the passenger class: first class boarded at Cherbourg, second at Queenstown, third at Southampton.
+// test_support::training_passengers all board at Southampton, so these are read from rows instead.
+fn training_passengers() -> Vec<TrainingPassenger> {
+	let rows: Vec<String> = (0..60_u64).map(|id| {
+		let (class, port) = match id % 3 {
+			0 => (1, "C"),
+			1 => (2, "Q"),
+			_ => (3, "S"),
+		};
+		format!("{},{},{},Passenger {},{},{},0,0,Ticket {},10,,{}", id, (id + 1) % 2, class, id, if id % 4 < 2 { "female" } else { "male" }, 20 + id % 7, id, port)
+	}).collect();
+	passengers(&rows.iter().map(|row| row.as_str()).collect::<Vec<&str>>())
+}
+
+fn port(passenger: &TrainingPassenger) -> FeatureValue {
+	passenger.get_feature(&Feature::PortOfEmbarkation)
+}
+
+fn settings() -> SolverSettings {
+	SolverSettings { max_iterations: Some(200), verbose: false, ..SolverSettings::new(0.01_f64, 0.05_f64) }
+}
+
+#[test]
+fn when_softmax_fitted_then_predicts_every_class() {
+	let passengers = training_passengers();
+	let model = SoftmaxRegression::fit(&passengers, port, &[Feature::PassengerClass, Feature::Sex], &settings()).unwrap();
+
+	assert_eq!(model.get_classes().len(), 3);
+	for passenger in &passengers {
+		let probabilities = model.predict_proba(passenger).unwrap();
+		assert_abs_diff_eq!(probabilities.iter().map(|(_class, probability)| probability).sum::<f64>(), 1_f64, epsilon = 1e-12);
+		assert_eq!(model.predict(passenger).unwrap(), port(passenger));
+	}
+}
+
+#[test]
+fn when_two_classes_with_zero_reference_then_softmax_is_logistic() {
+	let passenger = &training_passengers()[0];
+	let mut weights = PassengerWeights::from_schema(&[Feature::Sex, Feature::Age], 0.3_f64, || 0.02_f64);
+	weights.update(passenger, &0.1_f64).unwrap();
+	let zero = PassengerWeights::from_schema(&[Feature::Sex, Feature::Age], 0_f64, || 0_f64);
+	let model = SoftmaxRegression { classes: vec![BinaryClass::Yes, BinaryClass::No], weights: vec![weights.clone(), zero] };
+
+	let probabilities = model.predict_proba(passenger).unwrap();
+	assert_abs_diff_eq!(probabilities[0].1, passenger.hypothesis(&weights).unwrap(), epsilon = 1e-12);
+}
+
+#[test]
+fn when_one_vs_rest_over_logistic_regression_then_predicts_every_class() {
+	let passengers = training_passengers();
+	let features = [Feature::PassengerClass];
+	let model = OneVsRest::fit(&passengers, port, || LogisticRegression::new(PassengerWeights::from_schema(&features, 0_f64, || 0_f64), settings())).unwrap();
+
+	assert_eq!(model.get_classifiers().len(), 3);
+	for passenger in &passengers {
+		let probabilities = model.predict_proba(passenger).unwrap();
+		assert_abs_diff_eq!(probabilities.iter().map(|(_class, probability)| probability).sum::<f64>(), 1_f64, epsilon = 1e-12);
+		assert_eq!(model.predict(passenger).unwrap(), port(passenger));
+	}
+}
+
+#[test]
+fn when_one_class_then_error() {
+	let passengers: Vec<TrainingPassenger> = training_passengers().into_iter().filter(|passenger| passenger.get_passenger_id() % 3 == 0).collect();
+	assert!(SoftmaxRegression::fit(&passengers, port, &[Feature::Sex], &settings()).is_err());
+}
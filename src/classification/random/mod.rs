@@ -0,0 +1,62 @@
+use std::ops::{Add, Mul, Sub};
+
+#[cfg(test)]
+mod tests;
+
+// SplitMix64: small, fast and fully determined by its seed, so a run can be repeated bit for bit on any platform.
+#[derive(Debug, Clone)]
+pub struct Rng {
+	state: u64
+}
+
+impl Rng {
+	pub fn new(seed: u64) -> Rng {
+		Rng { state: seed }
+	}
+	
+	pub fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^ (z >> 31)
+	}
+	
+	// Uniform in [0, 1)
+	pub fn next_f64(&mut self) -> f64 {
+		((self.next_u64() >> 11) as f64).mul(1_f64 / (1_u64 << 53) as f64)
+	}
+	
+	// Uniform in [low, high)
+	pub fn uniform(&mut self, low: f64, high: f64) -> f64 {
+		low.add(high.sub(low).mul(self.next_f64()))
+	}
+	
+	// Uniform in 0..bound, without modulo bias
+	pub fn below(&mut self, bound: usize) -> usize {
+		assert!(bound > 0, "Rng::below bound must be positive");
+		let bound = bound as u64;
+		let zone = u64::MAX - (u64::MAX % bound);
+		loop {
+			let value = self.next_u64();
+			if value < zone {
+				return (value % bound) as usize
+			}
+		}
+	}
+	
+	// Normal with the given mean and standard deviation, by the Box-Muller transform
+	pub fn normal(&mut self, mean: f64, std: f64) -> f64 {
+		// 1 - next_f64 lies in (0, 1], so the logarithm is finite
+		let radius = (-2_f64.mul(1_f64.sub(self.next_f64()).ln())).sqrt();
+		let angle = 2_f64.mul(std::f64::consts::PI).mul(self.next_f64());
+		mean.add(std.mul(radius.mul(angle.cos())))
+	}
+	
+	pub fn shuffle<T>(&mut self, items: &mut [T]) {
+		for index in (1..items.len()).rev() {
+			let other = self.below(index + 1);
+			items.swap(index, other);
+		}
+	}
+}
@@ -0,0 +1,43 @@
+use super::*;
+
+#[test]
+fn when_same_seed_then_same_sequence() {
+	let mut first = Rng::new(7_u64);
+	let mut second = Rng::new(7_u64);
+	let mut third = Rng::new(8_u64);
+	
+	let first_values: Vec<u64> = (0..5).map(|_| first.next_u64()).collect();
+	let second_values: Vec<u64> = (0..5).map(|_| second.next_u64()).collect();
+	let third_values: Vec<u64> = (0..5).map(|_| third.next_u64()).collect();
+	
+	assert_eq!(first_values, second_values);
+	assert_ne!(first_values, third_values);
+}
+
+#[test]
+fn when_shuffled_then_permutation_within_bounds() {
+	let mut rng = Rng::new(1_u64);
+	let mut items: Vec<usize> = (0..20).collect();
+	rng.shuffle(&mut items);
+	
+	let mut sorted = items.clone();
+	sorted.sort();
+	assert_eq!(sorted, (0..20).collect::<Vec<usize>>());
+	assert_ne!(items, sorted);
+	for _draw in 0..100 {
+		assert!(rng.below(3) < 3);
+		let value = rng.uniform(-1_f64, 1_f64);
+		assert!((-1_f64..1_f64).contains(&value));
+	}
+}
+
+#[test]
+fn when_normal_then_sample_mean_and_std_close() {
+	let mut rng = Rng::new(11_u64);
+	let samples: Vec<f64> = (0..10000).map(|_| rng.normal(2_f64, 3_f64)).collect();
+	let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+	let variance = samples.iter().map(|sample| (sample - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+	
+	assert_abs_diff_eq!(mean, 2_f64, epsilon = 0.1);
+	assert_abs_diff_eq!(variance.sqrt(), 3_f64, epsilon = 0.1);
+}
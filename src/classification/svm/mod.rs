@@ -0,0 +1,246 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{quick_convert, random::Rng, BinaryClass, Classifier, Labelled, Regularizable};
+use crate::{Feature, FeatureValue, PassengerFeatures, PassengerWeights};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HingeLoss {
+	// max(0, 1 - margin)
+	Hinge,
+	// max(0, 1 - margin) squared, which is smooth at the margin and punishes large violations harder
+	SquaredHinge
+}
+
+impl FromStr for HingeLoss {
+	type Err = String;
+
+	fn from_str(name: &str) -> Result<HingeLoss, String> {
+		match name {
+			"hinge" => Ok(HingeLoss::Hinge),
+			"squared_hinge" => Ok(HingeLoss::SquaredHinge),
+			_ => Err(format!("HingeLoss::from_str unknown loss {}, expected one of hinge, squared_hinge", name)),
+		}
+	}
+}
+
+impl fmt::Display for HingeLoss {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			HingeLoss::Hinge => write!(f, "hinge"),
+			HingeLoss::SquaredHinge => write!(f, "squared_hinge"),
+		}
+	}
+}
+
+impl HingeLoss {
+	pub fn loss(&self, margin: f64) -> f64 {
+		let violation = 1_f64.sub(margin).max(0_f64);
+		match self {
+			HingeLoss::Hinge => violation,
+			HingeLoss::SquaredHinge => violation.mul(violation),
+		}
+	}
+
+	// Minus the derivative of the loss with respect to the margin
+	fn slope(&self, margin: f64) -> f64 {
+		let violation = 1_f64.sub(margin).max(0_f64);
+		match self {
+			HingeLoss::Hinge => if violation > 0_f64 { 1_f64 } else { 0_f64 },
+			HingeLoss::SquaredHinge => 2_f64.mul(violation),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SvmSettings {
+	pub loss: HingeLoss,
+	// Strength of the L2 penalty lambda / 2 * |w|^2, where w includes the bias
+	pub lambda: f64,
+	// Passes over the training records, each in a fresh random order
+	pub epochs: usize
+}
+
+impl Default for SvmSettings {
+	fn default() -> Self {
+		SvmSettings {
+			loss: HingeLoss::Hinge,
+			lambda: 0.01_f64,
+			epochs: 20
+		}
+	}
+}
+
+fn sign(label: &BinaryClass) -> f64 {
+	match label {
+		BinaryClass::Yes => 1_f64,
+		BinaryClass::No => -1_f64,
+	}
+}
+
+// Mean and standard deviation of the numbers of a feature among the training records
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Standardization {
+	pub feature: Feature,
+	pub mean: f64,
+	pub std: f64
+}
+
+// A record whose numbers are standardized, so that fares in the hundreds do not swamp the step size.
+struct Standardized<'a, R> {
+	record: &'a R,
+	standardizations: &'a [Standardization]
+}
+
+impl<'a, R: PassengerFeatures> PassengerFeatures for Standardized<'a, R> {
+	fn get_passenger_id(&self) -> &u64 {
+		self.record.get_passenger_id()
+	}
+
+	fn get_feature(&self, feature: &Feature) -> FeatureValue {
+		match (self.record.get_feature(feature), self.standardizations.iter().find(|standardization| standardization.feature == *feature)) {
+			(FeatureValue::Number(number), Some(standardization)) => FeatureValue::Number(number.sub(standardization.mean).div(standardization.std)),
+			(value, _standardization) => value,
+		}
+	}
+}
+
+fn standardizations<R: PassengerFeatures>(records: &[R], features: &[Feature]) -> Vec<Standardization> {
+	features.iter().filter_map(|feature| {
+		let numbers: Vec<f64> = records.iter().filter_map(|record| match record.get_feature(feature) {
+			FeatureValue::Number(number) => Some(number),
+			_ => None,
+		}).collect();
+		if numbers.is_empty() {
+			return None
+		}
+		let count = quick_convert(&numbers.len());
+		let mean = numbers.iter().fold(0_f64, |sum, number| sum.add(number)).div(count);
+		let std = numbers.iter().fold(0_f64, |sum, number| sum.add(number.sub(mean).powi(2))).div(count).sqrt();
+		Some(Standardization { feature: *feature, mean, std: if std > 0_f64 { std } else { 1_f64 } })
+	}).collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LinearSvm {
+	settings: SvmSettings,
+	seed: u64,
+	standardizations: Vec<Standardization>,
+	// Weights of the standardized numbers
+	weights: PassengerWeights
+}
+
+impl LinearSvm {
+	// Pegasos on the standardized numbers: stochastic subgradient descent on lambda / 2 * |w|^2 + mean loss, with step 1 / (lambda * t) at step t
+	// and a projection onto the ball of radius 1 / sqrt(lambda) that holds the optimum. The bias is penalised with the
+	// other weights, as in the original algorithm, which keeps the early steps from throwing it arbitrarily far.
+	// The weights are those of PassengerWeights, so BinaryClass::Yes lies on the side where the weighted sum is
+	// negative, as for logistic regression.
+	pub fn fit<R: PassengerFeatures + Labelled>(records: &[R], features: &[Feature], settings: &SvmSettings, seed: u64) -> Result<LinearSvm, String> {
+		if records.is_empty() || features.is_empty() {
+			return Err("LinearSvm::fit needs at least one record and feature".to_string())
+		}
+		if settings.lambda <= 0_f64 || settings.epochs == 0 {
+			return Err(format!("LinearSvm::fit lambda {} should be positive and epochs {} at least 1", settings.lambda, settings.epochs))
+		}
+		let mut rng = Rng::new(seed);
+		let mut svm = LinearSvm {
+			settings: settings.clone(),
+			seed,
+			standardizations: standardizations(records, features),
+			weights: PassengerWeights::from_schema(features, 0_f64, || 0_f64)
+		};
+		let radius = 1_f64.div(settings.lambda.sqrt());
+		let mut order: Vec<usize> = (0..records.len()).collect();
+		let mut step = 0_f64;
+		for _epoch in 0..settings.epochs {
+			rng.shuffle(&mut order);
+			for index in &order {
+				step = step.add(1_f64);
+				let learning_rate = 1_f64.div(settings.lambda.mul(step));
+				let record = &records[*index];
+				let label = sign(&record.label());
+				let slope = settings.loss.slope(label.mul(svm.margin(record)?));
+				svm.weights.scale(&1_f64.sub(learning_rate.mul(settings.lambda)));
+				if slope > 0_f64 {
+					// The margin is minus the weighted sum, so a step towards the label lowers the weighted sum.
+					let standardized = Standardized { record, standardizations: &svm.standardizations };
+					svm.weights.update(&standardized, &-learning_rate.mul(slope).mul(label))?;
+				}
+				let norm = svm.squared_norm().sqrt();
+				if norm > radius {
+					svm.weights.scale(&radius.div(norm));
+				}
+			}
+		}
+
+		let mut loss = 0_f64;
+		let mut violations = 0_usize;
+		for record in records {
+			let margin = sign(&record.label()).mul(svm.margin(record)?);
+			loss = loss.add(settings.loss.loss(margin));
+			if margin < 1_f64 {
+				violations = violations.add(1);
+			}
+		}
+		let objective = settings.lambda.div(2_f64).mul(svm.squared_norm()).add(loss.div(quick_convert(&records.len())));
+		eprintln!("LinearSvm::fit Trained with {} loss and lambda {} for {} epochs: objective {}, {} of {} records on or inside the margin", settings.loss, settings.lambda, settings.epochs, objective, violations, records.len());
+		Ok(svm)
+	}
+
+	fn standardize<'a, R>(&'a self, record: &'a R) -> Standardized<'a, R> {
+		Standardized { record, standardizations: &self.standardizations }
+	}
+
+	fn squared_norm(&self) -> f64 {
+		self.weights.squared_norm().add(self.weights.get_bias().powi(2))
+	}
+
+	pub fn get_settings(&self) -> &SvmSettings {
+		&self.settings
+	}
+
+	pub fn get_seed(&self) -> &u64 {
+		&self.seed
+	}
+
+	pub fn get_standardizations(&self) -> &Vec<Standardization> {
+		&self.standardizations
+	}
+
+	pub fn get_weights(&self) -> &PassengerWeights {
+		&self.weights
+	}
+
+	// Signed distance-like score, positive for BinaryClass::Yes
+	pub fn margin<R: PassengerFeatures>(&self, record: &R) -> Result<f64, String> {
+		Ok(-self.weights.weighted_sum(&self.standardize(record))?)
+	}
+
+	// The margin squashed into (0, 1) with the logistic function. It is not a calibrated probability, but its logit is
+	// the margin itself, which is what Platt scaling fits.
+	pub fn probability<R: PassengerFeatures>(&self, record: &R) -> Result<f64, String> {
+		Ok(1_f64.div((-self.margin(record)?).exp().add(1_f64)))
+	}
+}
+
+// fit trains the weights again on the records, over the features and with the settings and seed it was trained with.
+impl<R: PassengerFeatures + Labelled> Classifier<R> for LinearSvm {
+	fn fit(&mut self, records: &[R]) -> Result<(), String> {
+		let features = self.weights.get_features().clone();
+		*self = LinearSvm::fit(records, &features, &self.settings, self.seed)?;
+		Ok(())
+	}
+
+	fn predict_proba(&self, record: &R) -> Result<f64, String> {
+		self.probability(record)
+	}
+}
@@ -0,0 +1,56 @@
+use super::*;
+use crate::test_support::passengers;
+use crate::TrainingPassenger;
+
+fn separable_passengers() -> Vec<TrainingPassenger> {
+	passengers(&[
+		"1,1,1,A,female,30,0,0,T,10,,S",
+		"2,1,3,B,female,40,0,0,T,10,,S",
+		"3,1,2,C,female,20,0,0,T,10,,S",
+		"4,0,1,D,male,30,0,0,T,10,,S",
+		"5,0,3,E,male,40,0,0,T,10,,S",
+		"6,0,2,F,male,20,0,0,T,10,,S",
+	])
+}
+
+#[test]
+fn when_margin_given_then_hinge_losses_match_definition() {
+	assert_abs_diff_eq!(HingeLoss::Hinge.loss(2_f64), 0_f64);
+	assert_abs_diff_eq!(HingeLoss::Hinge.loss(0.25_f64), 0.75_f64);
+	assert_abs_diff_eq!(HingeLoss::SquaredHinge.loss(-1_f64), 4_f64);
+	assert_abs_diff_eq!(HingeLoss::SquaredHinge.slope(0.5_f64), 1_f64);
+}
+
+#[test]
+fn when_classes_separable_then_both_losses_separate_them() {
+	let records = separable_passengers();
+	for loss in [HingeLoss::Hinge, HingeLoss::SquaredHinge].iter() {
+		let settings = SvmSettings { loss: *loss, lambda: 0.1_f64, epochs: 200 };
+		let svm = LinearSvm::fit(&records, &[Feature::Sex], &settings, 1_u64).unwrap();
+
+		for record in &records {
+			let margin = svm.margin(record).unwrap();
+			assert_eq!(margin > 0_f64, record.label() == BinaryClass::Yes);
+			assert_eq!(svm.probability(record).unwrap() > 0.5_f64, margin > 0_f64);
+		}
+		assert_eq!(svm, LinearSvm::fit(&records, &[Feature::Sex], &settings, 1_u64).unwrap());
+	}
+}
+
+#[test]
+fn when_lambda_not_positive_then_error() {
+	let settings = SvmSettings { lambda: 0_f64, ..SvmSettings::default() };
+	assert!(LinearSvm::fit(&separable_passengers(), &Feature::ALL, &settings, 1_u64).is_err());
+}
+
+#[test]
+fn when_numeric_feature_then_standardized_before_training() {
+	let records = separable_passengers();
+	let svm = LinearSvm::fit(&records, &[Feature::Sex, Feature::Age], &SvmSettings::default(), 1_u64).unwrap();
+
+	assert_eq!(svm.get_standardizations().len(), 1);
+	let age = &svm.get_standardizations()[0];
+	assert_eq!(age.feature, Feature::Age);
+	assert_abs_diff_eq!(age.mean, 30_f64);
+	assert_abs_diff_eq!(age.std, (200_f64 / 3_f64).sqrt(), epsilon = 1e-12);
+}
@@ -18,6 +18,7 @@ struct TestData {
 	pub weight: f64
 }
 
+#[derive(Debug)]
 struct FootbalFanWeights {
 	pub age: f64,
 	pub weight: f64
@@ -26,12 +27,23 @@ struct FootbalFanWeights {
 impl Clone for FootbalFanWeights {
 	fn clone(&self) -> Self {
 		FootbalFanWeights {
-			age: self.age.clone(),
-			weight: self.weight.clone()
+			age: self.age,
+			weight: self.weight
 		}
 	}
 }
 
+impl Regularizable for FootbalFanWeights {
+	fn shrink(self: &mut Self, factor: &f64) {
+		self.age = self.age.mul(factor);
+		self.weight = self.weight.mul(factor);
+	}
+	
+	fn squared_norm(self: &Self) -> f64 {
+		self.age.mul(self.age).add(self.weight.mul(self.weight))
+	}
+}
+
 impl LogisticBinaryClassificationTestable for TrainingData {
 	type Weights = FootbalFanWeights;
 	
@@ -231,9 +243,68 @@ fn when_new_training_data_and_weights_then_avg_cost_correct() {
 		weight: 1_f64
 	};
 	
-	let mut training_data = Vec::new();
-	training_data.push(training_data1);
-	training_data.push(training_data2);
+	let training_data = vec![training_data1, training_data2];
 	
 	assert_abs_diff_eq!(avg_cost(&training_data, &footbal_fan_weights).unwrap(), ((- 1_f64.div(2_f64.exp().add(1_f64)).ln()) + (- (1_f64 - 1_f64.div(2_f64.exp().add(1_f64))).ln())).div(2_f64));
-}
\ No newline at end of file
+}
+
+#[test]
+fn when_one_solve_step_on_lone_no_record_then_cost_falls() {
+	let training_data = vec![TrainingData {
+		party_id: 1_u64,
+		age: 1_f64,
+		weight: 1_f64,
+		footbal_fan: FootbalFan::No
+	}];
+	
+	let mut footbal_fan_weights = FootbalFanWeights {
+		age: 1_f64,
+		weight: 1_f64
+	};
+	
+	let initial_cost = training_data[0].cost(&footbal_fan_weights).unwrap();
+	let mut settings = SolverSettings::new(0.1_f64, 0_f64);
+	settings.max_iterations = Some(1_u64);
+	settings.verbose = false;
+	solve_with_settings(&training_data, &mut footbal_fan_weights, &settings).unwrap();
+	
+	assert!(training_data[0].cost(&footbal_fan_weights).unwrap() < initial_cost);
+}
+
+#[test]
+fn when_threshold_given_then_predict_with_threshold_uses_it() {
+	let test_data = TestData {
+		party_id: 1_u64,
+		age: -1_f64,
+		weight: 0_f64
+	};
+	
+	let footbal_fan_weights = FootbalFanWeights {
+		age: 1_f64,
+		weight: 1_f64
+	};
+	
+	let probability = 1_f64.div((-1_f64).exp().add(1_f64));
+	assert_eq!(predict(&footbal_fan_weights, &test_data).unwrap(), Outcome { record_id: 1_u64, prediction: BinaryClass::Yes, probability });
+	assert_eq!(predict_with_threshold(&footbal_fan_weights, &test_data, &0.8_f64).unwrap().prediction, BinaryClass::No);
+}
+
+#[test]
+fn when_split_holdout_then_tail_held_out() {
+	let (training, holdout) = split_holdout(vec![1, 2, 3, 4, 5], &0.4_f64).unwrap();
+	
+	assert_eq!(training, vec![1, 2, 3]);
+	assert_eq!(holdout, vec![4, 5]);
+	assert!(split_holdout(vec![1, 2], &0.1_f64).is_err());
+}
+
+#[test]
+fn when_k_fold_indices_shuffled_then_every_record_in_one_fold_and_repeatable() {
+	let fold_indices = k_fold_indices_shuffled(10, 3, &mut Rng::new(5_u64)).unwrap();
+	
+	assert_eq!(fold_indices, k_fold_indices_shuffled(10, 3, &mut Rng::new(5_u64)).unwrap());
+	assert_eq!(fold_indices.iter().map(|fold| fold.len()).collect::<Vec<usize>>(), vec![4, 3, 3]);
+	let mut all: Vec<usize> = fold_indices.concat();
+	all.sort_unstable();
+	assert_eq!(all, (0..10).collect::<Vec<usize>>());
+}
@@ -0,0 +1,476 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{quick_convert, random::Rng, BinaryClass, Classifier, Labelled};
+use crate::{Feature, FeatureValue, PassengerFeatures};
+
+#[cfg(test)]
+mod tests;
+
+// A split must lower the weighted impurity by more than this to be kept.
+const MIN_IMPURITY_DECREASE: f64 = 1e-12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Criterion {
+	Gini,
+	Entropy
+}
+
+impl FromStr for Criterion {
+	type Err = String;
+
+	fn from_str(name: &str) -> Result<Criterion, String> {
+		match name {
+			"gini" => Ok(Criterion::Gini),
+			"entropy" => Ok(Criterion::Entropy),
+			_ => Err(format!("Criterion::from_str unknown criterion {}, expected one of gini, entropy", name)),
+		}
+	}
+}
+
+impl fmt::Display for Criterion {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Criterion::Gini => write!(f, "gini"),
+			Criterion::Entropy => write!(f, "entropy"),
+		}
+	}
+}
+
+impl Criterion {
+	pub fn impurity(&self, yes: usize, no: usize) -> f64 {
+		let total = yes.add(no);
+		if total == 0 {
+			return 0_f64
+		}
+		let p = quick_convert(&yes).div(quick_convert(&total));
+		match self {
+			Criterion::Gini => 2_f64.mul(p).mul(1_f64.sub(p)),
+			Criterion::Entropy => {
+				let term = |q: f64| if q > 0_f64 { -q.mul(q.log2()) } else { 0_f64 };
+				term(p).add(term(1_f64.sub(p)))
+			},
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TreeSettings {
+	pub criterion: Criterion,
+	// None grows until the leaves are pure or too small to split
+	pub max_depth: Option<usize>,
+	pub min_samples_leaf: usize,
+	// Cost-complexity parameter: subtrees that lower the weighted impurity by less than ccp_alpha per extra leaf are pruned
+	pub ccp_alpha: f64,
+	// None considers every feature at each split, otherwise this many drawn at random per split
+	pub max_features: Option<usize>
+}
+
+impl Default for TreeSettings {
+	fn default() -> Self {
+		TreeSettings {
+			criterion: Criterion::Gini,
+			max_depth: None,
+			min_samples_leaf: 1,
+			ccp_alpha: 0_f64,
+			max_features: None
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Split {
+	// Numbers up to threshold go left, missing numbers go left when missing_left
+	Numeric { feature: Feature, threshold: f64, missing_left: bool },
+	// Categories seen on either side during training, a missing value being category 0.
+	// A category seen on neither side follows the child that saw more records.
+	Categorical { feature: Feature, left: Vec<usize>, right: Vec<usize> }
+}
+
+// Missing is category 0, Present is category 1 and enum variants keep their category number.
+pub(crate) fn category(value: &FeatureValue) -> Option<usize> {
+	match value {
+		FeatureValue::Missing => Some(0),
+		FeatureValue::Present => Some(1),
+		FeatureValue::Category(category) => Some(*category),
+		FeatureValue::Number(_number) => None,
+	}
+}
+
+impl Split {
+	pub fn get_feature(&self) -> &Feature {
+		match self {
+			Split::Numeric { feature, .. } => feature,
+			Split::Categorical { feature, .. } => feature,
+		}
+	}
+
+	// None when the value was not seen during training and the caller has to choose.
+	pub fn goes_left(&self, value: &FeatureValue) -> Option<bool> {
+		match self {
+			Split::Numeric { threshold, missing_left, .. } => match value {
+				FeatureValue::Number(number) => Some(number <= threshold),
+				_ => Some(*missing_left),
+			},
+			Split::Categorical { left, right, .. } => match category(value) {
+				Some(category) if left.contains(&category) => Some(true),
+				Some(category) if right.contains(&category) => Some(false),
+				_ => None,
+			},
+		}
+	}
+}
+
+impl fmt::Display for Split {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Split::Numeric { feature, threshold, missing_left } => {
+				write!(f, "{} <= {}", feature, threshold)?;
+				if *missing_left {
+					write!(f, " or missing")?;
+				}
+				Ok(())
+			},
+			Split::Categorical { feature, left, .. } => write!(f, "{} in {:?}", feature, left),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Node {
+	Leaf { yes: usize, no: usize },
+	Branch { yes: usize, no: usize, split: Split, left: Box<Node>, right: Box<Node> }
+}
+
+impl Node {
+	pub fn get_counts(&self) -> (usize, usize) {
+		match self {
+			Node::Leaf { yes, no } => (*yes, *no),
+			Node::Branch { yes, no, .. } => (*yes, *no),
+		}
+	}
+
+	fn size(&self) -> usize {
+		let (yes, no) = self.get_counts();
+		yes.add(no)
+	}
+
+	pub fn get_depth(&self) -> usize {
+		match self {
+			Node::Leaf { .. } => 0,
+			Node::Branch { left, right, .. } => 1_usize.add(left.get_depth().max(right.get_depth())),
+		}
+	}
+
+	pub fn get_leaf_count(&self) -> usize {
+		match self {
+			Node::Leaf { .. } => 1,
+			Node::Branch { left, right, .. } => left.get_leaf_count().add(right.get_leaf_count()),
+		}
+	}
+
+	// Share of the training records in this node's leaves that answered Yes
+	fn probability<R: PassengerFeatures>(&self, record: &R) -> f64 {
+		match self {
+			Node::Leaf { yes, no } => quick_convert(yes).div(quick_convert(&yes.add(*no))),
+			Node::Branch { split, left, right, .. } => {
+				let goes_left = split.goes_left(&record.get_feature(split.get_feature())).unwrap_or(left.size() >= right.size());
+				if goes_left {
+					left.probability(record)
+				} else {
+					right.probability(record)
+				}
+			},
+		}
+	}
+
+	// Impurity of the leaves below, each weighted by its share of all total training records
+	fn leaf_risk(&self, criterion: &Criterion, total: usize) -> f64 {
+		match self {
+			Node::Leaf { yes, no } => node_risk(criterion, *yes, *no, total),
+			Node::Branch { left, right, .. } => left.leaf_risk(criterion, total).add(right.leaf_risk(criterion, total)),
+		}
+	}
+
+	// How much weighted impurity each extra leaf of this subtree removes. Branches with the smallest value are pruned first.
+	fn complexity_gain(&self, criterion: &Criterion, total: usize) -> Option<f64> {
+		match self {
+			Node::Leaf { .. } => None,
+			Node::Branch { yes, no, .. } => {
+				let gain = node_risk(criterion, *yes, *no, total).sub(self.leaf_risk(criterion, total));
+				Some(gain.div(quick_convert(&self.get_leaf_count()).sub(1_f64)))
+			},
+		}
+	}
+
+	fn weakest_link(&self, criterion: &Criterion, total: usize) -> Option<f64> {
+		match self {
+			Node::Leaf { .. } => None,
+			Node::Branch { left, right, .. } => {
+				let mut weakest = self.complexity_gain(criterion, total);
+				for child in [left, right].iter() {
+					if let Some(gain) = child.weakest_link(criterion, total) {
+						weakest = Some(weakest.map_or(gain, |weakest| weakest.min(gain)));
+					}
+				}
+				weakest
+			},
+		}
+	}
+
+	fn collapse(&mut self, criterion: &Criterion, total: usize, gain: f64) {
+		if let Some(own_gain) = self.complexity_gain(criterion, total) {
+			if own_gain <= gain.add(MIN_IMPURITY_DECREASE) {
+				let (yes, no) = self.get_counts();
+				*self = Node::Leaf { yes, no };
+			} else if let Node::Branch { left, right, .. } = self {
+				left.collapse(criterion, total, gain);
+				right.collapse(criterion, total, gain);
+			}
+		}
+	}
+}
+
+fn node_risk(criterion: &Criterion, yes: usize, no: usize, total: usize) -> f64 {
+	criterion.impurity(yes, no).mul(quick_convert(&yes.add(no))).div(quick_convert(&total))
+}
+
+// Weighted impurity decrease of a split, or None when a side would hold fewer than min_samples_leaf records.
+fn split_gain(settings: &TreeSettings, parent: (usize, usize), left: (usize, usize)) -> Option<f64> {
+	let right = (parent.0.sub(left.0), parent.1.sub(left.1));
+	let (left_size, right_size) = (left.0.add(left.1), right.0.add(right.1));
+	if left_size < settings.min_samples_leaf || right_size < settings.min_samples_leaf {
+		return None
+	}
+	let total = quick_convert(&parent.0.add(parent.1));
+	let criterion = &settings.criterion;
+	let children = criterion.impurity(left.0, left.1).mul(quick_convert(&left_size))
+		.add(criterion.impurity(right.0, right.1).mul(quick_convert(&right_size)))
+		.div(total);
+	Some(criterion.impurity(parent.0, parent.1).sub(children))
+}
+
+fn count(labels: &[BinaryClass]) -> (usize, usize) {
+	let yes = labels.iter().filter(|label| **label == BinaryClass::Yes).count();
+	(yes, labels.len().sub(yes))
+}
+
+fn add_label(counts: &mut (usize, usize), label: &BinaryClass) {
+	match label {
+		BinaryClass::Yes => counts.0 = counts.0.add(1),
+		BinaryClass::No => counts.1 = counts.1.add(1),
+	}
+}
+
+// Sweeps the thresholds between consecutive distinct numbers, trying missing numbers on either side.
+fn best_numeric_split(settings: &TreeSettings, feature: &Feature, values: &[FeatureValue], labels: &[BinaryClass]) -> Option<(f64, Split)> {
+	let parent = count(labels);
+	let mut numbers = Vec::new();
+	let mut missing = (0_usize, 0_usize);
+	for (value, label) in values.iter().zip(labels) {
+		match value {
+			FeatureValue::Number(number) => numbers.push((*number, *label)),
+			_ => add_label(&mut missing, label),
+		}
+	}
+	numbers.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+	let mut best: Option<(f64, Split)> = None;
+	let mut below = (0_usize, 0_usize);
+	for index in 0..numbers.len().saturating_sub(1) {
+		add_label(&mut below, &numbers[index].1);
+		if numbers[index].0 >= numbers[index.add(1)].0 {
+			continue
+		}
+		let threshold = numbers[index].0.add(numbers[index.add(1)].0).div(2_f64);
+		for missing_left in [false, true].iter() {
+			let left = if *missing_left { (below.0.add(missing.0), below.1.add(missing.1)) } else { below };
+			if let Some(gain) = split_gain(settings, parent, left) {
+				if best.as_ref().is_none_or(|(best_gain, _split)| gain > *best_gain) {
+					best = Some((gain, Split::Numeric { feature: *feature, threshold, missing_left: *missing_left }));
+				}
+			}
+		}
+	}
+	best
+}
+
+// Orders the categories by their share of Yes and sweeps the cut points of that order, which finds the best
+// grouping of categories into two sides for a binary class.
+fn best_categorical_split(settings: &TreeSettings, feature: &Feature, values: &[FeatureValue], labels: &[BinaryClass]) -> Option<(f64, Split)> {
+	let parent = count(labels);
+	let mut counts: BTreeMap<usize, (usize, usize)> = BTreeMap::new();
+	for (value, label) in values.iter().zip(labels) {
+		add_label(counts.entry(category(value)?).or_insert((0, 0)), label);
+	}
+	let mut categories: Vec<(usize, (usize, usize))> = counts.into_iter().collect();
+	categories.sort_by(|a, b| {
+		let rate = |counts: &(usize, usize)| quick_convert(&counts.0).div(quick_convert(&counts.0.add(counts.1)));
+		rate(&a.1).partial_cmp(&rate(&b.1)).unwrap_or(std::cmp::Ordering::Equal).then(a.0.cmp(&b.0))
+	});
+
+	let mut best: Option<(f64, Split)> = None;
+	let mut left = (0_usize, 0_usize);
+	for cut in 1..categories.len() {
+		let counts = categories[cut.sub(1)].1;
+		left = (left.0.add(counts.0), left.1.add(counts.1));
+		if let Some(gain) = split_gain(settings, parent, left) {
+			if best.as_ref().is_none_or(|(best_gain, _split)| gain > *best_gain) {
+				let mut left_categories: Vec<usize> = categories[..cut].iter().map(|(category, _counts)| *category).collect();
+				let mut right_categories: Vec<usize> = categories[cut..].iter().map(|(category, _counts)| *category).collect();
+				left_categories.sort_unstable();
+				right_categories.sort_unstable();
+				best = Some((gain, Split::Categorical { feature: *feature, left: left_categories, right: right_categories }));
+			}
+		}
+	}
+	best
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DecisionTree {
+	features: Vec<Feature>,
+	settings: TreeSettings,
+	seed: u64,
+	root: Node
+}
+
+impl DecisionTree {
+	// CART: grows the tree greedily on the given features, then prunes it back by cost complexity.
+	pub fn fit<R: PassengerFeatures + Labelled>(records: &[R], features: &[Feature], settings: &TreeSettings, seed: u64) -> Result<DecisionTree, String> {
+		let indices: Vec<usize> = (0..records.len()).collect();
+		let tree = DecisionTree::fit_sample(records, &indices, features, settings, seed)?;
+		eprintln!("DecisionTree::fit Grew a tree on {} records with {} leaves and depth {} after pruning with ccp_alpha {}", records.len(), tree.get_leaf_count(), tree.get_depth(), settings.ccp_alpha);
+		Ok(tree)
+	}
+
+	// Fits on the records at the given indices, which may repeat, as in a bootstrap sample.
+	// seed picks the features considered at each split when settings.max_features is set.
+	pub fn fit_sample<R: PassengerFeatures + Labelled>(records: &[R], indices: &[usize], features: &[Feature], settings: &TreeSettings, seed: u64) -> Result<DecisionTree, String> {
+		if indices.is_empty() {
+			return Err("DecisionTree::fit needs at least one record".to_string())
+		}
+		if settings.min_samples_leaf == 0 {
+			return Err("DecisionTree::fit min_samples_leaf must be at least 1".to_string())
+		}
+		if settings.max_features == Some(0) {
+			return Err("DecisionTree::fit max_features must be at least 1".to_string())
+		}
+		let mut tree = DecisionTree {
+			features: features.to_vec(),
+			settings: settings.clone(),
+			seed,
+			root: Node::Leaf { yes: 0, no: 0 }
+		};
+		tree.root = tree.grow(records, indices, 0, &mut Rng::new(seed));
+		tree.prune(indices.len());
+		Ok(tree)
+	}
+
+	fn grow<R: PassengerFeatures + Labelled>(&self, records: &[R], indices: &[usize], depth: usize, rng: &mut Rng) -> Node {
+		let labels: Vec<BinaryClass> = indices.iter().map(|index| records[*index].label()).collect();
+		let (yes, no) = count(&labels);
+		let too_deep = self.settings.max_depth.is_some_and(|max_depth| depth >= max_depth);
+		if too_deep || yes == 0 || no == 0 || indices.len() < 2_usize.mul(self.settings.min_samples_leaf) {
+			return Node::Leaf { yes, no }
+		}
+
+		let mut candidates = self.features.clone();
+		if let Some(max_features) = self.settings.max_features {
+			if max_features < candidates.len() {
+				rng.shuffle(&mut candidates);
+				candidates.truncate(max_features);
+			}
+		}
+		let mut best: Option<(f64, Split)> = None;
+		for feature in &candidates {
+			let values: Vec<FeatureValue> = indices.iter().map(|index| records[*index].get_feature(feature)).collect();
+			let numeric = values.iter().any(|value| matches!(value, FeatureValue::Number(_)));
+			let candidate = if numeric {
+				best_numeric_split(&self.settings, feature, &values, &labels)
+			} else {
+				best_categorical_split(&self.settings, feature, &values, &labels)
+			};
+			if let Some((gain, split)) = candidate {
+				if gain > MIN_IMPURITY_DECREASE && best.as_ref().is_none_or(|(best_gain, _split)| gain > *best_gain) {
+					best = Some((gain, split));
+				}
+			}
+		}
+
+		match best {
+			None => Node::Leaf { yes, no },
+			Some((_gain, split)) => {
+				let (left, right): (Vec<usize>, Vec<usize>) = indices.iter()
+					.partition(|index| split.goes_left(&records[**index].get_feature(split.get_feature())).unwrap_or(true));
+				Node::Branch {
+					yes,
+					no,
+					left: Box::new(self.grow(records, &left, depth.add(1), rng)),
+					right: Box::new(self.grow(records, &right, depth.add(1), rng)),
+					split,
+				}
+			},
+		}
+	}
+
+	// Weakest-link pruning: repeatedly collapses the subtrees whose extra leaves remove the least impurity, while that is at most ccp_alpha.
+	fn prune(&mut self, total: usize) {
+		let criterion = self.settings.criterion;
+		while let Some(gain) = self.root.weakest_link(&criterion, total) {
+			if gain > self.settings.ccp_alpha {
+				break
+			}
+			self.root.collapse(&criterion, total, gain);
+		}
+	}
+
+	pub fn get_features(&self) -> &Vec<Feature> {
+		&self.features
+	}
+
+	pub fn get_settings(&self) -> &TreeSettings {
+		&self.settings
+	}
+
+	pub fn get_seed(&self) -> &u64 {
+		&self.seed
+	}
+
+	pub fn get_root(&self) -> &Node {
+		&self.root
+	}
+
+	pub fn get_depth(&self) -> usize {
+		self.root.get_depth()
+	}
+
+	pub fn get_leaf_count(&self) -> usize {
+		self.root.get_leaf_count()
+	}
+
+	// Probability of BinaryClass::Yes
+	pub fn probability<R: PassengerFeatures>(&self, record: &R) -> f64 {
+		self.root.probability(record)
+	}
+}
+
+// fit grows the tree again on the records, with the features, settings and seed it was grown with.
+impl<R: PassengerFeatures + Labelled> Classifier<R> for DecisionTree {
+	fn fit(&mut self, records: &[R]) -> Result<(), String> {
+		*self = DecisionTree::fit(records, &self.features, &self.settings, self.seed)?;
+		Ok(())
+	}
+
+	fn predict_proba(&self, record: &R) -> Result<f64, String> {
+		Ok(self.probability(record))
+	}
+}
@@ -0,0 +1,76 @@
+use super::*;
+use crate::test_support::passengers;
+
+#[test]
+fn when_gini_and_entropy_then_impurity_of_even_split_is_maximal() {
+	assert_abs_diff_eq!(Criterion::Gini.impurity(5, 5), 0.5_f64);
+	assert_abs_diff_eq!(Criterion::Entropy.impurity(5, 5), 1_f64);
+	assert_abs_diff_eq!(Criterion::Gini.impurity(4, 0), 0_f64);
+	assert_abs_diff_eq!(Criterion::Entropy.impurity(1, 3), -(0.25_f64 * 0.25_f64.log2() + 0.75_f64 * 0.75_f64.log2()));
+}
+
+#[test]
+fn when_sex_separates_classes_then_one_categorical_split() {
+	let records = passengers(&[
+		"1,1,1,A,female,30,0,0,T,10,,S",
+		"2,1,3,B,female,40,0,0,T,10,,S",
+		"3,0,1,C,male,30,0,0,T,10,,S",
+		"4,0,3,D,male,40,0,0,T,10,,S",
+	]);
+	let tree = DecisionTree::fit(&records, &Feature::ALL, &TreeSettings::default(), 1_u64).unwrap();
+
+	assert_eq!(tree.get_depth(), 1);
+	match tree.get_root() {
+		Node::Branch { split, .. } => assert_eq!(*split.get_feature(), Feature::Sex),
+		Node::Leaf { .. } => panic!("tree::tests::when_sex_separates_classes_then_one_categorical_split expected a branch"),
+	}
+	assert_eq!(tree.score_batch(&records).unwrap(), vec![(1_f64, BinaryClass::Yes), (1_f64, BinaryClass::Yes), (0_f64, BinaryClass::No), (0_f64, BinaryClass::No)]);
+}
+
+#[test]
+fn when_ages_missing_then_missing_side_learned() {
+	let records = passengers(&[
+		"1,1,3,A,male,5,0,0,T,10,,S",
+		"2,1,3,B,male,8,0,0,T,10,,S",
+		"3,1,3,C,male,,0,0,T,10,,S",
+		"4,1,3,D,male,,0,0,T,10,,S",
+		"5,0,3,E,male,40,0,0,T,10,,S",
+		"6,0,3,F,male,50,0,0,T,10,,S",
+	]);
+	let tree = DecisionTree::fit(&records, &[Feature::Age], &TreeSettings::default(), 1_u64).unwrap();
+
+	assert_eq!(*tree.get_root(), Node::Branch {
+		yes: 4,
+		no: 2,
+		split: Split::Numeric { feature: Feature::Age, threshold: 24_f64, missing_left: true },
+		left: Box::new(Node::Leaf { yes: 4, no: 0 }),
+		right: Box::new(Node::Leaf { yes: 0, no: 2 }),
+	});
+	let unseen = passengers(&["7,0,2,G,female,,1,1,T,10,,C", "8,0,2,H,female,60,1,1,T,10,,C"]);
+	assert_abs_diff_eq!(tree.probability(&unseen[0]), 1_f64);
+	assert_abs_diff_eq!(tree.probability(&unseen[1]), 0_f64);
+}
+
+#[test]
+fn when_limits_and_pruning_then_tree_stays_small() {
+	let records = passengers(&[
+		"1,1,1,A,female,5,0,0,T,10,,S",
+		"2,1,2,B,female,15,0,0,T,10,,C",
+		"3,0,3,C,female,25,0,0,T,10,,Q",
+		"4,1,1,D,male,35,0,0,T,10,,S",
+		"5,0,2,E,male,45,0,0,T,10,,C",
+		"6,0,3,F,male,55,0,0,T,10,,Q",
+		"7,1,3,G,male,65,0,0,T,10,,S",
+		"8,0,3,H,female,75,0,0,T,10,,S",
+	]);
+	let grown = DecisionTree::fit(&records, &Feature::ALL, &TreeSettings::default(), 1_u64).unwrap();
+	let shallow = DecisionTree::fit(&records, &Feature::ALL, &TreeSettings { max_depth: Some(1), ..TreeSettings::default() }, 1_u64).unwrap();
+	let wide_leaves = DecisionTree::fit(&records, &Feature::ALL, &TreeSettings { min_samples_leaf: 3, ..TreeSettings::default() }, 1_u64).unwrap();
+	let pruned = DecisionTree::fit(&records, &Feature::ALL, &TreeSettings { ccp_alpha: 1_f64, ..TreeSettings::default() }, 1_u64).unwrap();
+
+	assert!(grown.get_depth() > 1);
+	assert_eq!(grown.score_batch(&records).unwrap().iter().filter(|(probability, label)| (*probability > 0.5_f64) != (*label == BinaryClass::Yes)).count(), 0);
+	assert_eq!(shallow.get_depth(), 1);
+	assert!(wide_leaves.get_leaf_count() <= 2);
+	assert_eq!(*pruned.get_root(), Node::Leaf { yes: 4, no: 4 });
+}
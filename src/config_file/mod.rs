@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::classification::{self, metrics::ThresholdMetric};
+use crate::search::SearchSpace;
+use crate::{BayesSettings, BoostingSettings, CalibrationMethod, Command, Config, DataFormat, EnsembleSettings, Feature, ForestSettings, Initialization, KnnSettings, Learner, MlpSettings, Schema, Optimizer, SvmSettings, TreeSettings, DEFAULT_SEED};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Toml,
+    Json,
+}
+
+impl Format {
+    // The format is chosen by the file extension, .toml or .json.
+    pub fn from_filename(filename: &str) -> Result<Format, String> {
+        match Path::new(filename).extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Ok(Format::Toml),
+            Some("json") => Ok(Format::Json),
+            _ => Err(format!("Format::from_filename {} should end in .toml or .json", filename)),
+        }
+    }
+}
+
+// Every setting of a run. Keys left out of a file fall back to the positional arguments or the defaults of Config,
+// and the keys written by the search command to best_config.json are a subset, so that file can be loaded as is.
+// Keys left out of the learner settings and search space tables keep the defaults of those settings.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub command: Option<Command>,
+    pub learning_rate: Option<f64>,
+    pub tolerance: Option<f64>,
+    pub training_data_filename: Option<String>,
+    pub test_data_filename: Option<String>,
+    pub output_filename: Option<String>,
+    pub threshold: Option<f64>,
+    pub threshold_metric: Option<ThresholdMetric>,
+    pub validation_fraction: Option<f64>,
+    pub write_probability: Option<bool>,
+    pub calibration: Option<CalibrationMethod>,
+    pub calibration_bins: Option<usize>,
+    pub reliability_filename: Option<String>,
+    pub max_iterations: Option<u64>,
+    pub l2: Option<f64>,
+    pub optimizer: Option<Optimizer>,
+    pub features: Option<Vec<Feature>>,
+    pub folds: Option<usize>,
+    pub seed: Option<u64>,
+    pub search_results_filename: Option<String>,
+    pub best_config_filename: Option<String>,
+    pub resolved_config_filename: Option<String>,
+    pub model_filename: Option<String>,
+    pub initialization: Option<Initialization>,
+    pub learner: Option<Learner>,
+    pub lenient: Option<bool>,
+    pub validation_report_filename: Option<String>,
+    pub progress_every: Option<usize>,
+    pub input_format: Option<DataFormat>,
+    pub output_format: Option<DataFormat>,
+    pub extended_output: Option<bool>,
+    pub top_features: Option<usize>,
+    pub kaggle: Option<bool>,
+    pub analysis_filename: Option<String>,
+    pub coefficients_filename: Option<String>,
+    pub write_contributions: Option<bool>,
+    pub explain_id: Option<u64>,
+    // Tables have to come after plain values in TOML, so the learner settings and search space are kept last.
+    pub tree: Option<TreeSettings>,
+    pub forest: Option<ForestSettings>,
+    pub boosting: Option<BoostingSettings>,
+    pub bayes: Option<BayesSettings>,
+    pub knn: Option<KnnSettings>,
+    pub svm: Option<SvmSettings>,
+    pub mlp: Option<MlpSettings>,
+    pub ensemble: Option<EnsembleSettings>,
+    pub schema: Option<Schema>,
+    pub search: Option<SearchSpace>,
+}
+
+impl ConfigFile {
+    pub fn read(filename: &str) -> Result<ConfigFile, String> {
+        let format = Format::from_filename(filename)?;
+        let contents = match fs::read_to_string(filename) {
+            Ok(contents) => contents,
+            Err(e1) => return Err(format!("ConfigFile::read Failed to read {}. {}", filename, e1)),
+        };
+        let config_file = match format {
+            Format::Toml => toml::from_str(&contents).map_err(|e2| format!("ConfigFile::read Failed to parse {}. Toml: {}", filename, e2))?,
+            Format::Json => serde_json::from_str(&contents).map_err(|e2| format!("ConfigFile::read Failed to parse {}. Serde: {}", filename, e2))?,
+        };
+        eprintln!("ConfigFile::read Loaded configuration from {}", filename);
+        Ok(config_file)
+    }
+
+    pub fn write(&self, filename: &str) -> Result<(), String> {
+        let contents = match Format::from_filename(filename)? {
+            Format::Toml => toml::to_string(self).map_err(|e1| format!("ConfigFile::write Failed to serialize ConfigFile. Toml: {}", e1))?,
+            Format::Json => serde_json::to_string_pretty(self).map_err(|e1| format!("ConfigFile::write Failed to serialize ConfigFile. Serde: {}", e1))?,
+        };
+        if let Err(e2) = fs::write(filename, contents) {
+            let message = format!("ConfigFile::write Failed to write {}. {}", filename, e2);
+            return Err(message)
+        }
+        eprintln!("ConfigFile::write Completed writing the resolved configuration to {}", filename);
+        Ok(())
+    }
+
+    // Every value the run actually used, including defaults, so the file alone reproduces it.
+    pub fn from_config(config: &Config) -> ConfigFile {
+        ConfigFile {
+            command: Some(*config.get_command()),
+            learning_rate: Some(*config.get_learning_rate()),
+            tolerance: Some(*config.get_tolerance()),
+            training_data_filename: Some(config.get_training_data_filename().clone()),
+            test_data_filename: Some(config.get_test_data_filename().clone()),
+            output_filename: Some(config.get_output_filename().clone()),
+            threshold: Some(*config.get_threshold()),
+            threshold_metric: *config.get_threshold_metric(),
+            validation_fraction: Some(*config.get_validation_fraction()),
+            write_probability: Some(*config.get_write_probability()),
+            calibration: *config.get_calibration(),
+            calibration_bins: Some(*config.get_calibration_bins()),
+            reliability_filename: config.get_reliability_filename().clone(),
+            max_iterations: *config.get_max_iterations(),
+            l2: Some(*config.get_l2()),
+            optimizer: Some(*config.get_optimizer()),
+            features: Some(config.get_features().clone()),
+            folds: Some(*config.get_folds()),
+            seed: Some(*config.get_seed()),
+            search_results_filename: Some(config.get_search_results_filename().clone()),
+            best_config_filename: Some(config.get_best_config_filename().clone()),
+            resolved_config_filename: Some(config.get_resolved_config_filename()),
+            model_filename: config.get_model_filename().clone(),
+            initialization: Some(config.get_initialization().clone()),
+            learner: Some(*config.get_learner()),
+            lenient: Some(*config.get_lenient()),
+            validation_report_filename: config.get_validation_report_filename().clone(),
+            progress_every: Some(*config.get_progress_every()),
+            input_format: *config.get_input_format(),
+            output_format: *config.get_output_format(),
+            extended_output: Some(*config.get_extended_output()),
+            top_features: Some(*config.get_top_features()),
+            kaggle: Some(*config.get_kaggle()),
+            analysis_filename: config.get_analysis_filename().clone(),
+            coefficients_filename: config.get_coefficients_filename().clone(),
+            write_contributions: Some(*config.get_write_contributions()),
+            explain_id: *config.get_explain_id(),
+            tree: Some(config.get_tree_settings().clone()),
+            forest: Some(config.get_forest_settings().clone()),
+            boosting: Some(config.get_boosting_settings().clone()),
+            bayes: Some(config.get_bayes_settings().clone()),
+            knn: Some(config.get_knn_settings().clone()),
+            svm: Some(config.get_svm_settings().clone()),
+            mlp: Some(config.get_mlp_settings().clone()),
+            ensemble: Some(config.get_ensemble_settings().clone()),
+            schema: Some(config.get_schema().clone()),
+            search: Some(config.get_search_space().clone()),
+        }
+    }
+
+    // Fills in the defaults of everything left out and checks the values the command line would also reject.
+    pub fn into_config(self) -> Result<Config, String> {
+        let threshold = self.threshold.unwrap_or(classification::DEFAULT_THRESHOLD);
+        if !(0_f64..=1_f64).contains(&threshold) {
+            return Err(format!("ConfigFile::into_config threshold {} should be between 0 and 1", threshold))
+        }
+        let validation_fraction = self.validation_fraction.unwrap_or(0.2_f64);
+        if validation_fraction <= 0_f64 || validation_fraction >= 1_f64 {
+            return Err(format!("ConfigFile::into_config validation_fraction {} should be strictly between 0 and 1", validation_fraction))
+        }
+        let calibration_bins = self.calibration_bins.unwrap_or(10);
+        if calibration_bins == 0 {
+            return Err("ConfigFile::into_config calibration_bins should be at least 1".to_string())
+        }
+        if let Some(tree) = &self.tree {
+            if tree.min_samples_leaf == 0 || tree.ccp_alpha < 0_f64 || tree.max_features == Some(0) {
+                return Err("ConfigFile::into_config tree.min_samples_leaf and tree.max_features should be at least 1 and tree.ccp_alpha at least 0".to_string())
+            }
+        }
+        if let Some(forest) = &self.forest {
+            if forest.trees == 0 || forest.threads == Some(0) {
+                return Err("ConfigFile::into_config forest.trees and forest.threads should be at least 1".to_string())
+            }
+        }
+        if let Some(boosting) = &self.boosting {
+            boosting.validate()?;
+        }
+        if let Some(bayes) = &self.bayes {
+            if bayes.alpha <= 0_f64 {
+                return Err(format!("ConfigFile::into_config bayes.alpha {} should be positive", bayes.alpha))
+            }
+        }
+        if let Some(knn) = &self.knn {
+            if knn.neighbours == 0 || knn.leaf_size == 0 {
+                return Err("ConfigFile::into_config knn.neighbours and knn.leaf_size should be at least 1".to_string())
+            }
+        }
+        if let Some(svm) = &self.svm {
+            if svm.lambda <= 0_f64 || svm.epochs == 0 {
+                return Err("ConfigFile::into_config svm.lambda should be positive and svm.epochs at least 1".to_string())
+            }
+        }
+        if let Some(mlp) = &self.mlp {
+            mlp.validate()?;
+        }
+        if let Some(ensemble) = &self.ensemble {
+            ensemble.validate()?;
+        }
+        let folds = self.folds.unwrap_or(5);
+        if folds < 2 {
+            return Err(format!("ConfigFile::into_config folds {} should be at least 2", folds))
+        }
+
+        Ok(Config {
+            command: self.command.unwrap_or(Command::Train),
+            learning_rate: self.learning_rate.ok_or("not enough arguments: missing learning rate")?,
+            tolerance: self.tolerance.ok_or("not enough arguments: missing tolerance")?,
+            training_data_filename: self.training_data_filename.ok_or("not enough arguments: missing training data filename")?,
+            test_data_filename: self.test_data_filename.ok_or("not enough arguments: missing test data filename")?,
+            output_filename: self.output_filename.ok_or("not enough arguments: missing output filename")?,
+            threshold,
+            threshold_metric: self.threshold_metric,
+            validation_fraction,
+            write_probability: self.write_probability.unwrap_or(false),
+            calibration: self.calibration,
+            calibration_bins,
+            reliability_filename: self.reliability_filename,
+            max_iterations: self.max_iterations,
+            l2: self.l2.unwrap_or(0_f64),
+            optimizer: self.optimizer.unwrap_or(Optimizer::Batch),
+            features: self.features.unwrap_or_else(|| Feature::ALL.to_vec()),
+            folds,
+            seed: self.seed.unwrap_or(DEFAULT_SEED),
+            search_space: self.search.unwrap_or_default(),
+            search_results_filename: self.search_results_filename.unwrap_or_else(|| "search_results.csv".to_string()),
+            best_config_filename: self.best_config_filename.unwrap_or_else(|| "best_config.json".to_string()),
+            resolved_config_filename: self.resolved_config_filename,
+            model_filename: self.model_filename,
+            initialization: self.initialization.unwrap_or_default(),
+            learner: self.learner.unwrap_or(Learner::Logistic),
+            tree_settings: self.tree.unwrap_or_default(),
+            forest_settings: self.forest.unwrap_or_default(),
+            boosting_settings: self.boosting.unwrap_or_default(),
+            bayes_settings: self.bayes.unwrap_or_default(),
+            knn_settings: self.knn.unwrap_or_default(),
+            svm_settings: self.svm.unwrap_or_default(),
+            mlp_settings: self.mlp.unwrap_or_default(),
+            ensemble_settings: self.ensemble.unwrap_or_default(),
+            schema: self.schema.unwrap_or_default(),
+            lenient: self.lenient.unwrap_or(false),
+            validation_report_filename: self.validation_report_filename,
+            progress_every: self.progress_every.unwrap_or(100000),
+            input_format: self.input_format,
+            output_format: self.output_format,
+            extended_output: self.extended_output.unwrap_or(false),
+            top_features: self.top_features.unwrap_or(3),
+            kaggle: self.kaggle.unwrap_or(false),
+            analysis_filename: self.analysis_filename,
+            coefficients_filename: self.coefficients_filename,
+            write_contributions: self.write_contributions.unwrap_or(false),
+            explain_id: self.explain_id,
+        })
+    }
+}
@@ -0,0 +1,114 @@
+use super::*;
+use std::env;
+
+fn temp_filename(name: &str) -> String {
+    env::temp_dir().join(format!("kaggle_c_titanic_{}_{}", std::process::id(), name)).to_string_lossy().into_owned()
+}
+
+#[test]
+fn when_toml_file_and_options_then_options_override_file() {
+    let filename = temp_filename("override.toml");
+    fs::write(&filename, r#"
+learning_rate = 0.001
+tolerance = 2.0
+training_data_filename = "train.csv"
+test_data_filename = "test.csv"
+output_filename = "output.csv"
+l2 = 3.0
+threshold = 0.3
+features = ["sex", "age"]
+
+[search]
+l2 = [0.0, 1.0]
+"#).unwrap();
+    let args = vec!["first".to_string(), "--config".to_string(), filename.clone(), "--l2".to_string(), "5".to_string()];
+    let config = Config::new(&args).unwrap();
+    fs::remove_file(&filename).unwrap();
+
+    assert_abs_diff_eq!(*config.get_learning_rate(), 0.001_f64);
+    assert_abs_diff_eq!(*config.get_l2(), 5_f64);
+    assert_abs_diff_eq!(*config.get_threshold(), 0.3_f64);
+    assert_eq!(config.get_features(), &vec![Feature::Sex, Feature::Age]);
+    assert_eq!(config.get_search_space().l2, vec![0_f64, 1_f64]);
+    assert_eq!(config.get_search_space().thresholds, SearchSpace::default().thresholds);
+    assert_eq!(config.get_resolved_config_filename(), "output.config.toml");
+}
+
+#[test]
+fn when_positional_arguments_and_json_file_then_file_wins() {
+    let filename = temp_filename("best_config.json");
+    fs::write(&filename, r#"{"learning_rate": 0.5, "max_iterations": 300, "l2": 10.0, "optimizer": "online", "features": ["sex"], "threshold": 0.6}"#).unwrap();
+    let args = vec!["first".to_string(), "2".to_string(), "3".to_string(), "fourth".to_string(), "fifth".to_string(), "sixth".to_string(),
+        "--config".to_string(), filename.clone()];
+    let config = Config::new(&args).unwrap();
+    fs::remove_file(&filename).unwrap();
+
+    assert_abs_diff_eq!(*config.get_learning_rate(), 0.5_f64);
+    assert_abs_diff_eq!(*config.get_tolerance(), 3_f64);
+    assert_eq!(config.get_training_data_filename(), "fourth");
+    assert_eq!(*config.get_max_iterations(), Some(300_u64));
+    assert_eq!(*config.get_optimizer(), Optimizer::Online);
+    assert_abs_diff_eq!(*config.get_threshold(), 0.6_f64);
+}
+
+#[test]
+fn when_best_config_and_data_options_then_file_completed_by_options() {
+    let filename = temp_filename("best_config_options.json");
+    fs::write(&filename, r#"{"learning_rate": 1e-7, "tolerance": 1.0, "features": ["sex"]}"#).unwrap();
+    let args = vec!["first".to_string(), "train".to_string(), "--config".to_string(), filename.clone(),
+        "--training-data".to_string(), "train.csv".to_string(), "--test-data".to_string(), "test.csv".to_string(), "--output".to_string(), "o.csv".to_string()];
+    let config = Config::new(&args).unwrap();
+    fs::remove_file(&filename).unwrap();
+
+    assert_abs_diff_eq!(*config.get_learning_rate(), 1e-7_f64);
+    assert_eq!(config.get_training_data_filename(), "train.csv");
+    assert_eq!(config.get_test_data_filename(), "test.csv");
+    assert_eq!(config.get_output_filename(), "o.csv");
+}
+
+#[test]
+fn when_resolved_config_written_then_reading_it_gives_same_config() {
+    let args = vec!["first".to_string(), "search".to_string(), "2".to_string(), "3".to_string(), "fourth".to_string(), "fifth".to_string(), "sixth".to_string(),
+        "--select-threshold".to_string(), "youden".to_string(), "--calibration".to_string(), "platt".to_string(), "--search-mode".to_string(), "random".to_string()];
+    let config = Config::new(&args).unwrap();
+    let resolved = ConfigFile::from_config(&config);
+
+    for name in &["resolved.toml", "resolved.json"] {
+        let filename = temp_filename(name);
+        resolved.write(&filename).unwrap();
+        let read = ConfigFile::read(&filename).unwrap();
+        fs::remove_file(&filename).unwrap();
+        assert_eq!(read, resolved);
+    }
+    assert_eq!(resolved.command, Some(Command::Search));
+    assert_eq!(resolved.resolved_config_filename, Some("search_results.config.toml".to_string()));
+}
+
+#[test]
+fn when_command_neither_trains_nor_searches_then_no_resolved_config_written() {
+    let training_filename = temp_filename("describe_train.csv");
+    let resolved_filename = temp_filename("describe.config.toml");
+    fs::write(&training_filename, "PassengerId,Survived,Pclass,Name,Sex,Age,SibSp,Parch,Ticket,Fare,Cabin,Embarked\n1,1,1,A,female,20,1,0,T,10,,S\n2,0,3,B,male,30,0,0,T,20,,C\n").unwrap();
+    let args: Vec<String> = ["first", "describe", "0.1", "0.5", &training_filename, "test.csv", "output.csv", "--resolved-config", &resolved_filename].iter().map(|arg| arg.to_string()).collect();
+    let mut config = Config::new(&args).unwrap();
+    
+    crate::run(&mut config).unwrap();
+    fs::remove_file(&training_filename).unwrap();
+    
+    assert!(!Path::new(&resolved_filename).exists());
+}
+
+#[test]
+fn when_unknown_key_or_missing_learning_rate_then_return_error() {
+    let filename = temp_filename("unknown.toml");
+    fs::write(&filename, "learning_rate = 1.0\nlearningrate = 2.0\n").unwrap();
+    let unknown = ConfigFile::read(&filename);
+    fs::remove_file(&filename).unwrap();
+    assert!(unknown.is_err());
+
+    let missing = ConfigFile { tolerance: Some(1_f64), ..ConfigFile::default() }.into_config();
+    match missing {
+        Ok(_) => panic!("config_file::tests::when_unknown_key_or_missing_learning_rate_then_return_error built a Config without a learning rate"),
+        Err(message) => assert!(message.contains("missing learning rate")),
+    }
+}
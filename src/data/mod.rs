@@ -0,0 +1,334 @@
+use csv::{Position, ReaderBuilder, StringRecord, Writer};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::reader::RowIter;
+use parquet::record::Field;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{Survived, TestedPassenger};
+
+#[cfg(test)]
+mod tests;
+
+// The filename of standard input or output
+pub const STANDARD_STREAM: &str = "-";
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataFormat {
+    Csv,
+    // One JSON object per line
+    Jsonl,
+    // Apache Parquet, which can only be read
+    Parquet,
+}
+
+impl DataFormat {
+    // The format is chosen by the file extension: .jsonl or .ndjson, .parquet, and CSV for anything else, including -.
+    pub fn from_filename(filename: &str) -> DataFormat {
+        match Path::new(filename).extension().and_then(|extension| extension.to_str()).map(|extension| extension.to_ascii_lowercase()) {
+            Some(extension) if extension == "jsonl" || extension == "ndjson" => DataFormat::Jsonl,
+            Some(extension) if extension == "parquet" => DataFormat::Parquet,
+            _ => DataFormat::Csv,
+        }
+    }
+
+    // The format given, or else the one of the extension
+    pub fn resolve(format: &Option<DataFormat>, filename: &str) -> DataFormat {
+        format.unwrap_or_else(|| DataFormat::from_filename(filename))
+    }
+}
+
+impl FromStr for DataFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<DataFormat, String> {
+        match name {
+            "csv" => Ok(DataFormat::Csv),
+            "jsonl" => Ok(DataFormat::Jsonl),
+            "parquet" => Ok(DataFormat::Parquet),
+            _ => Err(format!("DataFormat::from_str unknown data format {}, expected one of csv, jsonl, parquet", name)),
+        }
+    }
+}
+
+impl fmt::Display for DataFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataFormat::Csv => write!(f, "csv"),
+            DataFormat::Jsonl => write!(f, "jsonl"),
+            DataFormat::Parquet => write!(f, "parquet"),
+        }
+    }
+}
+
+fn open_input(filename: &str) -> Result<Box<dyn Read>, String> {
+    if filename == STANDARD_STREAM {
+        return Ok(Box::new(io::stdin()))
+    }
+    match File::open(filename) {
+        Ok(file) => Ok(Box::new(file)),
+        Err(e1) => Err(format!("RowReader::open Failed to read from {}. {}", filename, e1)),
+    }
+}
+
+// The text of a JSON value as it would appear in a CSV cell. Null is a missing value and booleans are 1 or 0, like
+// the Survived column.
+fn json_text(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::Bool(flag)) => if *flag { "1".to_string() } else { "0".to_string() },
+        Some(Value::String(text)) => text.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn parquet_text(field: &Field) -> String {
+    match field {
+        Field::Null => String::new(),
+        Field::Bool(flag) => if *flag { "1".to_string() } else { "0".to_string() },
+        Field::Str(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+enum Source {
+    Csv(csv::Reader<Box<dyn Read>>),
+    Jsonl {
+        lines: Box<dyn BufRead>,
+        // The first object, read to find the columns
+        first: Option<Map<String, Value>>,
+        line: u64,
+        bytes: u64,
+    },
+    Parquet {
+        rows: RowIter<'static>,
+        row: u64,
+    },
+}
+
+// Reads the rows of a CSV, JSON Lines or Parquet file, or of standard input, as CSV records with the file's column
+// names as headers, so every format is bound through the same Schema and checked by the same Validator. The columns of
+// a JSON Lines file are the keys of its first object. A row's position holds its line, or its row of a Parquet file.
+pub struct RowReader {
+    filename: String,
+    source: Source,
+    headers: StringRecord,
+    // Bytes of the file, or rows of a Parquet file, for the progress; 0 when unknown
+    total: u64,
+}
+
+impl RowReader {
+    pub fn open(filename: &str, format: &Option<DataFormat>) -> Result<RowReader, String> {
+        let total = fs::metadata(filename).map(|metadata| metadata.len()).unwrap_or(0);
+        match DataFormat::resolve(format, filename) {
+            DataFormat::Csv => {
+                let mut reader = ReaderBuilder::new().from_reader(open_input(filename)?);
+                let headers = reader.headers().map_err(|e2| format!("RowReader::open Failed to read the headers of {}. {}", filename, e2))?.clone();
+                Ok(RowReader { filename: filename.to_string(), source: Source::Csv(reader), headers, total })
+            },
+            DataFormat::Jsonl => {
+                let mut reader = RowReader {
+                    filename: filename.to_string(),
+                    source: Source::Jsonl { lines: Box::new(BufReader::new(open_input(filename)?)), first: None, line: 0, bytes: 0 },
+                    headers: StringRecord::new(),
+                    total,
+                };
+                if let Some(first) = reader.next_object()? {
+                    reader.headers = first.keys().collect();
+                    if let Source::Jsonl { first: pending, .. } = &mut reader.source {
+                        *pending = Some(first);
+                    }
+                }
+                Ok(reader)
+            },
+            DataFormat::Parquet => {
+                if filename == STANDARD_STREAM {
+                    return Err("RowReader::open Parquet cannot be read from standard input, as its footer is read first".to_string())
+                }
+                let file = File::open(filename).map_err(|e3| format!("RowReader::open Failed to read from {}. {}", filename, e3))?;
+                let reader = SerializedFileReader::new(file).map_err(|e4| format!("RowReader::open Failed to read {} as Parquet. {}", filename, e4))?;
+                let metadata = reader.metadata().file_metadata();
+                let headers: StringRecord = metadata.schema_descr().root_schema().get_fields().iter().map(|field| field.name()).collect();
+                let total = u64::try_from(metadata.num_rows()).unwrap_or(0);
+                Ok(RowReader { filename: filename.to_string(), source: Source::Parquet { rows: reader.into_iter(), row: 0 }, headers, total })
+            },
+        }
+    }
+
+    pub fn get_headers(&self) -> &StringRecord {
+        &self.headers
+    }
+
+    // How far the reading has got, in bytes or Parquet rows, and the total, 0 when unknown as for standard input.
+    pub fn progress(&self) -> (u64, u64) {
+        match &self.source {
+            Source::Csv(reader) => (reader.position().byte(), self.total),
+            Source::Jsonl { bytes, .. } => (*bytes, self.total),
+            Source::Parquet { row, .. } => (*row, self.total),
+        }
+    }
+
+    fn next_object(&mut self) -> Result<Option<Map<String, Value>>, String> {
+        let filename = &self.filename;
+        if let Source::Jsonl { lines, line, bytes, .. } = &mut self.source {
+            let mut text = String::new();
+            loop {
+                text.clear();
+                let read = lines.read_line(&mut text).map_err(|e1| format!("RowReader::read_row Failed to read a line of {}. {}", filename, e1))?;
+                if read == 0 {
+                    return Ok(None)
+                }
+                *line += 1;
+                *bytes += read as u64;
+                if !text.trim().is_empty() {
+                    break
+                }
+            }
+            return match serde_json::from_str(&text) {
+                Ok(Value::Object(object)) => Ok(Some(object)),
+                Ok(_other) => Err(format!("RowReader::read_row Line {} of {} is not a JSON object", line, filename)),
+                Err(e2) => Err(format!("RowReader::read_row Failed to parse line {} of {}. Serde: {}", line, filename, e2)),
+            }
+        }
+        Ok(None)
+    }
+
+    fn read_json_row(&mut self, row: &mut StringRecord) -> Result<bool, String> {
+        let pending = match &mut self.source {
+            Source::Jsonl { first, .. } => first.take(),
+            _ => None,
+        };
+        let object = match pending {
+            Some(object) => object,
+            None => match self.next_object()? {
+                Some(object) => object,
+                None => return Ok(false),
+            },
+        };
+        row.clear();
+        for header in self.headers.iter() {
+            row.push_field(&json_text(object.get(header)));
+        }
+        let line = match &self.source {
+            Source::Jsonl { line, .. } => *line,
+            _ => 0,
+        };
+        let mut position = Position::new();
+        position.set_line(line);
+        row.set_position(Some(position));
+        Ok(true)
+    }
+
+    // Reads the next row into row, false at the end of the file.
+    pub fn read_row(&mut self, row: &mut StringRecord) -> Result<bool, String> {
+        let filename = &self.filename;
+        match &mut self.source {
+            Source::Csv(reader) => reader.read_record(row).map_err(|e1| format!("RowReader::read_row Failed to read a row of {}. {}", filename, e1)),
+            Source::Jsonl { .. } => self.read_json_row(row),
+            Source::Parquet { rows, row: count } => match rows.next() {
+                None => Ok(false),
+                Some(Err(e2)) => Err(format!("RowReader::read_row Failed to read row {} of {}. {}", *count + 1, filename, e2)),
+                Some(Ok(record)) => {
+                    *count += 1;
+                    row.clear();
+                    for header in self.headers.iter() {
+                        let field = record.get_column_iter().find(|(name, _field)| name.as_str() == header).map(|(_name, field)| field);
+                        row.push_field(&field.map(parquet_text).unwrap_or_default());
+                    }
+                    let mut position = Position::new();
+                    position.set_line(*count);
+                    row.set_position(Some(position));
+                    Ok(true)
+                },
+            },
+        }
+    }
+}
+
+// A prediction as one JSON object, with Survived as a number like in the CSV
+#[derive(Debug, Serialize)]
+struct JsonPrediction {
+    #[serde(rename = "PassengerId")]
+    passenger_id: u64,
+
+    #[serde(rename = "Survived")]
+    survived: u8,
+
+    #[serde(rename = "Probability", skip_serializing_if = "Option::is_none")]
+    probability: Option<f64>,
+
+    #[serde(rename = "TopFeatures", skip_serializing_if = "Option::is_none")]
+    top_features: Option<String>,
+
+    #[serde(rename = "ModelId", skip_serializing_if = "Option::is_none")]
+    model_id: Option<String>,
+
+    #[serde(rename = "Contributions", skip_serializing_if = "Option::is_none")]
+    contributions: Option<String>,
+}
+
+enum Sink {
+    Csv(Box<Writer<Box<dyn Write>>>),
+    Jsonl(BufWriter<Box<dyn Write>>),
+}
+
+// Writes predictions as CSV or JSON Lines, to a file or to standard output.
+pub struct PredictionWriter {
+    filename: String,
+    sink: Sink,
+}
+
+impl PredictionWriter {
+    pub fn create(filename: &str, format: &Option<DataFormat>) -> Result<PredictionWriter, String> {
+        let format = DataFormat::resolve(format, filename);
+        if format == DataFormat::Parquet {
+            return Err(format!("PredictionWriter::create cannot write {} as Parquet, only as csv or jsonl", filename))
+        }
+        let output: Box<dyn Write> = if filename == STANDARD_STREAM {
+            Box::new(io::stdout())
+        } else {
+            Box::new(File::create(filename).map_err(|e1| format!("PredictionWriter::create Failed to create writer to {}. {}", filename, e1))?)
+        };
+        let sink = match format {
+            DataFormat::Jsonl => Sink::Jsonl(BufWriter::new(output)),
+            DataFormat::Csv | DataFormat::Parquet => Sink::Csv(Box::new(Writer::from_writer(output))),
+        };
+        Ok(PredictionWriter { filename: filename.to_string(), sink })
+    }
+
+    pub fn write(&mut self, tested_passenger: &TestedPassenger) -> Result<(), String> {
+        match &mut self.sink {
+            Sink::Csv(writer) => writer.serialize(tested_passenger).map_err(|e1| format!("PredictionWriter::write Failed to serialize TestedPassenger {}. Serde: {}", tested_passenger.passenger_id, e1)),
+            Sink::Jsonl(writer) => {
+                let prediction = JsonPrediction {
+                    passenger_id: tested_passenger.passenger_id,
+                    survived: match tested_passenger.survived {
+                        Survived::Yes => 1,
+                        Survived::No => 0,
+                    },
+                    probability: tested_passenger.probability,
+                    top_features: tested_passenger.top_features.clone(),
+                    model_id: tested_passenger.model_id.clone(),
+                    contributions: tested_passenger.contributions.clone(),
+                };
+                serde_json::to_writer(&mut *writer, &prediction).map_err(|e2| format!("PredictionWriter::write Failed to serialize TestedPassenger {}. Serde: {}", tested_passenger.passenger_id, e2))?;
+                writer.write_all(b"\n").map_err(|e3| format!("PredictionWriter::write Failed to write {}. {}", self.filename, e3))
+            },
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<(), String> {
+        let result = match &mut self.sink {
+            Sink::Csv(writer) => writer.flush(),
+            Sink::Jsonl(writer) => writer.flush(),
+        };
+        result.map_err(|e1| format!("PredictionWriter::flush Failed to write {}. {}", self.filename, e1))
+    }
+}
@@ -0,0 +1,119 @@
+use super::*;
+use parquet::basic::Compression;
+use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::env;
+use std::sync::Arc;
+
+use crate::classification::{classify, BinaryClass, LogisticBinaryClassificationTrainable};
+use crate::schema::{Column, Schema};
+use crate::validation::read_rows;
+use crate::{Feature, FeatureValue, PassengerFeatures, TrainingPassenger};
+
+fn temp_filename(name: &str) -> String {
+    env::temp_dir().join(format!("kaggle_c_titanic_data_{}_{}", std::process::id(), name)).to_string_lossy().into_owned()
+}
+
+fn read_training(filename: &str, format: &Option<DataFormat>) -> Vec<TrainingPassenger> {
+    let (passengers, report) = read_rows(filename, format, &Schema::default(), &[Column::PassengerId, Column::Survived], false).unwrap();
+    report.ensure_valid(filename).unwrap();
+    passengers
+}
+
+#[test]
+fn when_extension_or_flag_then_format_chosen() {
+    assert_eq!(DataFormat::from_filename("train.csv"), DataFormat::Csv);
+    assert_eq!(DataFormat::from_filename("train.JSONL"), DataFormat::Jsonl);
+    assert_eq!(DataFormat::from_filename("train.ndjson"), DataFormat::Jsonl);
+    assert_eq!(DataFormat::from_filename("train.parquet"), DataFormat::Parquet);
+    assert_eq!(DataFormat::from_filename(STANDARD_STREAM), DataFormat::Csv);
+    assert_eq!(DataFormat::resolve(&Some(DataFormat::Jsonl), STANDARD_STREAM), DataFormat::Jsonl);
+    assert_eq!("parquet".parse::<DataFormat>().unwrap(), DataFormat::Parquet);
+    assert!("xml".parse::<DataFormat>().is_err());
+}
+
+#[test]
+fn when_jsonl_then_keys_of_first_object_are_columns() {
+    let filename = temp_filename("train.jsonl");
+    fs::write(&filename, "{\"PassengerId\": 1, \"Survived\": true, \"Sex\": \"female\", \"Age\": 38.5, \"Fare\": null}\n\n{\"PassengerId\": 2, \"Survived\": 0, \"Sex\": \"male\", \"Cabin\": \"C85\"}\n").unwrap();
+
+    let passengers = read_training(&filename, &None);
+    let mut reader = RowReader::open(&filename, &None).unwrap();
+    let mut row = StringRecord::new();
+    reader.read_row(&mut row).unwrap();
+    reader.read_row(&mut row).unwrap();
+    let line = row.position().unwrap().line();
+    let (done, total) = reader.progress();
+    fs::remove_file(&filename).unwrap();
+
+    assert_eq!(passengers.len(), 2);
+    assert_eq!(passengers[0].answer(), BinaryClass::Yes);
+    assert_eq!(passengers[1].answer(), BinaryClass::No);
+    assert_eq!(passengers[0].get_feature(&Feature::Age), FeatureValue::Number(38.5_f64));
+    assert_eq!(passengers[0].get_feature(&Feature::Fare), FeatureValue::Missing);
+    assert_eq!(passengers[1].get_feature(&Feature::Age), FeatureValue::Missing);
+    assert_eq!(line, 3);
+    assert_eq!(done, total);
+}
+
+#[test]
+fn when_jsonl_line_not_an_object_then_error_names_line() {
+    let filename = temp_filename("broken.jsonl");
+    fs::write(&filename, "{\"PassengerId\": 1, \"Survived\": 1}\n[1, 2]\n").unwrap();
+
+    let error = read_rows::<TrainingPassenger>(&filename, &Some(DataFormat::Jsonl), &Schema::default(), &[Column::PassengerId], false).unwrap_err();
+    fs::remove_file(&filename).unwrap();
+
+    assert!(error.contains("Line 2"), "{}", error);
+}
+
+#[test]
+fn when_parquet_then_read_like_csv() {
+    let filename = temp_filename("train.parquet");
+    let schema = Arc::new(parse_message_type("message passenger { REQUIRED INT64 PassengerId; REQUIRED INT64 Survived; OPTIONAL BYTE_ARRAY Sex (UTF8); OPTIONAL DOUBLE Age; }").unwrap());
+    let properties = Arc::new(WriterProperties::builder().set_compression(Compression::SNAPPY).build());
+    let mut writer = SerializedFileWriter::new(File::create(&filename).unwrap(), schema, properties).unwrap();
+    let mut row_group = writer.next_row_group().unwrap();
+    let mut column = row_group.next_column().unwrap().unwrap();
+    column.typed::<Int64Type>().write_batch(&[1, 2, 3], None, None).unwrap();
+    column.close().unwrap();
+    let mut column = row_group.next_column().unwrap().unwrap();
+    column.typed::<Int64Type>().write_batch(&[1, 0, 0], None, None).unwrap();
+    column.close().unwrap();
+    let mut column = row_group.next_column().unwrap().unwrap();
+    column.typed::<ByteArrayType>().write_batch(&[ByteArray::from("female"), ByteArray::from("male")], Some(&[1, 1, 0]), None).unwrap();
+    column.close().unwrap();
+    let mut column = row_group.next_column().unwrap().unwrap();
+    column.typed::<DoubleType>().write_batch(&[29_f64, 0.42_f64], Some(&[1, 0, 1]), None).unwrap();
+    column.close().unwrap();
+    row_group.close().unwrap();
+    writer.close().unwrap();
+
+    let passengers = read_training(&filename, &None);
+    let reader = RowReader::open(&filename, &None).unwrap();
+    fs::remove_file(&filename).unwrap();
+
+    assert_eq!(reader.progress(), (0, 3));
+    assert_eq!(passengers.len(), 3);
+    assert_eq!(*passengers[2].get_passenger_id(), 3_u64);
+    assert_eq!(passengers[0].answer(), BinaryClass::Yes);
+    assert_eq!(passengers[1].get_feature(&Feature::Age), FeatureValue::Missing);
+    assert_eq!(passengers[2].get_feature(&Feature::Age), FeatureValue::Number(0.42_f64));
+    assert_eq!(passengers[2].get_feature(&Feature::Sex), FeatureValue::Missing);
+}
+
+#[test]
+fn when_predictions_written_as_jsonl_then_one_object_per_line() {
+    let filename = temp_filename("predictions.jsonl");
+    let mut writer = PredictionWriter::create(&filename, &None).unwrap();
+    writer.write(&TestedPassenger::with_probability(classify(892, 0.75_f64, &0.5_f64))).unwrap();
+    writer.write(&TestedPassenger::new(classify(893, 0.25_f64, &0.5_f64))).unwrap();
+    writer.flush().unwrap();
+    let contents = fs::read_to_string(&filename).unwrap();
+    fs::remove_file(&filename).unwrap();
+
+    assert_eq!(contents, "{\"PassengerId\":892,\"Survived\":1,\"Probability\":0.75}\n{\"PassengerId\":893,\"Survived\":0}\n");
+    assert!(PredictionWriter::create(&temp_filename("predictions.parquet"), &None).is_err());
+}
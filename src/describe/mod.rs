@@ -0,0 +1,200 @@
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::classification::quick_convert;
+use crate::report::{conditional_table, ConditionalRow, REPORT_BINS};
+use crate::schema::Column;
+use crate::{Config, Feature, FeatureValue, PassengerFeatures, Survived, TrainingPassenger};
+
+#[cfg(test)]
+mod tests;
+
+// Frequency tables show this many of the most frequent values, which matters for names, tickets and cabins.
+pub const FREQUENCY_ROWS: usize = 10;
+
+// A value of one column of a passenger
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Missing,
+    Number(f64),
+    Text(String),
+}
+
+// The feature a column is read into, for the survival rates. PassengerId and Survived have none.
+pub fn column_feature(column: &Column) -> Option<Feature> {
+    match column {
+        Column::PassengerId | Column::Survived => None,
+        Column::PassengerClass => Some(Feature::PassengerClass),
+        Column::Name => Some(Feature::Name),
+        Column::Sex => Some(Feature::Sex),
+        Column::Age => Some(Feature::Age),
+        Column::SiblingsSpouses => Some(Feature::SiblingsSpouses),
+        Column::ParentsChildren => Some(Feature::ParentsChildren),
+        Column::Ticket => Some(Feature::TicketId),
+        Column::Fare => Some(Feature::Fare),
+        Column::Cabin => Some(Feature::CabinId),
+        Column::PortOfEmbarkation => Some(Feature::PortOfEmbarkation),
+    }
+}
+
+fn text(text: &Option<String>) -> Cell {
+    match text {
+        Some(text) => Cell::Text(text.clone()),
+        None => Cell::Missing,
+    }
+}
+
+// Categories are named as in the report tables, e.g. first or female.
+fn category(passenger: &TrainingPassenger, feature: &Feature) -> Cell {
+    match passenger.get_feature(feature) {
+        FeatureValue::Missing => Cell::Missing,
+        value => Cell::Text(feature.format_value(&value)),
+    }
+}
+
+pub fn cell(passenger: &TrainingPassenger, column: &Column) -> Cell {
+    match column {
+        Column::PassengerId => Cell::Number(*passenger.get_passenger_id() as f64),
+        Column::Survived => Cell::Text(match passenger.get_survived() {
+            Survived::Yes => "1".to_string(),
+            Survived::No => "0".to_string(),
+        }),
+        Column::PassengerClass => category(passenger, &Feature::PassengerClass),
+        Column::Name => text(passenger.get_name()),
+        Column::Sex => category(passenger, &Feature::Sex),
+        Column::Age => passenger.get_age().map_or(Cell::Missing, Cell::Number),
+        Column::SiblingsSpouses => passenger.get_siblings_spouses().map_or(Cell::Missing, |count| Cell::Number(quick_convert(&count))),
+        Column::ParentsChildren => passenger.get_parents_children().map_or(Cell::Missing, |count| Cell::Number(quick_convert(&count))),
+        Column::Ticket => text(passenger.get_ticket_id()),
+        Column::Fare => passenger.get_fare().map_or(Cell::Missing, Cell::Number),
+        Column::Cabin => text(passenger.get_cabin_id()),
+        Column::PortOfEmbarkation => category(passenger, &Feature::PortOfEmbarkation),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericSummary {
+    pub mean: f64,
+    // Population standard deviation
+    pub std: f64,
+    pub min: f64,
+    pub q25: f64,
+    pub median: f64,
+    pub q75: f64,
+    pub max: f64,
+}
+
+// The q quantile of sorted numbers, interpolating linearly between the two nearest.
+pub fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN
+    }
+    let position = q.mul(quick_convert(&sorted.len().sub(1)));
+    let below = position.floor();
+    let index = (below as usize).min(sorted.len() - 1);
+    let above = sorted[index.add(1).min(sorted.len() - 1)];
+    sorted[index].add(above.sub(sorted[index]).mul(position.sub(below)))
+}
+
+impl NumericSummary {
+    pub fn from_numbers(numbers: &[f64]) -> Option<NumericSummary> {
+        if numbers.is_empty() {
+            return None
+        }
+        let mut sorted = numbers.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let count = quick_convert(&sorted.len());
+        let mean = sorted.iter().sum::<f64>().div(count);
+        let variance = sorted.iter().fold(0_f64, |sum, number| sum.add(number.sub(mean).powi(2))).div(count);
+        Some(NumericSummary {
+            mean,
+            std: variance.sqrt(),
+            min: sorted[0],
+            q25: quantile(&sorted, 0.25_f64),
+            median: quantile(&sorted, 0.5_f64),
+            q75: quantile(&sorted, 0.75_f64),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSummary {
+    pub column: Column,
+    pub rows: usize,
+    pub missing: usize,
+    // Distinct values other than missing
+    pub distinct: usize,
+    // Only for numeric columns
+    pub numeric: Option<NumericSummary>,
+    // Only for the other columns: every value with its count, most frequent first
+    pub frequencies: Vec<(String, usize)>,
+    // P(Survived) per category, or per quantile bin of age and fare, as in the report command
+    pub survival: Vec<ConditionalRow>,
+}
+
+pub fn describe_column(passengers: &[TrainingPassenger], column: &Column) -> ColumnSummary {
+    let mut numbers = Vec::new();
+    let mut frequencies: Vec<(String, usize)> = Vec::new();
+    let mut missing = 0_usize;
+    for passenger in passengers {
+        match cell(passenger, column) {
+            Cell::Missing => missing += 1,
+            Cell::Number(number) => numbers.push(number),
+            Cell::Text(text) => match frequencies.iter_mut().find(|(value, _count)| *value == text) {
+                Some(frequency) => frequency.1 += 1,
+                None => frequencies.push((text, 1)),
+            },
+        }
+    }
+    frequencies.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let numeric = NumericSummary::from_numbers(&numbers);
+    numbers.sort_by(|a, b| a.total_cmp(b));
+    numbers.dedup();
+    ColumnSummary {
+        column: *column,
+        rows: passengers.len(),
+        missing,
+        distinct: numbers.len().add(frequencies.len()),
+        numeric,
+        frequencies,
+        survival: column_feature(column).map_or_else(Vec::new, |feature| conditional_table(passengers, &feature, REPORT_BINS)),
+    }
+}
+
+fn print_summary(summary: &ColumnSummary) {
+    println!();
+    println!("{} ({}): {} rows, {} missing ({:.1}%), {} distinct", summary.column.canonical(), summary.column, summary.rows, summary.missing, quick_convert(&summary.missing).div(quick_convert(&summary.rows)).mul(100_f64), summary.distinct);
+    if let Some(numeric) = &summary.numeric {
+        println!("{:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}", "mean", "std", "min", "25%", "median", "75%", "max");
+        println!("{:>12.4} {:>12.4} {:>12.4} {:>12.4} {:>12.4} {:>12.4} {:>12.4}", numeric.mean, numeric.std, numeric.min, numeric.q25, numeric.median, numeric.q75, numeric.max);
+    }
+    if !summary.frequencies.is_empty() {
+        println!("{:>20} {:>10} {:>10}", "value", "count", "share");
+        for (value, count) in summary.frequencies.iter().take(FREQUENCY_ROWS) {
+            println!("{:>20} {:>10} {:>10.4}", value, count, quick_convert(count).div(quick_convert(&summary.rows)));
+        }
+        if summary.frequencies.len() > FREQUENCY_ROWS {
+            println!("... {} more values", summary.frequencies.len().sub(FREQUENCY_ROWS));
+        }
+    }
+    if !summary.survival.is_empty() {
+        println!("P(Survived | {})", summary.column);
+        println!("{:>20} {:>10} {:>10} {:>12}", "value", "passengers", "survived", "P(Survived)");
+        for row in &summary.survival {
+            println!("{:>20} {:>10} {:>10} {:>12.4}", row.value, row.passengers, row.survived, row.survival_rate());
+        }
+    }
+}
+
+// Prints a summary of every column of the training passengers.
+pub fn run(config: &Config) -> Result<(), String> {
+    let training_passengers = config.get_training_passengers()?;
+    if training_passengers.is_empty() {
+        return Err("describe::run needs at least one training passenger".to_string())
+    }
+    println!("describe::run {} training passengers from {}", training_passengers.len(), config.get_training_data_filename());
+    for column in Column::ALL.iter() {
+        print_summary(&describe_column(&training_passengers, column));
+    }
+    Ok(())
+}
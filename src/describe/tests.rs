@@ -0,0 +1,69 @@
+use super::*;
+use std::env;
+use std::fs;
+
+use crate::test_support::passengers;
+
+#[test]
+fn when_quantile_then_interpolated_between_nearest() {
+    let sorted = [1_f64, 2_f64, 3_f64, 4_f64];
+
+    assert_abs_diff_eq!(quantile(&sorted, 0_f64), 1_f64);
+    assert_abs_diff_eq!(quantile(&sorted, 0.25_f64), 1.75_f64);
+    assert_abs_diff_eq!(quantile(&sorted, 0.5_f64), 2.5_f64);
+    assert_abs_diff_eq!(quantile(&sorted, 1_f64), 4_f64);
+    assert!(quantile(&[], 0.5_f64).is_nan());
+}
+
+#[test]
+fn when_numeric_column_then_missing_counted_and_summary_of_the_rest() {
+    let passengers = passengers(&["1,0,3,A,male,22,1,0,T1,7.25,,S", "2,1,1,B,female,,1,0,T2,71.28,C85,C", "3,1,3,C,female,26,0,0,T3,7.92,,S", "4,1,1,D,female,35,1,0,T4,53.1,C123,S"]);
+
+    let summary = describe_column(&passengers, &Column::Age);
+
+    assert_eq!(summary.rows, 4);
+    assert_eq!(summary.missing, 1);
+    assert_eq!(summary.distinct, 3);
+    assert!(summary.frequencies.is_empty());
+    let numeric = summary.numeric.unwrap();
+    assert_abs_diff_eq!(numeric.mean, 27.666666666666668_f64, epsilon = 1e-12);
+    assert_abs_diff_eq!(numeric.median, 26_f64);
+    assert_abs_diff_eq!(numeric.min, 22_f64);
+    assert_abs_diff_eq!(numeric.max, 35_f64);
+    assert_eq!(summary.survival[0].value, "missing");
+    assert_eq!(summary.survival[0].passengers, 1);
+}
+
+#[test]
+fn when_categorical_column_then_frequencies_most_frequent_first_with_survival() {
+    let passengers = passengers(&["1,0,3,A,male,22,1,0,T1,7.25,,S", "2,1,1,B,female,38,1,0,T2,71.28,C85,C", "3,1,3,C,female,26,0,0,T3,7.92,,S", "4,1,1,D,female,35,1,0,T4,53.1,C123,S"]);
+
+    let sex = describe_column(&passengers, &Column::Sex);
+    let cabin = describe_column(&passengers, &Column::Cabin);
+    let id = describe_column(&passengers, &Column::PassengerId);
+
+    assert_eq!(sex.frequencies, vec![("female".to_string(), 3), ("male".to_string(), 1)]);
+    assert_eq!(sex.distinct, 2);
+    assert!(sex.numeric.is_none());
+    let female = sex.survival.iter().find(|row| row.value == "female").unwrap();
+    assert_eq!((female.passengers, female.survived), (3, 3));
+    assert_eq!(cabin.missing, 2);
+    assert_eq!(cabin.frequencies, vec![("C123".to_string(), 1), ("C85".to_string(), 1)]);
+    assert!(id.survival.is_empty());
+    assert_eq!(id.distinct, 4);
+}
+
+#[test]
+fn when_run_then_training_file_read_from_its_path_and_empty_one_refused() {
+    let filename = env::temp_dir().join(format!("kaggle_c_titanic_describe_{}.csv", std::process::id())).to_string_lossy().into_owned();
+    let config = Config::new(&["first", "describe", "0", "0", &filename, "fifth", "sixth"].map(|arg| arg.to_string())).unwrap();
+
+    fs::write(&filename, "PassengerId,Survived,Pclass,Name,Sex,Age,SibSp,Parch,Ticket,Fare,Cabin,Embarked\n1,0,3,A,male,22,1,0,T1,7.25,,S\n").unwrap();
+    let described = run(&config);
+    fs::write(&filename, "PassengerId,Survived,Pclass,Name,Sex,Age,SibSp,Parch,Ticket,Fare,Cabin,Embarked\n").unwrap();
+    let empty = run(&config);
+    fs::remove_file(&filename).unwrap();
+
+    assert!(described.is_ok());
+    assert!(empty.unwrap_err().contains("at least one training passenger"));
+}
@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+use std::str::FromStr;
+
+use crate::classification::{self, quick_convert, random::Rng, BinaryClass, Classifier, LogisticBinaryClassificationTestable, LogisticBinaryClassificationTrainable, LogisticRegression, Regularizable, SolverSettings};
+use crate::{Config, Learner, PassengerFeatures, PassengerWeights, Registry, TrainedLearner, TrainingPassenger};
+
+#[cfg(test)]
+mod tests;
+
+// Probabilities are kept this far from 0 and 1 before the meta-learner takes their logit.
+const PROBABILITY_CLIP: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnsembleMethod {
+    // The share of the learners predicting survival
+    HardVoting,
+    // The mean probability of the learners
+    SoftVoting,
+    // A logistic meta-learner on the logits of the learners' out-of-fold probabilities
+    Stacking,
+}
+
+impl FromStr for EnsembleMethod {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<EnsembleMethod, String> {
+        match name {
+            "hard_voting" => Ok(EnsembleMethod::HardVoting),
+            "soft_voting" => Ok(EnsembleMethod::SoftVoting),
+            "stacking" => Ok(EnsembleMethod::Stacking),
+            _ => Err(format!("EnsembleMethod::from_str unknown ensemble method {}, expected one of hard_voting, soft_voting, stacking", name)),
+        }
+    }
+}
+
+impl fmt::Display for EnsembleMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EnsembleMethod::HardVoting => write!(f, "hard_voting"),
+            EnsembleMethod::SoftVoting => write!(f, "soft_voting"),
+            EnsembleMethod::Stacking => write!(f, "stacking"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnsembleSettings {
+    pub method: EnsembleMethod,
+    // The base learners, each fitted with its own settings from the configuration
+    pub learners: Vec<Learner>,
+    // Folds giving the out-of-fold probabilities the stacking meta-learner is trained on
+    pub folds: usize,
+    // Learning rate and iterations of classification::solve for the meta-learner
+    pub meta_learning_rate: f64,
+    pub meta_iterations: u64,
+}
+
+impl Default for EnsembleSettings {
+    fn default() -> Self {
+        EnsembleSettings {
+            method: EnsembleMethod::SoftVoting,
+            learners: vec![Learner::Forest, Learner::Boosting, Learner::Bayes],
+            folds: 5,
+            meta_learning_rate: 0.001_f64,
+            meta_iterations: 500,
+        }
+    }
+}
+
+impl EnsembleSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.learners.is_empty() || self.learners.contains(&Learner::Ensemble) {
+            return Err(format!("EnsembleSettings::validate expected at least one learner other than ensemble, got {:?}", self.learners))
+        }
+        if self.folds < 2 || self.meta_learning_rate <= 0_f64 || self.meta_iterations == 0 {
+            return Err("EnsembleSettings::validate folds should be at least 2, meta_learning_rate positive and meta_iterations at least 1".to_string())
+        }
+        Ok(())
+    }
+}
+
+// Weights of the stacking meta-learner, one per base learner. As for PassengerWeights the probability of survival is
+// the logistic of minus the weighted sum, so a learner that is trusted gets a negative weight.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StackingWeights {
+    pub bias: f64,
+    pub weights: Vec<f64>,
+}
+
+impl StackingWeights {
+    fn weighted_sum(&self, logits: &[f64]) -> f64 {
+        self.weights.iter().zip(logits).fold(self.bias, |sum, (weight, logit)| sum.add(weight.mul(logit)))
+    }
+}
+
+impl Regularizable for StackingWeights {
+    fn shrink(self: &mut Self, factor: &f64) {
+        for weight in self.weights.iter_mut() {
+            *weight = weight.mul(factor);
+        }
+    }
+
+    fn squared_norm(self: &Self) -> f64 {
+        self.weights.iter().fold(0_f64, |sum, weight| sum.add(weight.mul(weight)))
+    }
+}
+
+// A passenger as the meta-learner sees it: the logits of the base learners' probabilities
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackedRecord {
+    pub record_id: u64,
+    pub logits: Vec<f64>,
+    pub answer: BinaryClass,
+}
+
+impl LogisticBinaryClassificationTestable for StackedRecord {
+    type Weights = StackingWeights;
+
+    fn hypothesis(self: &Self, weights: &Self::Weights) -> Result<f64, String> {
+        Ok(Self::logistic(weights.weighted_sum(&self.logits)))
+    }
+
+    fn get_record_id(self: &Self) -> &u64 {
+        &self.record_id
+    }
+}
+
+impl LogisticBinaryClassificationTrainable for StackedRecord {
+    fn answer(self: &Self) -> BinaryClass {
+        self.answer
+    }
+
+    fn update_weights(self: &Self, diff: &f64, weights: &mut Self::Weights) -> Result<(), String> {
+        weights.bias = weights.bias.add(diff);
+        for (weight, logit) in weights.weights.iter_mut().zip(&self.logits) {
+            *weight = weight.add(diff.mul(logit));
+        }
+        Ok(())
+    }
+}
+
+fn logit(probability: f64) -> f64 {
+    let clipped = probability.max(PROBABILITY_CLIP).min(1_f64.sub(PROBABILITY_CLIP));
+    clipped.div(1_f64.sub(clipped)).ln()
+}
+
+fn fit_learners(config: &Config, learners: &[Learner], passengers: &[TrainingPassenger]) -> Result<Vec<TrainedLearner>, String> {
+    let registry = Registry::default();
+    let mut fitted = Vec::new();
+    for learner in learners {
+        match registry.get(&learner.to_string()) {
+            Some(entry) => fitted.push((entry.fit)(config, passengers)?),
+            None => return Err(format!("Ensemble::fit learner {} is not registered", learner)),
+        }
+    }
+    Ok(fitted)
+}
+
+// Fits a learner again to new passengers with the settings and seed it holds. A logistic learner has only its weights,
+// so it is solved with solver starting from them, as LogisticRegression does.
+fn refit(learner: &TrainedLearner, solver: &SolverSettings, passengers: &[TrainingPassenger]) -> Result<TrainedLearner, String> {
+    let mut refitted = learner.clone();
+    match &mut refitted {
+        TrainedLearner::Logistic(weights) => {
+            let mut logistic = LogisticRegression::new(weights.clone(), solver.clone());
+            logistic.fit(passengers)?;
+            *weights = logistic.into_weights();
+        },
+        TrainedLearner::Tree(tree) => Classifier::fit(tree, passengers)?,
+        TrainedLearner::Forest(forest) => Classifier::fit(forest, passengers)?,
+        TrainedLearner::Boosting(boosting) => Classifier::fit(boosting, passengers)?,
+        TrainedLearner::Bayes(bayes) => Classifier::fit(bayes, passengers)?,
+        TrainedLearner::Knn(knn) => Classifier::fit(knn, passengers)?,
+        TrainedLearner::Svm(svm) => Classifier::fit(svm, passengers)?,
+        TrainedLearner::Mlp(mlp) => Classifier::fit(mlp, passengers)?,
+        TrainedLearner::Ensemble(ensemble) => ensemble.fit(passengers)?,
+    }
+    Ok(refitted)
+}
+
+// Each learner is fitted by fit_fold once per fold on the other folds to predict the passengers of that fold, and the
+// meta-learner is solved on those out-of-fold predictions, so it learns how far to trust each learner on passengers it
+// has not seen.
+fn fit_meta<F>(settings: &EnsembleSettings, seed: u64, passengers: &[TrainingPassenger], fit_fold: F) -> Result<StackingWeights, String>
+where
+    F: Fn(&[TrainingPassenger]) -> Result<Vec<TrainedLearner>, String>,
+{
+    let mut rng = Rng::new(seed);
+    let fold_indices = classification::k_fold_indices_shuffled(passengers.len(), settings.folds, &mut rng)?;
+    let mut logits = vec![Vec::new(); passengers.len()];
+    for (fold, indices) in fold_indices.iter().enumerate() {
+        let training: Vec<TrainingPassenger> = passengers.iter().enumerate().filter(|(index, _passenger)| !indices.contains(index)).map(|(_index, passenger)| passenger.clone()).collect();
+        let learners = fit_fold(&training)?;
+        for index in indices {
+            for learner in &learners {
+                logits[*index].push(logit(learner.predict_proba(&passengers[*index])?));
+            }
+        }
+        eprintln!("Ensemble::fit Predicted fold {} of {} out of fold", fold.add(1), settings.folds);
+    }
+    let records: Vec<StackedRecord> = passengers.iter().zip(logits).map(|(passenger, logits)| StackedRecord {
+        record_id: *passenger.get_passenger_id(),
+        logits,
+        answer: passenger.answer(),
+    }).collect();
+    let mut weights = StackingWeights { bias: 0_f64, weights: vec![0_f64; settings.learners.len()] };
+    let mut solver_settings = SolverSettings::new(settings.meta_learning_rate, 0_f64);
+    solver_settings.max_iterations = Some(settings.meta_iterations);
+    solver_settings.seed = seed;
+    solver_settings.verbose = false;
+    classification::solve_with_settings(&records, &mut weights, &solver_settings)?;
+    eprintln!("Ensemble::fit Meta-learner weights {:?} for {:?} with bias {}", weights.weights, settings.learners, weights.bias);
+    Ok(weights)
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Ensemble {
+    settings: EnsembleSettings,
+    seed: u64,
+    // How a logistic base learner is solved when the ensemble is fitted again
+    solver: SolverSettings,
+    learners: Vec<TrainedLearner>,
+    // Only for EnsembleMethod::Stacking
+    meta: Option<StackingWeights>,
+}
+
+impl Ensemble {
+    // Fits every base learner on all the passengers, and for stacking the meta-learner on out-of-fold predictions.
+    pub fn fit(config: &Config, passengers: &[TrainingPassenger]) -> Result<Ensemble, String> {
+        let settings = config.get_ensemble_settings();
+        settings.validate()?;
+        let seed = *config.get_seed();
+        let meta = match settings.method {
+            EnsembleMethod::Stacking => Some(fit_meta(settings, seed, passengers, |training| fit_learners(config, &settings.learners, training))?),
+            EnsembleMethod::HardVoting | EnsembleMethod::SoftVoting => None,
+        };
+        let learners = fit_learners(config, &settings.learners, passengers)?;
+        eprintln!("Ensemble::fit Combined {} learners by {}", learners.len(), settings.method);
+        Ok(Ensemble { settings: settings.clone(), seed, solver: config.get_solver_settings(), learners, meta })
+    }
+
+    pub fn get_method(&self) -> &EnsembleMethod {
+        &self.settings.method
+    }
+
+    pub fn get_learners(&self) -> &Vec<TrainedLearner> {
+        &self.learners
+    }
+
+    pub fn get_meta(&self) -> &Option<StackingWeights> {
+        &self.meta
+    }
+
+    // Probability of BinaryClass::Yes
+    pub fn predict_proba<R>(&self, record: &R) -> Result<f64, String>
+    where
+        R: PassengerFeatures + LogisticBinaryClassificationTestable<Weights = PassengerWeights>,
+    {
+        let mut probabilities = Vec::new();
+        for learner in &self.learners {
+            probabilities.push(learner.predict_proba(record)?);
+        }
+        let count = quick_convert(&probabilities.len());
+        match (&self.settings.method, &self.meta) {
+            (EnsembleMethod::HardVoting, _meta) => Ok(quick_convert(&probabilities.iter().filter(|probability| **probability > 0.5_f64).count()).div(count)),
+            (EnsembleMethod::SoftVoting, _meta) => Ok(probabilities.iter().sum::<f64>().div(count)),
+            (EnsembleMethod::Stacking, Some(meta)) => {
+                let logits: Vec<f64> = probabilities.into_iter().map(logit).collect();
+                Ok(StackedRecord::logistic(meta.weighted_sum(&logits)))
+            },
+            (EnsembleMethod::Stacking, None) => Err("Ensemble::predict_proba a stacking ensemble has no meta-learner".to_string()),
+        }
+    }
+}
+
+// fit fits every base learner again with the settings it holds, and for stacking solves the meta-learner again.
+impl Classifier<TrainingPassenger> for Ensemble {
+    fn fit(&mut self, records: &[TrainingPassenger]) -> Result<(), String> {
+        if self.settings.method == EnsembleMethod::Stacking {
+            self.meta = Some(fit_meta(&self.settings, self.seed, records, |training| self.learners.iter().map(|learner| refit(learner, &self.solver, training)).collect())?);
+        }
+        let mut learners = Vec::new();
+        for learner in &self.learners {
+            learners.push(refit(learner, &self.solver, records)?);
+        }
+        self.learners = learners;
+        Ok(())
+    }
+
+    fn predict_proba(&self, record: &TrainingPassenger) -> Result<f64, String> {
+        Ensemble::predict_proba(self, record)
+    }
+}
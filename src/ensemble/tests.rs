@@ -0,0 +1,73 @@
+use super::*;
+use crate::test_support::training_passengers;
+use crate::{fit, Model};
+
+fn config(method: &str, learners: &str) -> Config {
+    let args = vec!["first".to_string(), "0.00001".to_string(), "0.01".to_string(), "fourth".to_string(), "fifth".to_string(), "sixth".to_string(),
+        "--learner".to_string(), "ensemble".to_string(), "--ensemble".to_string(), method.to_string(), "--ensemble-learners".to_string(), learners.to_string(),
+        "--stacking-folds".to_string(), "3".to_string(), "--max-depth".to_string(), "2".to_string(), "--neighbours".to_string(), "3".to_string()];
+    Config::new(&args).unwrap()
+}
+
+fn ensemble(model: &Model) -> &Ensemble {
+    match model.get_learner() {
+        TrainedLearner::Ensemble(ensemble) => ensemble,
+        _other => panic!("ensemble::tests::ensemble the model is not an ensemble"),
+    }
+}
+
+#[test]
+fn when_voting_then_probability_combines_learners() {
+    let passengers = training_passengers();
+    let soft = fit(&config("soft_voting", "tree,bayes,knn"), passengers.clone()).unwrap();
+    let hard = fit(&config("hard_voting", "tree,bayes,knn"), passengers.clone()).unwrap();
+
+    for passenger in &passengers {
+        let probabilities: Vec<f64> = ensemble(&soft).get_learners().iter().map(|learner| learner.predict_proba(passenger).unwrap()).collect();
+        assert_eq!(probabilities.len(), 3);
+        assert_abs_diff_eq!(ensemble(&soft).predict_proba(passenger).unwrap(), probabilities.iter().sum::<f64>() / 3_f64, epsilon = 1e-12);
+        let votes = probabilities.iter().filter(|probability| **probability > 0.5_f64).count() as f64;
+        assert_abs_diff_eq!(ensemble(&hard).predict_proba(passenger).unwrap(), votes / 3_f64);
+    }
+    assert_eq!(ensemble(&hard).get_learners(), ensemble(&soft).get_learners());
+}
+
+#[test]
+fn when_stacking_then_meta_learner_trusts_informative_learner_and_model_round_trips() {
+    let passengers = training_passengers();
+    let model = fit(&config("stacking", "tree,knn"), passengers.clone()).unwrap();
+    let meta = ensemble(&model).get_meta().as_ref().unwrap();
+
+    // The tree splits on sex, which decides survival, so its logit pushes the weighted sum down for survivors.
+    assert_eq!(meta.weights.len(), 2);
+    assert!(meta.weights[0] < 0_f64);
+    let json = serde_json::to_string(&model).unwrap();
+    let read: Model = serde_json::from_str(&json).unwrap();
+    assert_eq!(read, model);
+    for passenger in &passengers {
+        let probability = ensemble(&model).predict_proba(passenger).unwrap();
+        assert_eq!(probability > 0.5_f64, passenger.answer() == BinaryClass::Yes);
+    }
+}
+
+#[test]
+fn when_fitted_again_as_classifier_then_same_as_fitted_from_config() {
+    let passengers = training_passengers();
+    let model = fit(&config("stacking", "tree,bayes"), passengers.clone()).unwrap();
+    let mut refitted = ensemble(&model).clone();
+
+    Classifier::fit(&mut refitted, &passengers).unwrap();
+
+    assert_eq!(&refitted, ensemble(&model));
+    for passenger in &passengers {
+        assert_abs_diff_eq!(Classifier::predict_proba(&refitted, passenger).unwrap(), ensemble(&model).predict_proba(passenger).unwrap());
+    }
+}
+
+#[test]
+fn when_ensemble_settings_invalid_then_error() {
+    assert!(EnsembleSettings { learners: vec![Learner::Tree, Learner::Ensemble], ..EnsembleSettings::default() }.validate().is_err());
+    assert!(EnsembleSettings { learners: Vec::new(), ..EnsembleSettings::default() }.validate().is_err());
+    assert!(EnsembleSettings { folds: 1, ..EnsembleSettings::default() }.validate().is_err());
+    assert!(EnsembleSettings::default().validate().is_ok());
+}
@@ -0,0 +1,219 @@
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::classification::{BinaryClass, LogisticBinaryClassificationTestable};
+use crate::{fit, Config, Feature, FeatureValue, Model, PassengerFeatures, PassengerWeights, TrainedLearner};
+
+#[cfg(test)]
+mod tests;
+
+// What the parts of an explanation add up to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    // The weighted sum of a logistic model, where P(Survived) = 1 / (1 + exp(weighted sum)), so negative parts raise
+    // the chance of survival
+    WeightedSum,
+    // P(Survived) itself
+    Probability,
+}
+
+// How one prediction splits among the features: base plus the contributions gives the prediction on its scale.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub scale: Scale,
+    // The bias of a logistic model, or P(Survived) with every feature missing
+    pub base: f64,
+    // Largest first, leaving out the features that contribute nothing
+    pub contributions: Vec<(Feature, f64)>,
+}
+
+// Leaves out the features that contribute nothing and puts the rest largest first, whatever their sign.
+fn largest_first(mut contributions: Vec<(Feature, f64)>) -> Vec<(Feature, f64)> {
+    contributions.retain(|(_feature, contribution)| *contribution != 0_f64);
+    contributions.sort_by(|(_feature, contribution), (_other, other_contribution)| other_contribution.abs().total_cmp(&contribution.abs()));
+    contributions
+}
+
+// The first count contributions as e.g. "sex=female:+0.312; passenger_class=third:-0.101"
+pub fn format_effects<R: PassengerFeatures>(record: &R, effects: &[(Feature, f64)], count: usize) -> String {
+    effects.iter().take(count).map(|(feature, effect)| {
+        format!("{}={}:{:+.3}", feature.get_name(), feature.format_value(&record.get_feature(feature)), effect)
+    }).collect::<Vec<String>>().join("; ")
+}
+
+impl Explanation {
+    fn new(scale: Scale, base: f64, contributions: Vec<(Feature, f64)>) -> Explanation {
+        Explanation { scale, base, contributions: largest_first(contributions) }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.contributions.iter().fold(self.base, |total, (_feature, contribution)| total.add(contribution))
+    }
+
+    // Every contribution as e.g. "sex=male:+1.005; age=34.5:+0.424"
+    pub fn format<R: PassengerFeatures>(&self, record: &R) -> String {
+        format_effects(record, &self.contributions, self.contributions.len())
+    }
+}
+
+// A learner that can say how each feature of a passenger moved its prediction
+pub trait Explain {
+    fn explain<R>(&self, record: &R) -> Result<Explanation, String>
+    where
+        R: PassengerFeatures + LogisticBinaryClassificationTestable<Weights = PassengerWeights>;
+}
+
+// The contribution of a feature is its weight times its value, exactly as hypothesis sums them.
+impl Explain for PassengerWeights {
+    fn explain<R>(&self, record: &R) -> Result<Explanation, String>
+    where
+        R: PassengerFeatures + LogisticBinaryClassificationTestable<Weights = PassengerWeights>,
+    {
+        let mut contributions = Vec::new();
+        for feature in self.get_features() {
+            let (index, value) = PassengerWeights::encode(&record.get_feature(feature));
+            match self.get_feature_weights(feature).get(&index) {
+                Some(weight) => contributions.push((*feature, weight.mul(value))),
+                None => return Err(format!("PassengerWeights::explain: {} weight {} was unreachable for passenger {}", feature, index, record.get_passenger_id())),
+            }
+        }
+        Ok(Explanation::new(Scale::WeightedSum, *self.get_bias(), contributions))
+    }
+}
+
+// Logistic models are explained by their weights. Every other learner gets Shapley values of its probability.
+impl Explain for Model {
+    fn explain<R>(&self, record: &R) -> Result<Explanation, String>
+    where
+        R: PassengerFeatures + LogisticBinaryClassificationTestable<Weights = PassengerWeights>,
+    {
+        match self.get_learner() {
+            TrainedLearner::Logistic(weights) => weights.explain(record),
+            _ => shapley_values(self, record),
+        }
+    }
+}
+
+// A record with only some of its features kept, the others made missing
+struct Coalition<'a, R> {
+    record: &'a R,
+    kept: Vec<Feature>,
+}
+
+// The features a record has a value for, the only ones that can move its prediction
+fn present_features<R: PassengerFeatures>(record: &R) -> Vec<Feature> {
+    Feature::ALL.iter().filter(|feature| record.get_feature(feature) != FeatureValue::Missing).copied().collect()
+}
+
+impl<'a, R> Coalition<'a, R> {
+    // Every feature kept but one
+    fn without(record: &'a R, feature: Feature) -> Coalition<'a, R> {
+        Coalition { record, kept: Feature::ALL.iter().filter(|kept| **kept != feature).copied().collect() }
+    }
+}
+
+impl<'a, R: PassengerFeatures> PassengerFeatures for Coalition<'a, R> {
+    fn get_passenger_id(&self) -> &u64 {
+        self.record.get_passenger_id()
+    }
+
+    fn get_feature(&self, feature: &Feature) -> FeatureValue {
+        if self.kept.contains(feature) { self.record.get_feature(feature) } else { FeatureValue::Missing }
+    }
+}
+
+impl<'a, R: PassengerFeatures> LogisticBinaryClassificationTestable for Coalition<'a, R> {
+    type Weights = PassengerWeights;
+
+    fn hypothesis(self: &Self, weights: &Self::Weights) -> Result<f64, String> {
+        Ok(Self::logistic(weights.weighted_sum(self)?))
+    }
+
+    fn get_record_id(self: &Self) -> &u64 {
+        self.record.get_passenger_id()
+    }
+}
+
+// Shapley values of the model's probability over the features the passenger has, where leaving a feature out of a
+// coalition makes it missing, as every learner can handle. They are exact, from the probability of every coalition,
+// which the at most ten features keep to 1024 predictions.
+pub fn shapley_values<R>(model: &Model, record: &R) -> Result<Explanation, String>
+where
+    R: PassengerFeatures + LogisticBinaryClassificationTestable<Weights = PassengerWeights>,
+{
+    let features = present_features(record);
+    let count = features.len();
+    let mut probabilities = Vec::with_capacity(1 << count);
+    for coalition in 0..(1_usize << count) {
+        let kept = features.iter().enumerate().filter(|(position, _feature)| coalition & (1 << position) != 0).map(|(_position, feature)| *feature).collect();
+        probabilities.push(model.probability(&Coalition { record, kept })?);
+    }
+    // The share of the orderings of the features in which a feature joins a coalition of each size
+    let factorial = |number: usize| (1..=number).fold(1_f64, |product, factor| product.mul(factor as f64));
+    let shares: Vec<f64> = (0..count).map(|size| factorial(size).mul(factorial(count.sub(size).sub(1))).div(factorial(count))).collect();
+    let contributions = features.iter().enumerate().map(|(position, feature)| {
+        let value = (0..(1_usize << count)).filter(|coalition| coalition & (1 << position) == 0).fold(0_f64, |value, coalition| {
+            let gain = probabilities[coalition | (1 << position)].sub(probabilities[coalition]);
+            value.add(shares[coalition.count_ones() as usize].mul(gain))
+        });
+        (*feature, value)
+    }).collect();
+    Ok(Explanation::new(Scale::Probability, probabilities[0], contributions))
+}
+
+// How much each feature of a passenger moves the model's probability of survival: the probability minus the one with
+// that feature missing, which works for every learner since they all handle missing values. Features that make no
+// difference are left out and the rest come largest effect first.
+pub fn feature_effects<R>(model: &Model, record: &R) -> Result<Vec<(Feature, f64)>, String>
+where
+    R: PassengerFeatures + LogisticBinaryClassificationTestable<Weights = PassengerWeights>,
+{
+    let probability = model.probability(record)?;
+    let mut effects = Vec::new();
+    for feature in present_features(record) {
+        effects.push((feature, probability.sub(model.probability(&Coalition::without(record, feature))?)));
+    }
+    Ok(largest_first(effects))
+}
+
+fn print_explanation<R: PassengerFeatures>(record: &R, explanation: &Explanation) {
+    let (heading, base, total) = match explanation.scale {
+        Scale::WeightedSum => ("Contributions weight * value to the weighted sum, where P(Survived) = 1 / (1 + exp(weighted sum)), so negative ones raise survival", "bias", "weighted sum"),
+        Scale::Probability => ("Shapley values of P(Survived), starting from every feature missing", "all missing", "P(Survived)"),
+    };
+    println!("{}", heading);
+    println!("{:>40} {:>12}", "feature", "contribution");
+    println!("{:>40} {:>12.4}", base, explanation.base);
+    for (feature, contribution) in &explanation.contributions {
+        println!("{:>40} {:>+12.4}", format!("{}={}", feature.get_name(), feature.format_value(&record.get_feature(feature))), contribution);
+    }
+    println!("{:>40} {:>12.4}", total, explanation.total());
+}
+
+// Explains the prediction of the --model, or of one trained on the training data, for the test passenger with the
+// PassengerId given by --id.
+pub fn run(config: &Config) -> Result<(), String> {
+    let id = match config.get_explain_id() {
+        Some(id) => *id,
+        None => return Err("explain::run needs the --id of a test passenger to explain".to_string()),
+    };
+    if config.get_test_data_filename().is_empty() {
+        return Err("explain::run needs the --test-data holding the passenger".to_string())
+    }
+    let model = match config.get_model_filename() {
+        Some(filename) => Model::read(filename)?,
+        None => fit(config, config.get_training_passengers()?)?,
+    };
+    let test_passengers = config.get_test_passengers()?;
+    let passenger = match test_passengers.iter().find(|passenger| *passenger.get_passenger_id() == id) {
+        Some(passenger) => passenger,
+        None => return Err(format!("explain::run PassengerId {} is not in {}", id, config.get_test_data_filename())),
+    };
+    let outcome = model.predict(passenger)?;
+    let predicted = match outcome.prediction {
+        BinaryClass::Yes => 1,
+        BinaryClass::No => 0,
+    };
+    println!("explain::run PassengerId {} with model {}: P(Survived) = {:.4}, predicted {}", id, model.id()?, outcome.probability, predicted);
+    print_explanation(passenger, &model.explain(passenger)?);
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use super::*;
+
+use crate::test_support::training_passengers;
+use crate::{Passenger, PassengerClass, PortOfEmbarkation, Sex};
+
+fn model(options: &[&str]) -> Model {
+    let mut args: Vec<String> = ["first", "0.001", "0.01", "fourth", "fifth", "sixth", "--max-iterations", "200"].iter().map(|arg| arg.to_string()).collect();
+    args.extend(options.iter().map(|option| option.to_string()));
+    fit(&Config::new(&args).unwrap(), training_passengers()).unwrap()
+}
+
+fn passenger() -> Passenger {
+    Passenger::new(892, PassengerClass::Third, "Test".to_string(), Sex::Male, 34.5_f64, 0, 0, "Ticket".to_string(), 7.83_f64, "Cabin".to_string(), PortOfEmbarkation::Queenstown)
+}
+
+#[test]
+fn when_logistic_then_contributions_are_weight_times_value_largest_first() {
+    let model = model(&["--features", "sex,age,passenger_class"]);
+    let weights = model.get_weights().unwrap();
+
+    let explanation = model.explain(&passenger()).unwrap();
+
+    assert_eq!(explanation.scale, Scale::WeightedSum);
+    assert_abs_diff_eq!(explanation.base, *weights.get_bias());
+    assert_abs_diff_eq!(explanation.total(), weights.weighted_sum(&passenger()).unwrap(), epsilon = 1e-12);
+    let age = explanation.contributions.iter().find(|(feature, _contribution)| *feature == Feature::Age).unwrap();
+    assert_abs_diff_eq!(age.1, weights.get_feature_weights(&Feature::Age)[&1] * 34.5_f64, epsilon = 1e-12);
+    assert!(explanation.contributions.iter().all(|(feature, _contribution)| [Feature::Sex, Feature::Age, Feature::PassengerClass].contains(feature)));
+    assert!(explanation.contributions.windows(2).all(|pair| pair[0].1.abs() >= pair[1].1.abs()));
+    assert!(explanation.format(&passenger()).starts_with(&format!("{}=", explanation.contributions[0].0.get_name())));
+}
+
+#[test]
+fn when_not_logistic_then_shapley_values_add_up_to_probability() {
+    let model = model(&["--learner", "forest", "--trees", "5"]);
+
+    let explanation = model.explain(&passenger()).unwrap();
+
+    assert_eq!(explanation.scale, Scale::Probability);
+    assert_abs_diff_eq!(explanation.total(), model.probability(&passenger()).unwrap(), epsilon = 1e-12);
+    assert_abs_diff_eq!(explanation.base, model.probability(&Coalition { record: &passenger(), kept: Vec::new() }).unwrap(), epsilon = 1e-12);
+}
+
+#[test]
+fn when_one_feature_used_then_it_takes_the_whole_difference() {
+    let model = model(&["--learner", "tree", "--features", "sex"]);
+    let female = Passenger::new(893, PassengerClass::Third, "Test".to_string(), Sex::Female, 34.5_f64, 0, 0, "Ticket".to_string(), 7.83_f64, "Cabin".to_string(), PortOfEmbarkation::Queenstown);
+
+    let male_explanation = shapley_values(&model, &passenger()).unwrap();
+    let female_explanation = shapley_values(&model, &female).unwrap();
+
+    // A missing sex follows one of the two branches, so one of the sexes differs from it.
+    assert_eq!(male_explanation.contributions.len() + female_explanation.contributions.len(), 1);
+    for (record, explanation) in [(passenger(), male_explanation), (female, female_explanation)].iter() {
+        assert!(explanation.contributions.iter().all(|(feature, _contribution)| *feature == Feature::Sex));
+        assert_abs_diff_eq!(explanation.total(), model.probability(record).unwrap(), epsilon = 1e-12);
+    }
+}
+
+#[test]
+fn when_feature_effects_then_largest_first_and_only_for_features_used() {
+    let model = model(&["--features", "sex,age,passenger_class"]);
+    let passenger = Passenger::new(892, PassengerClass::First, "Test".to_string(), Sex::Female, 30_f64, 0, 0, "Ticket".to_string(), 10_f64, "Cabin".to_string(), PortOfEmbarkation::Southampton);
+
+    let effects = feature_effects(&model, &passenger).unwrap();
+
+    assert!(!effects.is_empty());
+    assert!(effects.iter().all(|(feature, _effect)| [Feature::Sex, Feature::Age, Feature::PassengerClass].contains(feature)));
+    assert!(effects.windows(2).all(|pair| pair[0].1.abs() >= pair[1].1.abs()));
+    let masked = model.probability(&Coalition::without(&passenger, effects[0].0)).unwrap();
+    assert_abs_diff_eq!(model.probability(&passenger).unwrap() - masked, effects[0].1, epsilon = 1e-12);
+    let formatted = format_effects(&passenger, &effects, 1);
+    assert!(formatted.starts_with(&format!("{}=", effects[0].0.get_name())), "{}", formatted);
+    assert!(!formatted.contains(';'));
+}
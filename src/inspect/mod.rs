@@ -0,0 +1,211 @@
+use csv::Writer;
+use serde::Serialize;
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::analysis::chi_square_p_value;
+use crate::{fit, Config, Feature, FeatureValue, Model, PassengerFeatures, PassengerWeights};
+
+#[cfg(test)]
+mod tests;
+
+// Sweeps of Jacobi rotations, each of which roughly squares the size of what is left off the diagonal
+const JACOBI_SWEEPS: usize = 100;
+
+// Eigenvalues below this share of the largest count as zero, leaving their weights without a standard error.
+const SINGULAR_TOLERANCE: f64 = 1e-10;
+
+// One weight of a logistic model: the bias, or the weight a feature picks for one of its values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Parameter {
+    Bias,
+    Weight(Feature, usize),
+}
+
+impl Parameter {
+    // The weight a value picks, e.g. sex=female, passenger_class=third, age=missing, age or cabin_id=present
+    pub fn get_name(&self) -> String {
+        match self {
+            Parameter::Bias => "bias".to_string(),
+            Parameter::Weight(feature, 0) => format!("{}={}", feature.get_name(), feature.format_value(&FeatureValue::Missing)),
+            Parameter::Weight(feature @ (Feature::PassengerClass | Feature::Sex | Feature::PortOfEmbarkation), index) => format!("{}={}", feature.get_name(), feature.format_value(&FeatureValue::Category(*index))),
+            Parameter::Weight(feature @ (Feature::Name | Feature::TicketId | Feature::CabinId), _index) => format!("{}={}", feature.get_name(), feature.format_value(&FeatureValue::Present)),
+            Parameter::Weight(feature, _index) => feature.get_name().to_string(),
+        }
+    }
+}
+
+// The bias, then every weight of the features the model uses, in the order they are summed.
+pub fn parameters(weights: &PassengerWeights) -> Vec<(Parameter, f64)> {
+    let mut parameters = vec![(Parameter::Bias, *weights.get_bias())];
+    for feature in weights.get_features() {
+        for (index, weight) in weights.get_feature_weights(feature) {
+            parameters.push((Parameter::Weight(*feature, *index), *weight));
+        }
+    }
+    parameters
+}
+
+// Eigenvalues and eigenvectors, as columns, of a symmetric matrix by cyclic Jacobi rotations
+fn symmetric_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let size = matrix.len();
+    let mut a = matrix.to_vec();
+    let mut vectors: Vec<Vec<f64>> = (0..size).map(|row| (0..size).map(|column| if row == column { 1_f64 } else { 0_f64 }).collect()).collect();
+    let norm = a.iter().flatten().map(|value| value.powi(2)).sum::<f64>();
+    for _sweep in 0..JACOBI_SWEEPS {
+        let off_diagonal = (0..size).map(|p| ((p + 1)..size).map(|q| a[p][q].powi(2)).sum::<f64>()).sum::<f64>();
+        if off_diagonal <= norm.mul(f64::EPSILON.powi(2)) {
+            break
+        }
+        for p in 0..size {
+            for q in (p + 1)..size {
+                if a[p][q] == 0_f64 {
+                    continue
+                }
+                let theta = a[q][q].sub(a[p][p]).div(2_f64.mul(a[p][q]));
+                let t = theta.signum().div(theta.abs().add(theta.powi(2).add(1_f64).sqrt()));
+                let c = 1_f64.div(t.powi(2).add(1_f64).sqrt());
+                let s = t.mul(c);
+                for row in a.iter_mut() {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c.mul(kp).sub(s.mul(kq));
+                    row[q] = s.mul(kp).add(c.mul(kq));
+                }
+                let (row_p, row_q) = (a[p].clone(), a[q].clone());
+                a[p] = row_p.iter().zip(row_q.iter()).map(|(pk, qk)| c.mul(pk).sub(s.mul(qk))).collect();
+                a[q] = row_p.iter().zip(row_q.iter()).map(|(pk, qk)| s.mul(pk).add(c.mul(qk))).collect();
+                for row in vectors.iter_mut() {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c.mul(kp).sub(s.mul(kq));
+                    row[q] = s.mul(kp).add(c.mul(kq));
+                }
+            }
+        }
+    }
+    ((0..size).map(|index| a[index][index]).collect(), vectors)
+}
+
+// The Fisher information of the weights: the sum over the records of p (1 - p) x x', plus l2 for every weight but the
+// bias, as the penalty of the solver adds. x is 1 for the bias and, for each feature, the value of the weight it picks.
+pub fn fisher_information<R: PassengerFeatures>(weights: &PassengerWeights, records: &[R], l2: f64) -> Result<Vec<Vec<f64>>, String> {
+    let parameters = parameters(weights);
+    let mut information = vec![vec![0_f64; parameters.len()]; parameters.len()];
+    for record in records {
+        let probability = 1_f64.div(weights.weighted_sum(record)?.exp().add(1_f64));
+        let variance = probability.mul(1_f64.sub(probability));
+        let mut design = vec![(0_usize, 1_f64)];
+        for feature in weights.get_features() {
+            let (index, value) = PassengerWeights::encode(&record.get_feature(feature));
+            if let Some(position) = parameters.iter().position(|(parameter, _weight)| *parameter == Parameter::Weight(*feature, index)) {
+                design.push((position, value));
+            }
+        }
+        for (row, row_value) in &design {
+            for (column, column_value) in &design {
+                information[*row][*column] = information[*row][*column].add(variance.mul(row_value.mul(*column_value)));
+            }
+        }
+    }
+    for (position, row) in information.iter_mut().enumerate().skip(1) {
+        row[position] = row[position].add(l2);
+    }
+    Ok(information)
+}
+
+// Wald standard errors of the weights, from the inverse of the Fisher information. A weight gets none when the records
+// cannot tell it apart from the others: a value that never occurs, or, without l2, a bias and a full set of categories,
+// which only fix the differences between them.
+pub fn standard_errors(information: &[Vec<f64>]) -> Vec<Option<f64>> {
+    let (values, vectors) = symmetric_eigen(information);
+    let largest = values.iter().fold(0_f64, |largest, value| largest.max(value.abs()));
+    let singular: Vec<bool> = values.iter().map(|value| *value <= largest.mul(SINGULAR_TOLERANCE)).collect();
+    vectors.iter().map(|row| {
+        let in_null_space = row.iter().zip(singular.iter()).filter(|(_component, singular)| **singular).map(|(component, _singular)| component.powi(2)).sum::<f64>();
+        if in_null_space > SINGULAR_TOLERANCE.sqrt() {
+            return None
+        }
+        let variance = row.iter().zip(values.iter()).zip(singular.iter()).filter(|(_pair, singular)| !**singular).map(|((component, value), _singular)| component.powi(2).div(*value)).sum::<f64>();
+        Some(variance.sqrt())
+    }).collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Coefficient {
+    pub name: String,
+    pub weight: f64,
+    // exp(-weight), the factor on the odds of survival for each unit of the value, since the probability of survival
+    // is 1 / (1 + exp(weighted sum))
+    pub odds_ratio: f64,
+    pub standard_error: Option<f64>,
+    pub z: Option<f64>,
+    // Two-sided, for the weight being 0
+    pub p_value: Option<f64>,
+}
+
+// The named weights of a logistic model, with Wald statistics when the training records are given.
+pub fn coefficients<R: PassengerFeatures>(weights: &PassengerWeights, records: Option<&[R]>, l2: f64) -> Result<Vec<Coefficient>, String> {
+    let parameters = parameters(weights);
+    let errors = match records {
+        Some(records) if !records.is_empty() => standard_errors(&fisher_information(weights, records, l2)?),
+        _ => vec![None; parameters.len()],
+    };
+    Ok(parameters.iter().zip(errors).map(|((parameter, weight), error)| {
+        let z = error.map(|error| weight.div(error));
+        Coefficient {
+            name: parameter.get_name(),
+            weight: *weight,
+            odds_ratio: weight.mul(-1_f64).exp(),
+            standard_error: error,
+            z,
+            p_value: z.map(|z| chi_square_p_value(z.powi(2), 1)),
+        }
+    }).collect())
+}
+
+fn format_statistic(statistic: &Option<f64>, precision: usize) -> String {
+    statistic.map_or_else(|| "-".to_string(), |statistic| format!("{:.*}", precision, statistic))
+}
+
+fn print_coefficients(coefficients: &[Coefficient]) {
+    println!("{:>32} {:>10} {:>11} {:>10} {:>9} {:>10}", "coefficient", "weight", "odds_ratio", "std_error", "z", "p_value");
+    for coefficient in coefficients {
+        let p_value = coefficient.p_value.map_or_else(|| "-".to_string(), |p_value| format!("{:.3e}", p_value));
+        println!("{:>32} {:>10.4} {:>11.4} {:>10} {:>9} {:>10}", coefficient.name, coefficient.weight, coefficient.odds_ratio, format_statistic(&coefficient.standard_error, 4), format_statistic(&coefficient.z, 3), p_value);
+    }
+}
+
+fn write_coefficients(filename: &str, coefficients: &[Coefficient]) -> Result<(), String> {
+    match Writer::from_path(filename) {
+        Ok(mut writer) => {
+            for coefficient in coefficients {
+                if let Err(e2) = writer.serialize(coefficient) {
+                    let message = format!("inspect::write_coefficients Failed to serialize Coefficient {}. Serde: {}", coefficient.name, e2);
+                    return Err(message)
+                }
+            }
+            eprintln!("inspect::write_coefficients Completed writing the coefficients to {}", filename);
+            Ok(())
+        },
+        Err(e1) => {
+            let message = format!("inspect::write_coefficients Failed to create writer to {}. Serde: {}", filename, e1);
+            Err(message)
+        },
+    }
+}
+
+// Prints the weights of the logistic --model, or of one trained on the training data, by name. The standard errors
+// need the training data, and the --l2 the model was trained with.
+pub fn run(config: &Config) -> Result<(), String> {
+    let training_passengers = if config.get_training_data_filename().is_empty() { None } else { Some(config.get_training_passengers()?) };
+    let model = match (config.get_model_filename(), &training_passengers) {
+        (Some(filename), _) => Model::read(filename)?,
+        (None, Some(training_passengers)) => fit(config, training_passengers.clone())?,
+        (None, None) => return Err("inspect::run needs a --model to inspect or the --training-data to train one".to_string()),
+    };
+    let coefficients = coefficients(model.get_weights()?, training_passengers.as_deref(), *config.get_l2())?;
+    println!("inspect::run model {} with threshold {}, odds_ratio = exp(-weight) as P(Survived) = 1 / (1 + exp(weighted sum))", model.id()?, model.get_threshold());
+    print_coefficients(&coefficients);
+    if let Some(filename) = config.get_coefficients_filename() {
+        write_coefficients(filename, &coefficients)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use super::*;
+use crate::test_support::passengers;
+use crate::TrainingPassenger;
+
+fn records() -> Vec<TrainingPassenger> {
+    passengers(&[
+        "1,1,1,A,female,20,1,0,T,10,,S",
+        "2,1,3,B,female,35,0,0,T,20,,C",
+        "3,0,3,C,male,30,1,0,T,10,,S",
+        "4,0,1,D,male,50,0,0,T,20,,C",
+        "5,1,2,E,male,5,0,0,T,20,,C",
+    ])
+}
+
+#[test]
+fn when_symmetric_matrix_then_eigenvectors_rebuild_it() {
+    let matrix = vec![vec![4_f64, 1_f64, 2_f64], vec![1_f64, 3_f64, 0_f64], vec![2_f64, 0_f64, 5_f64]];
+
+    let (values, vectors) = symmetric_eigen(&matrix);
+
+    for row in 0..3 {
+        for column in 0..3 {
+            let rebuilt = (0..3).map(|k| vectors[row][k] * values[k] * vectors[column][k]).sum::<f64>();
+            assert_abs_diff_eq!(rebuilt, matrix[row][column], epsilon = 1e-12);
+        }
+    }
+    let (values, _vectors) = symmetric_eigen(&[vec![2_f64, 1_f64], vec![1_f64, 2_f64]]);
+    assert_abs_diff_eq!(values[0].min(values[1]), 1_f64, epsilon = 1e-12);
+    assert_abs_diff_eq!(values[0].max(values[1]), 3_f64, epsilon = 1e-12);
+}
+
+#[test]
+fn when_weights_not_identified_then_no_standard_error() {
+    let diagonal = standard_errors(&[vec![4_f64, 0_f64], vec![0_f64, 0_f64]]);
+    // A bias and two categories that every record has one of
+    let aliased = standard_errors(&[vec![2_f64, 1_f64, 1_f64], vec![1_f64, 1_f64, 0_f64], vec![1_f64, 0_f64, 1_f64]]);
+
+    assert_abs_diff_eq!(diagonal[0].unwrap(), 0.5_f64, epsilon = 1e-12);
+    assert!(diagonal[1].is_none());
+    assert!(aliased.iter().all(|error| error.is_none()));
+}
+
+#[test]
+fn when_weights_zero_then_wald_error_of_age_from_its_spread() {
+    let weights = PassengerWeights::from_schema(&[Feature::Age], 0_f64, || 0_f64);
+    let ages = [20_f64, 35_f64, 30_f64, 50_f64, 5_f64];
+    let (count, sum, squares) = (5_f64, ages.iter().sum::<f64>(), ages.iter().map(|age| age * age).sum::<f64>());
+
+    let coefficients = coefficients(&weights, Some(&records()), 0_f64).unwrap();
+
+    let names: Vec<&str> = coefficients.iter().map(|coefficient| coefficient.name.as_str()).collect();
+    assert_eq!(names, vec!["bias", "age=missing", "age"]);
+    assert!(coefficients[1].standard_error.is_none());
+    assert_abs_diff_eq!(coefficients[2].standard_error.unwrap(), (count / (0.25_f64 * (count * squares - sum * sum))).sqrt(), epsilon = 1e-9);
+    assert_abs_diff_eq!(coefficients[2].p_value.unwrap(), 1_f64, epsilon = 1e-12);
+}
+
+#[test]
+fn when_l2_then_every_weight_named_with_odds_ratio_and_standard_error() {
+    let mut weights = PassengerWeights::from_schema(&[Feature::Sex, Feature::PassengerClass, Feature::CabinId], 0_f64, || 0.5_f64);
+    weights.scale(&-1_f64);
+
+    let unpenalized = coefficients(&weights, Some(&records()), 0_f64).unwrap();
+    let penalized = coefficients(&weights, Some(&records()), 0.5_f64).unwrap();
+    let without_records = coefficients::<TrainingPassenger>(&weights, None, 0.5_f64).unwrap();
+
+    assert_eq!(penalized[2].name, "sex=female");
+    assert_eq!(penalized[7].name, "passenger_class=third");
+    assert_eq!(penalized[9].name, "cabin_id=present");
+    assert_abs_diff_eq!(penalized[2].odds_ratio, 0.5_f64.exp(), epsilon = 1e-12);
+    assert!(penalized.iter().all(|coefficient| coefficient.standard_error.is_some() && coefficient.p_value.is_some()));
+    assert!(unpenalized[2].standard_error.is_none());
+    assert!(without_records.iter().all(|coefficient| coefficient.standard_error.is_none()));
+}
@@ -1,44 +1,446 @@
-use csv::{Reader, Writer};
+// Trait methods in this crate take `self: &Self` by convention.
+#![allow(clippy::needless_arbitrary_self_type)]
+
+use csv::Writer;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::ops::{Add, Mul};
-use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 
-#[macro_use]
+#[cfg_attr(test, macro_use)]
 extern crate approx;
 
-mod classification;
+pub mod analysis;
+pub mod classification;
+pub mod config_file;
+pub mod data;
+pub mod describe;
+pub mod inspect;
+pub mod ensemble;
+pub mod explain;
+pub mod model;
+pub mod predict;
+pub mod report;
+pub mod schema;
+pub mod search;
+pub mod submission;
+pub mod validation;
 
 #[cfg(test)]
 mod tests;
 
+#[cfg(test)]
+mod test_support;
+
+pub use classification::calibration::{CalibrationMethod, Calibrator};
+pub use classification::metrics::ThresholdMetric;
+pub use classification::{Classifier, LogisticRegression, Optimizer, SolverSettings};
+pub use config_file::ConfigFile;
+pub use data::DataFormat;
+pub use model::{Estimator, Learner, Model, Registry, TrainedLearner};
+pub use classification::tree::{Criterion, DecisionTree, TreeSettings};
+pub use classification::forest::{ForestSettings, RandomForest};
+pub use classification::boosting::{BoostingSettings, GradientBoosting};
+pub use classification::bayes::{BayesSettings, NaiveBayes};
+pub use classification::knn::{KnnSettings, NearestNeighbours};
+pub use classification::svm::{LinearSvm, SvmSettings};
+pub use classification::mlp::{Mlp, MlpSettings};
+pub use classification::multiclass::{OneVsRest, SoftmaxRegression};
+pub use ensemble::{Ensemble, EnsembleSettings};
+pub use schema::Schema;
+
+pub const DEFAULT_SEED: u64 = 42;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    Train,
+    Search,
+    Report,
+    Predict,
+    Describe,
+    Analyse,
+    Inspect,
+    Explain,
+}
+
+impl FromStr for Command {
+    type Err = String;
+    
+    fn from_str(name: &str) -> Result<Command, String> {
+        match name {
+            "train" => Ok(Command::Train),
+            "search" => Ok(Command::Search),
+            "report" => Ok(Command::Report),
+            "predict" => Ok(Command::Predict),
+            "describe" => Ok(Command::Describe),
+            "analyse" => Ok(Command::Analyse),
+            "inspect" => Ok(Command::Inspect),
+            "explain" => Ok(Command::Explain),
+            _ => Err(format!("Command::from_str unknown command {}", name)),
+        }
+    }
+}
+
 pub struct Config {
+    command: Command,
     learning_rate: f64,
     tolerance: f64,
     training_data_filename: String,
     test_data_filename: String,
     output_filename: String,
+    threshold: f64,
+    threshold_metric: Option<ThresholdMetric>,
+    validation_fraction: f64,
+    write_probability: bool,
+    calibration: Option<CalibrationMethod>,
+    calibration_bins: usize,
+    reliability_filename: Option<String>,
+    max_iterations: Option<u64>,
+    l2: f64,
+    optimizer: Optimizer,
+    features: Vec<Feature>,
+    folds: usize,
+    seed: u64,
+    search_space: search::SearchSpace,
+    search_results_filename: String,
+    best_config_filename: String,
+    resolved_config_filename: Option<String>,
+    model_filename: Option<String>,
+    initialization: Initialization,
+    learner: Learner,
+    tree_settings: TreeSettings,
+    forest_settings: ForestSettings,
+    boosting_settings: BoostingSettings,
+    bayes_settings: BayesSettings,
+    knn_settings: KnnSettings,
+    svm_settings: SvmSettings,
+    mlp_settings: MlpSettings,
+    ensemble_settings: EnsembleSettings,
+    schema: Schema,
+    lenient: bool,
+    validation_report_filename: Option<String>,
+    progress_every: usize,
+    input_format: Option<DataFormat>,
+    output_format: Option<DataFormat>,
+    extended_output: bool,
+    top_features: usize,
+    kaggle: bool,
+    analysis_filename: Option<String>,
+    coefficients_filename: Option<String>,
+    write_contributions: bool,
+    explain_id: Option<u64>,
+}
+
+fn parse_value<T: FromStr>(value: Option<&String>, error: &'static str) -> Result<T, &'static str> {
+    match value.map(|value| value.parse::<T>()) {
+        Some(Ok(value)) => Ok(value),
+        _ => Err(error),
+    }
+}
+
+fn parse_values<T: FromStr>(value: Option<&String>, error: &'static str) -> Result<Vec<T>, &'static str> {
+    match value {
+        Some(value) => {
+            let mut values = Vec::new();
+            for item in value.split(',') {
+                values.push(parse_value(Some(&item.trim().to_string()), error)?);
+            }
+            Ok(values)
+        },
+        None => Err(error),
+    }
 }
 
 impl Config {
-    pub fn new(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() < 6 {
-            return Err("not enough arguments");
+    pub fn new(args: &[String]) -> Result<Config, String> {
+        // An optional command may come before the positional arguments.
+        let (command, args) = match args.get(1).map(|command| command.parse::<Command>()) {
+            Some(Ok(command)) => (Some(command), &args[1..]),
+            _ => (None, args),
+        };
+        // The positional arguments may be left out when a configuration file given by --config provides them.
+        let (positionals, options) = match args.get(1) {
+            Some(first) if first.starts_with("--") => (&args[1..1], &args[1..]),
+            _ if args.len() < 6 => return Err("not enough arguments".to_string()),
+            _ => (&args[1..6], &args[6..]),
+        };
+        
+        // Values from the configuration file come first, then the options override them.
+        let mut config_file = match options.iter().position(|option| option == "--config") {
+            Some(index) => ConfigFile::read(&parse_value::<String>(options.get(index + 1), "missing config filename")?)?,
+            None => ConfigFile::default(),
+        };
+        if command.is_some() {
+            config_file.command = command;
+        }
+        // The positional arguments fill in what the file leaves out, so best_config.json keeps the winning learning rate.
+        if !positionals.is_empty() {
+            config_file.learning_rate.get_or_insert(parse_value(positionals.first(), "unable to parse learning rate")?);
+            config_file.tolerance.get_or_insert(parse_value(positionals.get(1), "unable to parse tolerance")?);
+            config_file.training_data_filename.get_or_insert_with(|| positionals[2].clone());
+            config_file.test_data_filename.get_or_insert_with(|| positionals[3].clone());
+            config_file.output_filename.get_or_insert_with(|| positionals[4].clone());
         }
-        let learning_rate_string = args[1].clone();
-        let tolerance_string = args[2].clone();
-        let training_data_filename = args[3].clone();
-        let test_data_filename = args[4].clone();
-        let output_filename = args[5].clone();
+        // The data files given as options are needed before the file can become a Config.
+        for pair in options.windows(2) {
+            match pair[0].as_str() {
+                "--training-data" => config_file.training_data_filename = Some(pair[1].clone()),
+                "--test-data" => config_file.test_data_filename = Some(pair[1].clone()),
+                "--output" => config_file.output_filename = Some(pair[1].clone()),
+                _ => (),
+            }
+        }
+        if matches!(config_file.command, Some(Command::Predict) | Some(Command::Inspect) | Some(Command::Explain)) {
+            // A prediction, inspection or explanation of a saved model trains nothing, and its files may be given by options.
+            config_file.learning_rate.get_or_insert(0_f64);
+            config_file.tolerance.get_or_insert(0_f64);
+            config_file.training_data_filename.get_or_insert_with(String::new);
+            config_file.test_data_filename.get_or_insert_with(String::new);
+            config_file.output_filename.get_or_insert_with(String::new);
+        }
+        let mut config = config_file.into_config()?;
         
-        match learning_rate_string.parse::<f64>() {
-            Ok(learning_rate) => {
-                match tolerance_string.parse::<f64>() {
-                    Ok(tolerance) => Ok(Config {learning_rate, tolerance, training_data_filename, test_data_filename, output_filename}),
-                    Err(_) => Err("unable to parse tolerance"),
-                }
-            },
-            Err(_) => Err("unable to parse learning rate"),
+        // Any arguments after the five positional ones are options.
+        let mut options = options.iter();
+        let mut search_feature_sets = Vec::new();
+        while let Some(option) = options.next() {
+            match option.as_str() {
+                "--probability" => config.write_probability = true,
+                "--threshold" => {
+                    match options.next().map(|value| value.parse::<f64>()) {
+                        Some(Ok(threshold)) if (0_f64..=1_f64).contains(&threshold) => config.threshold = threshold,
+                        _ => return Err("unable to parse threshold".to_string()),
+                    }
+                },
+                "--select-threshold" => config.threshold_metric = Some(parse_value(options.next(), "unable to parse threshold metric")?),
+                "--validation-fraction" => {
+                    match options.next().map(|value| value.parse::<f64>()) {
+                        Some(Ok(fraction)) if fraction > 0_f64 && fraction < 1_f64 => config.validation_fraction = fraction,
+                        _ => return Err("unable to parse validation fraction".to_string()),
+                    }
+                },
+                "--calibration" => config.calibration = Some(parse_value(options.next(), "unable to parse calibration method")?),
+                "--calibration-bins" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(bins)) if bins > 0 => config.calibration_bins = bins,
+                        _ => return Err("unable to parse calibration bins".to_string()),
+                    }
+                },
+                "--reliability" => config.reliability_filename = Some(parse_value(options.next(), "missing reliability filename")?),
+                "--max-iterations" => config.max_iterations = Some(parse_value(options.next(), "unable to parse max iterations")?),
+                "--l2" => config.l2 = parse_value(options.next(), "unable to parse l2")?,
+                "--optimizer" => config.optimizer = parse_value(options.next(), "unable to parse optimizer")?,
+                "--features" => {
+                    match options.next().map(|value| Feature::parse_list(value)) {
+                        Some(Ok(features)) => config.features = features,
+                        _ => return Err("unable to parse features".to_string()),
+                    }
+                },
+                "--folds" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(folds)) if folds >= 2 => config.folds = folds,
+                        _ => return Err("unable to parse folds".to_string()),
+                    }
+                },
+                "--seed" => config.seed = parse_value(options.next(), "unable to parse seed")?,
+                "--search-mode" => config.search_space.mode = parse_value(options.next(), "unable to parse search mode")?,
+                "--search-samples" => config.search_space.samples = parse_value(options.next(), "unable to parse search samples")?,
+                "--search-learning-rates" => config.search_space.learning_rates = parse_values(options.next(), "unable to parse search learning rates")?,
+                "--search-l2" => config.search_space.l2 = parse_values(options.next(), "unable to parse search l2")?,
+                "--search-optimizers" => config.search_space.optimizers = parse_values(options.next(), "unable to parse search optimizers")?,
+                "--search-thresholds" => config.search_space.thresholds = parse_values(options.next(), "unable to parse search thresholds")?,
+                "--search-feature-set" => {
+                    match options.next().map(|value| Feature::parse_list(value)) {
+                        Some(Ok(features)) => search_feature_sets.push(features),
+                        _ => return Err("unable to parse search feature set".to_string()),
+                    }
+                },
+                "--search-results" => config.search_results_filename = parse_value(options.next(), "missing search results filename")?,
+                "--best-config" => config.best_config_filename = parse_value(options.next(), "missing best config filename")?,
+                "--config" => {
+                    options.next();
+                },
+                "--learner" => config.learner = parse_value(options.next(), "unable to parse learner")?,
+                "--criterion" => config.tree_settings.criterion = parse_value(options.next(), "unable to parse criterion")?,
+                "--max-depth" => config.tree_settings.max_depth = Some(parse_value(options.next(), "unable to parse max depth")?),
+                "--min-samples-leaf" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(min_samples_leaf)) if min_samples_leaf > 0 => config.tree_settings.min_samples_leaf = min_samples_leaf,
+                        _ => return Err("unable to parse min samples leaf".to_string()),
+                    }
+                },
+                "--ccp-alpha" => {
+                    match options.next().map(|value| value.parse::<f64>()) {
+                        Some(Ok(ccp_alpha)) if ccp_alpha >= 0_f64 => config.tree_settings.ccp_alpha = ccp_alpha,
+                        _ => return Err("unable to parse ccp alpha".to_string()),
+                    }
+                },
+                "--max-features" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(max_features)) if max_features > 0 => config.tree_settings.max_features = Some(max_features),
+                        _ => return Err("unable to parse max features".to_string()),
+                    }
+                },
+                "--trees" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(trees)) if trees > 0 => config.forest_settings.trees = trees,
+                        _ => return Err("unable to parse trees".to_string()),
+                    }
+                },
+                "--threads" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(threads)) if threads > 0 => config.forest_settings.threads = Some(threads),
+                        _ => return Err("unable to parse threads".to_string()),
+                    }
+                },
+                "--rounds" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(rounds)) if rounds > 0 => config.boosting_settings.rounds = rounds,
+                        _ => return Err("unable to parse rounds".to_string()),
+                    }
+                },
+                "--shrinkage" => {
+                    match options.next().map(|value| value.parse::<f64>()) {
+                        Some(Ok(shrinkage)) if shrinkage > 0_f64 => config.boosting_settings.shrinkage = shrinkage,
+                        _ => return Err("unable to parse shrinkage".to_string()),
+                    }
+                },
+                "--boosting-max-depth" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(max_depth)) if max_depth > 0 => config.boosting_settings.max_depth = max_depth,
+                        _ => return Err("unable to parse boosting max depth".to_string()),
+                    }
+                },
+                "--subsample" => {
+                    match options.next().map(|value| value.parse::<f64>()) {
+                        Some(Ok(subsample)) if subsample > 0_f64 && subsample <= 1_f64 => config.boosting_settings.subsample = subsample,
+                        _ => return Err("unable to parse subsample".to_string()),
+                    }
+                },
+                "--early-stopping-rounds" => {
+                    // 0 turns early stopping off
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(0)) => config.boosting_settings.early_stopping_rounds = None,
+                        Some(Ok(rounds)) => config.boosting_settings.early_stopping_rounds = Some(rounds),
+                        _ => return Err("unable to parse early stopping rounds".to_string()),
+                    }
+                },
+                "--laplace" => {
+                    match options.next().map(|value| value.parse::<f64>()) {
+                        Some(Ok(alpha)) if alpha > 0_f64 => config.bayes_settings.alpha = alpha,
+                        _ => return Err("unable to parse laplace smoothing".to_string()),
+                    }
+                },
+                "--neighbours" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(neighbours)) if neighbours > 0 => config.knn_settings.neighbours = neighbours,
+                        _ => return Err("unable to parse neighbours".to_string()),
+                    }
+                },
+                "--weighting" => config.knn_settings.weighting = parse_value(options.next(), "unable to parse weighting")?,
+                "--neighbour-search" => config.knn_settings.search = parse_value(options.next(), "unable to parse neighbour search")?,
+                "--leaf-size" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(leaf_size)) if leaf_size > 0 => config.knn_settings.leaf_size = leaf_size,
+                        _ => return Err("unable to parse leaf size".to_string()),
+                    }
+                },
+                "--svm-loss" => config.svm_settings.loss = parse_value(options.next(), "unable to parse svm loss")?,
+                "--svm-lambda" => {
+                    match options.next().map(|value| value.parse::<f64>()) {
+                        Some(Ok(lambda)) if lambda > 0_f64 => config.svm_settings.lambda = lambda,
+                        _ => return Err("unable to parse svm lambda".to_string()),
+                    }
+                },
+                "--epochs" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(epochs)) if epochs > 0 => config.svm_settings.epochs = epochs,
+                        _ => return Err("unable to parse epochs".to_string()),
+                    }
+                },
+                "--hidden" => {
+                    match parse_values::<usize>(options.next(), "unable to parse hidden layers") {
+                        Ok(hidden) if !hidden.is_empty() && hidden.len() <= 2 && !hidden.contains(&0) => config.mlp_settings.hidden = hidden,
+                        _ => return Err("unable to parse hidden layers".to_string()),
+                    }
+                },
+                "--activation" => config.mlp_settings.activation = parse_value(options.next(), "unable to parse activation")?,
+                "--dropout" => {
+                    match options.next().map(|value| value.parse::<f64>()) {
+                        Some(Ok(dropout)) if (0_f64..1_f64).contains(&dropout) => config.mlp_settings.dropout = dropout,
+                        _ => return Err("unable to parse dropout".to_string()),
+                    }
+                },
+                "--mlp-optimizer" => config.mlp_settings.optimizer = parse_value(options.next(), "unable to parse mlp optimizer")?,
+                "--mlp-learning-rate" => {
+                    match options.next().map(|value| value.parse::<f64>()) {
+                        Some(Ok(learning_rate)) if learning_rate > 0_f64 => config.mlp_settings.learning_rate = learning_rate,
+                        _ => return Err("unable to parse mlp learning rate".to_string()),
+                    }
+                },
+                "--mlp-epochs" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(epochs)) if epochs > 0 => config.mlp_settings.epochs = epochs,
+                        _ => return Err("unable to parse mlp epochs".to_string()),
+                    }
+                },
+                "--batch-size" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(batch_size)) if batch_size > 0 => config.mlp_settings.batch_size = batch_size,
+                        _ => return Err("unable to parse batch size".to_string()),
+                    }
+                },
+                "--ensemble" => config.ensemble_settings.method = parse_value(options.next(), "unable to parse ensemble method")?,
+                "--ensemble-learners" => {
+                    match parse_values::<Learner>(options.next(), "unable to parse ensemble learners") {
+                        Ok(learners) if !learners.is_empty() && !learners.contains(&Learner::Ensemble) => config.ensemble_settings.learners = learners,
+                        _ => return Err("unable to parse ensemble learners".to_string()),
+                    }
+                },
+                "--stacking-folds" => {
+                    match options.next().map(|value| value.parse::<usize>()) {
+                        Some(Ok(folds)) if folds > 1 => config.ensemble_settings.folds = folds,
+                        _ => return Err("unable to parse stacking folds".to_string()),
+                    }
+                },
+                "--column" => {
+                    let assignment: String = parse_value(options.next(), "missing column assignment")?;
+                    config.schema.set_header(&assignment)?;
+                },
+                "--lenient" => config.lenient = true,
+                "--validation-report" => config.validation_report_filename = Some(parse_value(options.next(), "missing validation report filename")?),
+                "--training-data" => config.training_data_filename = parse_value(options.next(), "missing training data filename")?,
+                "--test-data" => config.test_data_filename = parse_value(options.next(), "missing test data filename")?,
+                "--output" => config.output_filename = parse_value(options.next(), "missing output filename")?,
+                "--input-format" => config.input_format = Some(parse_value(options.next(), "unable to parse input format")?),
+                "--output-format" => config.output_format = Some(parse_value(options.next(), "unable to parse output format")?),
+                "--extended" => config.extended_output = true,
+                "--top-features" => config.top_features = parse_value(options.next(), "unable to parse top features")?,
+                "--kaggle" => config.kaggle = true,
+                "--analysis" => config.analysis_filename = Some(parse_value(options.next(), "missing analysis filename")?),
+                "--contributions" => config.write_contributions = true,
+                "--id" => config.explain_id = Some(parse_value(options.next(), "unable to parse passenger id")?),
+                "--coefficients" => config.coefficients_filename = Some(parse_value(options.next(), "missing coefficients filename")?),
+                "--progress-every" => config.progress_every = parse_value(options.next(), "unable to parse progress interval")?,
+                "--init" => config.initialization = parse_value(options.next(), "unable to parse initialization")?,
+                "--model" => config.model_filename = Some(parse_value(options.next(), "missing model filename")?),
+                "--resolved-config" => config.resolved_config_filename = Some(parse_value(options.next(), "missing resolved config filename")?),
+                _ => return Err("unknown option".to_string()),
+            }
         }
+        if !search_feature_sets.is_empty() {
+            config.search_space.feature_sets = search_feature_sets;
+        }
+        
+        Ok(config)
+    }
+    
+    pub fn get_command(&self) -> &Command {
+        &self.command
     }
     
     pub fn get_learning_rate(&self) -> &f64 {
@@ -60,72 +462,247 @@ impl Config {
     pub fn get_output_filename(&self) -> &String {
         &self.output_filename
     }
+    
+    pub fn get_threshold(&self) -> &f64 {
+        &self.threshold
+    }
+    
+    pub fn get_threshold_metric(&self) -> &Option<ThresholdMetric> {
+        &self.threshold_metric
+    }
+    
+    pub fn get_validation_fraction(&self) -> &f64 {
+        &self.validation_fraction
+    }
+    
+    pub fn get_write_probability(&self) -> &bool {
+        &self.write_probability
+    }
+    
+    pub fn get_calibration(&self) -> &Option<CalibrationMethod> {
+        &self.calibration
+    }
+    
+    pub fn get_calibration_bins(&self) -> &usize {
+        &self.calibration_bins
+    }
+    
+    pub fn get_reliability_filename(&self) -> &Option<String> {
+        &self.reliability_filename
+    }
+    
+    pub fn get_max_iterations(&self) -> &Option<u64> {
+        &self.max_iterations
+    }
+    
+    pub fn get_l2(&self) -> &f64 {
+        &self.l2
+    }
+    
+    pub fn get_optimizer(&self) -> &Optimizer {
+        &self.optimizer
+    }
+    
+    pub fn get_features(&self) -> &Vec<Feature> {
+        &self.features
+    }
+    
+    pub fn get_folds(&self) -> &usize {
+        &self.folds
+    }
+    
+    pub fn get_seed(&self) -> &u64 {
+        &self.seed
+    }
+    
+    pub fn get_search_space(&self) -> &search::SearchSpace {
+        &self.search_space
+    }
+    
+    pub fn get_search_results_filename(&self) -> &String {
+        &self.search_results_filename
+    }
+    
+    pub fn get_best_config_filename(&self) -> &String {
+        &self.best_config_filename
+    }
+    
+    // Unless --resolved-config says otherwise, the resolved configuration is written next to the main output of the command,
+    // or to resolved.config.toml when that goes to standard output or there is none.
+    pub fn get_resolved_config_filename(&self) -> String {
+        match &self.resolved_config_filename {
+            Some(filename) => filename.clone(),
+            None => {
+                let output_filename = match self.command {
+                    Command::Train | Command::Report | Command::Predict | Command::Describe | Command::Analyse | Command::Inspect | Command::Explain => &self.output_filename,
+                    Command::Search => &self.search_results_filename,
+                };
+                if output_filename.is_empty() || output_filename == data::STANDARD_STREAM {
+                    return "resolved.config.toml".to_string()
+                }
+                Path::new(output_filename).with_extension("config.toml").to_string_lossy().into_owned()
+            },
+        }
+    }
+    
+    pub fn get_model_filename(&self) -> &Option<String> {
+        &self.model_filename
+    }
+    
+    pub fn get_learner(&self) -> &Learner {
+        &self.learner
+    }
+    
+    pub fn get_tree_settings(&self) -> &TreeSettings {
+        &self.tree_settings
+    }
+    
+    pub fn get_forest_settings(&self) -> &ForestSettings {
+        &self.forest_settings
+    }
+    
+    pub fn get_boosting_settings(&self) -> &BoostingSettings {
+        &self.boosting_settings
+    }
+    
+    pub fn get_bayes_settings(&self) -> &BayesSettings {
+        &self.bayes_settings
+    }
+    
+    pub fn get_knn_settings(&self) -> &KnnSettings {
+        &self.knn_settings
+    }
+    
+    pub fn get_svm_settings(&self) -> &SvmSettings {
+        &self.svm_settings
+    }
+    
+    pub fn get_mlp_settings(&self) -> &MlpSettings {
+        &self.mlp_settings
+    }
+    
+    pub fn get_ensemble_settings(&self) -> &EnsembleSettings {
+        &self.ensemble_settings
+    }
+    
+    pub fn get_schema(&self) -> &Schema {
+        &self.schema
+    }
+    
+    pub fn get_lenient(&self) -> &bool {
+        &self.lenient
+    }
+    
+    pub fn get_validation_report_filename(&self) -> &Option<String> {
+        &self.validation_report_filename
+    }
+    
+    pub fn get_progress_every(&self) -> &usize {
+        &self.progress_every
+    }
+    
+    pub fn get_input_format(&self) -> &Option<DataFormat> {
+        &self.input_format
+    }
+    
+    pub fn get_output_format(&self) -> &Option<DataFormat> {
+        &self.output_format
+    }
+    
+    pub fn get_extended_output(&self) -> &bool {
+        &self.extended_output
+    }
+    
+    pub fn get_top_features(&self) -> &usize {
+        &self.top_features
+    }
+    
+    pub fn get_kaggle(&self) -> &bool {
+        &self.kaggle
+    }
+    
+    pub fn get_analysis_filename(&self) -> &Option<String> {
+        &self.analysis_filename
+    }
+    
+    pub fn get_coefficients_filename(&self) -> &Option<String> {
+        &self.coefficients_filename
+    }
+    
+    pub fn get_write_contributions(&self) -> &bool {
+        &self.write_contributions
+    }
+    
+    pub fn get_explain_id(&self) -> &Option<u64> {
+        &self.explain_id
+    }
+    
+    pub fn get_initialization(&self) -> &Initialization {
+        &self.initialization
+    }
+    
+    // Fresh weights for the given features, started as --init says.
+    pub fn new_weights(&self, features: &[Feature]) -> Result<PassengerWeights, String> {
+        PassengerWeights::initialized(features, &self.initialization, self.seed)
+    }
+    
+    pub fn get_solver_settings(&self) -> SolverSettings {
+        let mut settings = SolverSettings::new(self.learning_rate, self.tolerance);
+        settings.max_iterations = self.max_iterations;
+        settings.l2 = self.l2;
+        settings.optimizer = self.optimizer;
+        settings.seed = self.seed;
+        settings
+    }
+    
+    // Held-out passengers are needed to choose a threshold, fit a calibrator or measure reliability.
+    pub fn needs_validation(&self) -> bool {
+        self.threshold_metric.is_some() || self.calibration.is_some() || self.reliability_filename.is_some()
+    }
+	
+	// Checks and reads a data file. The problems of the training file start the validation report and those of the test
+	// file are appended to it.
+	fn read_passengers<T: serde::de::DeserializeOwned>(&self, filename: &str, required: &[schema::Column], append: bool) -> Result<Vec<T>, String> {
+		let (passengers, report) = validation::read_rows(filename, &self.input_format, &self.schema, required, self.lenient)?;
+		if let Some(report_filename) = &self.validation_report_filename {
+			report.write(report_filename, append)?;
+		}
+		report.ensure_valid(filename)?;
+		Ok(passengers)
+	}
 	
 	pub fn get_training_passengers(&self) -> Result<Vec<TrainingPassenger>, String> {
 		//Read training_data into vector of training_passengers, which will be reused many times.
-		let mut training_passengers = Vec::new();
-		match Reader::from_path(self.get_training_data_filename()) {
-			Ok(mut training_data) => {
-				for result in training_data.deserialize() {
-					match result {
-						Ok(record) => {
-							let training_passenger: TrainingPassenger = record;
-							training_passengers.push(training_passenger);
-						},
-						Err(_) => return Err("Config::get_training_passengers Failed to deserialize TrainingPassenger".to_string()),
-					}
-				}
-				println!("Config::get_training_passengers training_passengers: Vec<TrainingPassenger> has been instantiated with length {}", training_passengers.len());
-				Ok(training_passengers)
-			},
-			Err(_) => {
-				let message = format!("Config::get_training_passengers Failed to read from {}", &self.get_training_data_filename());
-				Err(message)
-			},
-		}
+		let training_passengers: Vec<TrainingPassenger> = self.read_passengers(self.get_training_data_filename(), &[schema::Column::PassengerId, schema::Column::Survived], false)?;
+		eprintln!("Config::get_training_passengers training_passengers: Vec<TrainingPassenger> has been instantiated with length {}", training_passengers.len());
+		Ok(training_passengers)
 	}
 	
 	pub fn get_test_passengers(&self) -> Result<Vec<Passenger>, String> {
 		//Read test_data into vector of passengers, which will be tested once each.
-		let mut test_passengers = Vec::new();
-		match Reader::from_path(self.get_test_data_filename()) {
-			Ok(mut test_data) => {
-				for result in test_data.deserialize() {
-					match result {
-						Ok(record) => {
-							let test_passenger: Passenger = record;
-							test_passengers.push(test_passenger);
-						},
-						Err(_) => return Err("Config::get_test_passengers Failed to deserialize TestPassenger".to_string()),
-					}
-				}
-				println!("Config::get_test_passengers test_passengers: Vec<TestPassenger> has been instantiated with length {}", test_passengers.len());
-				Ok(test_passengers)
-			},
-			Err(_) => {
-				let message = format!("Config::get_test_passengers Failed to read from {}", &self.get_test_data_filename());
-				Err(message)
-			},
-		}
+		let test_passengers: Vec<Passenger> = self.read_passengers(self.get_test_data_filename(), &[schema::Column::PassengerId], true)?;
+		eprintln!("Config::get_test_passengers test_passengers: Vec<TestPassenger> has been instantiated with length {}", test_passengers.len());
+		Ok(test_passengers)
 	}
 	
-	pub fn write_output(&self, passenger_weights: &PassengerWeights, test_passengers: &Vec<Passenger>) -> Result<(), String> {
-		match Writer::from_path(self.get_output_filename()) {
+	pub fn write_reliability(&self, bins: &[classification::metrics::ReliabilityBin]) -> Result<(), String> {
+		let filename = match self.get_reliability_filename() {
+			Some(filename) => filename,
+			None => return Ok(()),
+		};
+		match Writer::from_path(filename) {
 			Ok(mut writer) => {
-				for test_passenger in test_passengers {
-					let outcome = classification::predict(passenger_weights, test_passenger)?;
-					let tested_passenger = TestedPassenger::new(outcome);
-					
-					if let Err(e2) = writer.serialize(tested_passenger) {
-						let message = format!("Config::write_output Failed to serialize TestedPassenger {}. Serde: {}", test_passenger.get_passenger_id(), e2);
+				for bin in bins {
+					if let Err(e2) = writer.serialize(bin) {
+						let message = format!("Config::write_reliability Failed to serialize ReliabilityBin. Serde: {}", e2);
 						return Err(message)
 					}
 				}
-				println!("Config::write_output Completed writing test results to {}", &self.get_output_filename());
+				eprintln!("Config::write_reliability Completed writing reliability diagram data to {}", filename);
 				Ok(())
 			},
 			Err(e1) => {
-				let message = format!("Config::write_output Failed to create writer to {}. Serde: {}", &self.get_output_filename(), e1);
+				let message = format!("Config::write_reliability Failed to create writer to {}. Serde: {}", filename, e1);
 				Err(message)
 			},
 		}
@@ -133,28 +710,97 @@ impl Config {
 }
 
 pub fn run(config: &mut Config) -> Result<(), String> {
-    
+	match config.get_command() {
+		Command::Train => train(config)?,
+		Command::Search => search::run(config)?,
+		Command::Report => report::run(config)?,
+		Command::Predict => predict::run(config)?,
+		Command::Describe => describe::run(config)?,
+		Command::Analyse => analysis::run(config)?,
+		Command::Inspect => inspect::run(config)?,
+		Command::Explain => explain::run(config)?,
+	}
+	// Only training and searching have settings worth reproducing. Written after the run so a threshold chosen on the
+	// validation passengers is recorded too.
+	match config.get_command() {
+		Command::Train | Command::Search => ConfigFile::from_config(config).write(&config.get_resolved_config_filename()),
+		_ => Ok(()),
+	}
+}
+
+fn train(config: &mut Config) -> Result<(), String> {
 	let training_passengers = config.get_training_passengers()?;
-	let test_passengers = config.get_test_passengers()?;
 	
-	// Initialize weights
-	let mut passenger_weights = PassengerWeights::new();
+	eprintln!("run Training with seed {}", config.get_seed());
+	let model = fit(config, training_passengers)?;
+	// Record a threshold chosen on the validation passengers in the resolved configuration.
+	config.threshold = *model.get_threshold();
+	
+	if let Some(filename) = config.get_model_filename() {
+		model.write(filename)?;
+	}
+	// The test passengers are streamed, so a test file of any length is scored in bounded memory.
+	predict::stream(config, &model, true)?;
+	Ok(())
+}
+
+// Every random choice made while fitting is drawn from config's seed, so the same seed gives the same model.
+pub fn fit(config: &Config, training_passengers: Vec<TrainingPassenger>) -> Result<Model, String> {
+	let mut threshold = *config.get_threshold();
+	let mut calibrator = None;
 	
-	classification::solve(&training_passengers, &mut passenger_weights, *config.get_learning_rate(), config.get_tolerance())?;
+	let learner = if config.needs_validation() {
+		// Hold back part of the training data so the threshold and calibration are chosen on passengers the learner has not seen.
+		let mut rng = classification::random::Rng::new(*config.get_seed());
+		let (training_passengers, validation_passengers) = classification::split_holdout_shuffled(training_passengers, config.get_validation_fraction(), &mut rng)?;
+		let classifier = fit_classifier(config, &training_passengers)?;
+		
+		let mut scores = classifier.score_batch(&validation_passengers)?;
+		let bins = *config.get_calibration_bins();
+		
+		if let Some(method) = config.get_calibration() {
+			// The calibrator is fitted on half of the validation passengers, so that the calibration error, the reliability
+			// bins and the threshold are measured on the other half, which it has not seen.
+			let (evaluation_scores, calibration_scores) = classification::split_holdout(scores, &0.5_f64)?;
+			let fitted = Calibrator::fit(method, &calibration_scores)?;
+			eprintln!("run Fitted {} calibration on {} validation passengers", method, calibration_scores.len());
+			eprintln!("run Validation expected calibration error on the other {}: {}", evaluation_scores.len(), classification::metrics::expected_calibration_error(&evaluation_scores, bins)?);
+			scores = fitted.calibrate_scores(&evaluation_scores);
+			eprintln!("run Calibrated validation expected calibration error: {}", classification::metrics::expected_calibration_error(&scores, bins)?);
+			calibrator = Some(fitted);
+		} else {
+			eprintln!("run Validation expected calibration error: {}", classification::metrics::expected_calibration_error(&scores, bins)?);
+		}
+		
+		config.write_reliability(&classification::metrics::reliability_bins(&scores, bins)?)?;
+		
+		if let Some(metric) = config.get_threshold_metric() {
+			threshold = classification::select_threshold(&scores, metric)?;
+		}
+		classifier.into_fitted()?
+	} else {
+		fit_classifier(config, &training_passengers)?.into_fitted()?
+	};
 	
-	config.write_output(&passenger_weights, &test_passengers)
+	Ok(Model::new(*config.get_seed(), threshold, calibrator, learner))
+}
+
+fn fit_classifier<'a>(config: &'a Config, training_passengers: &[TrainingPassenger]) -> Result<Estimator<'a>, String> {
+	let mut classifier = Registry::default().classifier(&config.get_learner().to_string(), config)?;
+	classifier.fit(training_passengers)?;
+	Ok(classifier)
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum Survived {
-    #[serde(rename = "0")]
+    #[serde(rename = "1")]
     Yes,
     
-    #[serde(rename = "1")]
+    #[serde(rename = "0")]
     No
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub enum PassengerClass {
     #[serde(rename = "1")]
     First,
@@ -166,7 +812,7 @@ pub enum PassengerClass {
     Third,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub enum Sex {
     #[serde(rename = "male")]
     Male,
@@ -175,7 +821,7 @@ pub enum Sex {
     Female
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub enum PortOfEmbarkation {
     #[serde(rename = "C")]
     Cherbourg,
@@ -187,7 +833,7 @@ pub enum PortOfEmbarkation {
     Queenstown,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct TrainingPassenger {
     #[serde(rename = "PassengerId")]
     passenger_id: u64,
@@ -227,6 +873,7 @@ pub struct TrainingPassenger {
 }
 
 impl TrainingPassenger {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         passenger_id: u64,
         survived: Survived,
@@ -306,7 +953,7 @@ impl TrainingPassenger {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Passenger {
     #[serde(rename = "PassengerId")]
     passenger_id: u64,
@@ -343,6 +990,7 @@ pub struct Passenger {
 }
 
 impl Passenger {
+	#[allow(clippy::too_many_arguments)]
 	pub fn new(
         passenger_id: u64,
         passenger_class: PassengerClass,
@@ -416,6 +1064,233 @@ impl Passenger {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    PassengerClass,
+    Name,
+    Sex,
+    Age,
+    SiblingsSpouses,
+    ParentsChildren,
+    TicketId,
+    Fare,
+    CabinId,
+    PortOfEmbarkation,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 10] = [
+        Feature::PassengerClass,
+        Feature::Name,
+        Feature::Sex,
+        Feature::Age,
+        Feature::SiblingsSpouses,
+        Feature::ParentsChildren,
+        Feature::TicketId,
+        Feature::Fare,
+        Feature::CabinId,
+        Feature::PortOfEmbarkation,
+    ];
+    
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Feature::PassengerClass => "passenger_class",
+            Feature::Name => "name",
+            Feature::Sex => "sex",
+            Feature::Age => "age",
+            Feature::SiblingsSpouses => "siblings_spouses",
+            Feature::ParentsChildren => "parents_children",
+            Feature::TicketId => "ticket_id",
+            Feature::Fare => "fare",
+            Feature::CabinId => "cabin_id",
+            Feature::PortOfEmbarkation => "port_of_embarkation",
+        }
+    }
+    
+    // Index 0 is reserved for a missing value, enums have one more index per variant and everything else has index 1.
+    pub fn get_weight_count(&self) -> usize {
+        match self {
+            Feature::PassengerClass => 4,
+            Feature::Sex => 3,
+            Feature::PortOfEmbarkation => 4,
+            _ => 2,
+        }
+    }
+    
+    // Age and fare take any value, the other features a handful of categories or counts.
+    pub fn is_continuous(&self) -> bool {
+        matches!(self, Feature::Age | Feature::Fare)
+    }
+    
+    // Readable name of a value of this feature, e.g. "first" for FeatureValue::Category(1) of the passenger class.
+    pub fn format_value(&self, value: &FeatureValue) -> String {
+        match (self, value) {
+            (_, FeatureValue::Missing) => "missing".to_string(),
+            (_, FeatureValue::Present) => "present".to_string(),
+            (_, FeatureValue::Number(number)) => number.to_string(),
+            (Feature::PassengerClass, FeatureValue::Category(1)) => "first".to_string(),
+            (Feature::PassengerClass, FeatureValue::Category(2)) => "second".to_string(),
+            (Feature::PassengerClass, FeatureValue::Category(3)) => "third".to_string(),
+            (Feature::Sex, FeatureValue::Category(1)) => "female".to_string(),
+            (Feature::Sex, FeatureValue::Category(2)) => "male".to_string(),
+            (Feature::PortOfEmbarkation, FeatureValue::Category(1)) => "cherbourg".to_string(),
+            (Feature::PortOfEmbarkation, FeatureValue::Category(2)) => "southampton".to_string(),
+            (Feature::PortOfEmbarkation, FeatureValue::Category(3)) => "queenstown".to_string(),
+            (_, FeatureValue::Category(category)) => category.to_string(),
+        }
+    }
+    
+    // Parses a comma separated list of feature names, where "all" stands for every feature.
+    pub fn parse_list(list: &str) -> Result<Vec<Feature>, String> {
+        if list == "all" {
+            return Ok(Feature::ALL.to_vec())
+        }
+        let mut features = Vec::new();
+        for name in list.split(',') {
+            let feature = name.trim().parse::<Feature>()?;
+            if !features.contains(&feature) {
+                features.push(feature);
+            }
+        }
+        Ok(features)
+    }
+    
+    pub fn format_list(features: &[Feature]) -> String {
+        if features == Feature::ALL {
+            return "all".to_string()
+        }
+        let names: Vec<&str> = features.iter().map(|feature| feature.get_name()).collect();
+        names.join(",")
+    }
+}
+
+impl FromStr for Feature {
+    type Err = String;
+    
+    fn from_str(name: &str) -> Result<Feature, String> {
+        match Feature::ALL.iter().find(|feature| feature.get_name() == name) {
+            Some(feature) => Ok(*feature),
+            None => Err(format!("Feature::from_str unknown feature {}", name)),
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureValue {
+    Missing,
+    // Strings only record whether they are present
+    Present,
+    // Enum variants are numbered from 1
+    Category(usize),
+    Number(f64),
+}
+
+impl FeatureValue {
+    fn from_passenger_class(passenger_class: &Option<PassengerClass>) -> FeatureValue {
+        match passenger_class {
+            None => FeatureValue::Missing,
+            Some(PassengerClass::First) => FeatureValue::Category(1),
+            Some(PassengerClass::Second) => FeatureValue::Category(2),
+            Some(PassengerClass::Third) => FeatureValue::Category(3),
+        }
+    }
+    
+    fn from_sex(sex: &Option<Sex>) -> FeatureValue {
+        match sex {
+            None => FeatureValue::Missing,
+            Some(Sex::Female) => FeatureValue::Category(1),
+            Some(Sex::Male) => FeatureValue::Category(2),
+        }
+    }
+    
+    fn from_port_of_embarkation(port_of_embarkation: &Option<PortOfEmbarkation>) -> FeatureValue {
+        match port_of_embarkation {
+            None => FeatureValue::Missing,
+            Some(PortOfEmbarkation::Cherbourg) => FeatureValue::Category(1),
+            Some(PortOfEmbarkation::Southampton) => FeatureValue::Category(2),
+            Some(PortOfEmbarkation::Queenstown) => FeatureValue::Category(3),
+        }
+    }
+    
+    fn from_text(text: &Option<String>) -> FeatureValue {
+        match text {
+            None => FeatureValue::Missing,
+            Some(_text) => FeatureValue::Present,
+        }
+    }
+    
+    fn from_number(number: &Option<f64>) -> FeatureValue {
+        match number {
+            None => FeatureValue::Missing,
+            Some(number) => FeatureValue::Number(*number),
+        }
+    }
+    
+    fn from_count(count: &Option<usize>) -> FeatureValue {
+        match count {
+            None => FeatureValue::Missing,
+            Some(count) => FeatureValue::Number(classification::quick_convert(count)),
+        }
+    }
+}
+
+// Passenger records with and without the Survived label expose their fields in the same way.
+pub trait PassengerFeatures {
+    fn get_passenger_id(&self) -> &u64;
+    
+    fn get_feature(&self, feature: &Feature) -> FeatureValue;
+}
+
+impl PassengerFeatures for TrainingPassenger {
+    fn get_passenger_id(&self) -> &u64 {
+        &self.passenger_id
+    }
+    
+    fn get_feature(&self, feature: &Feature) -> FeatureValue {
+        match feature {
+            Feature::PassengerClass => FeatureValue::from_passenger_class(self.get_passenger_class()),
+            Feature::Name => FeatureValue::from_text(self.get_name()),
+            Feature::Sex => FeatureValue::from_sex(self.get_sex()),
+            Feature::Age => FeatureValue::from_number(self.get_age()),
+            Feature::SiblingsSpouses => FeatureValue::from_count(self.get_siblings_spouses()),
+            Feature::ParentsChildren => FeatureValue::from_count(self.get_parents_children()),
+            Feature::TicketId => FeatureValue::from_text(self.get_ticket_id()),
+            Feature::Fare => FeatureValue::from_number(self.get_fare()),
+            Feature::CabinId => FeatureValue::from_text(self.get_cabin_id()),
+            Feature::PortOfEmbarkation => FeatureValue::from_port_of_embarkation(self.get_port_of_embarkation()),
+        }
+    }
+}
+
+impl PassengerFeatures for Passenger {
+    fn get_passenger_id(&self) -> &u64 {
+        &self.passenger_id
+    }
+    
+    fn get_feature(&self, feature: &Feature) -> FeatureValue {
+        match feature {
+            Feature::PassengerClass => FeatureValue::from_passenger_class(self.get_passenger_class()),
+            Feature::Name => FeatureValue::from_text(self.get_name()),
+            Feature::Sex => FeatureValue::from_sex(self.get_sex()),
+            Feature::Age => FeatureValue::from_number(self.get_age()),
+            Feature::SiblingsSpouses => FeatureValue::from_count(self.get_siblings_spouses()),
+            Feature::ParentsChildren => FeatureValue::from_count(self.get_parents_children()),
+            Feature::TicketId => FeatureValue::from_text(self.get_ticket_id()),
+            Feature::Fare => FeatureValue::from_number(self.get_fare()),
+            Feature::CabinId => FeatureValue::from_text(self.get_cabin_id()),
+            Feature::PortOfEmbarkation => FeatureValue::from_port_of_embarkation(self.get_port_of_embarkation()),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct TestedPassenger {
     #[serde(rename = "PassengerId")]
@@ -423,6 +1298,18 @@ pub struct TestedPassenger {
     
     #[serde(rename = "Survived")]
     survived: Survived,
+    
+    #[serde(rename = "Probability", skip_serializing_if = "Option::is_none")]
+    probability: Option<f64>,
+    
+    #[serde(rename = "TopFeatures", skip_serializing_if = "Option::is_none")]
+    top_features: Option<String>,
+    
+    #[serde(rename = "ModelId", skip_serializing_if = "Option::is_none")]
+    model_id: Option<String>,
+    
+    #[serde(rename = "Contributions", skip_serializing_if = "Option::is_none")]
+    contributions: Option<String>,
 }
 
 impl TestedPassenger {
@@ -432,448 +1319,305 @@ impl TestedPassenger {
 			survived: match outcome.prediction {
 				classification::BinaryClass::Yes => Survived::Yes,
 				classification::BinaryClass::No => Survived::No
-			}
+			},
+			probability: None,
+			top_features: None,
+			model_id: None,
+			contributions: None,
 		}
 		
 	}
+	
+	pub fn with_probability(outcome: classification::Outcome) -> TestedPassenger {
+		let probability = outcome.probability;
+		let mut tested_passenger = TestedPassenger::new(outcome);
+		tested_passenger.probability = Some(probability);
+		tested_passenger
+	}
+	
+	// The extended output: the probability, the features that moved it most and the id of the model.
+	pub fn extended(outcome: classification::Outcome, top_features: String, model_id: String) -> TestedPassenger {
+		let mut tested_passenger = TestedPassenger::with_probability(outcome);
+		tested_passenger.top_features = Some(top_features);
+		tested_passenger.model_id = Some(model_id);
+		tested_passenger
+	}
+	
+	// Adds how each feature moved the prediction, as explain::Explanation::format gives it.
+	pub fn with_contributions(mut self, contributions: String) -> TestedPassenger {
+		self.contributions = Some(contributions);
+		self
+	}
+}
+
+// How PassengerWeights starts before training, written on the command line as constant:1, zeros, uniform:0.01, normal:0.01 or model:model.json.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Initialization {
+    // Every weight, including the bias, starts at the value
+    Constant(f64),
+    // Uniform between -scale and scale
+    Uniform(f64),
+    // Normal with mean 0 and the given standard deviation
+    Normal(f64),
+    // The weights of a model saved with --model
+    WarmStart(String),
+}
+
+impl FromStr for Initialization {
+    type Err = String;
+    
+    fn from_str(name: &str) -> Result<Initialization, String> {
+        let (strategy, value) = match name.find(':') {
+            Some(index) => (&name[..index], Some(&name[index + 1..])),
+            None => (name, None),
+        };
+        let number = |value: Option<&str>| match value.map(|value| value.parse::<f64>()) {
+            Some(Ok(number)) if number.is_finite() => Ok(number),
+            _ => Err(format!("Initialization::from_str {} needs a number, e.g. {}:0.01", name, strategy)),
+        };
+        match (strategy, value) {
+            ("zeros", None) => Ok(Initialization::Constant(0_f64)),
+            ("constant", _) => Ok(Initialization::Constant(number(value)?)),
+            ("uniform", _) => Ok(Initialization::Uniform(number(value)?)),
+            ("normal", _) => Ok(Initialization::Normal(number(value)?)),
+            ("model", Some(filename)) if !filename.is_empty() => Ok(Initialization::WarmStart(filename.to_string())),
+            _ => Err(format!("Initialization::from_str unknown initialization {}, expected one of zeros, constant:<value>, uniform:<scale>, normal:<std>, model:<file>", name)),
+        }
+    }
+}
+
+impl fmt::Display for Initialization {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Initialization::Constant(value) => write!(f, "constant:{}", value),
+            Initialization::Uniform(scale) => write!(f, "uniform:{}", scale),
+            Initialization::Normal(std) => write!(f, "normal:{}", std),
+            Initialization::WarmStart(filename) => write!(f, "model:{}", filename),
+        }
+    }
+}
+
+impl TryFrom<String> for Initialization {
+    type Error = String;
+    
+    fn try_from(name: String) -> Result<Initialization, String> {
+        name.parse()
+    }
+}
+
+impl From<Initialization> for String {
+    fn from(initialization: Initialization) -> String {
+        initialization.to_string()
+    }
+}
+
+impl Default for Initialization {
+    fn default() -> Self {
+        Initialization::Constant(1_f64)
+    }
 }
 
-#[derive(Debug)]
+// Weights are kept in BTreeMaps so they are visited, summed and written in the same order on every run.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct PassengerWeights {
     bias: f64,
-    passenger_class: HashMap<usize, f64>,
-    name: HashMap<usize, f64>,
-    sex: HashMap<usize, f64>,
-    age: HashMap<usize, f64>,
-    siblings_spouses: HashMap<usize, f64>,
-    parents_children: HashMap<usize, f64>,
-    ticket_id: HashMap<usize, f64>,
-    fare: HashMap<usize, f64>,
-    cabin_id: HashMap<usize, f64>,
-    port_of_embarkation: HashMap<usize, f64>,
+    passenger_class: BTreeMap<usize, f64>,
+    name: BTreeMap<usize, f64>,
+    sex: BTreeMap<usize, f64>,
+    age: BTreeMap<usize, f64>,
+    siblings_spouses: BTreeMap<usize, f64>,
+    parents_children: BTreeMap<usize, f64>,
+    ticket_id: BTreeMap<usize, f64>,
+    fare: BTreeMap<usize, f64>,
+    cabin_id: BTreeMap<usize, f64>,
+    port_of_embarkation: BTreeMap<usize, f64>,
+    features: Vec<Feature>,
 }
 
 impl PassengerWeights {
+    // Every weight, including the bias, starts at 1.
     pub fn new() -> PassengerWeights {
-        let bias = 1_f64;
-        
-        //Optional integers, floats, enums and string can be fully instantiated now, because they have a maximum number of weights.
-        //At least one for when Optional matches Some and one for when Optional matches None.
-        //Index 0 is always reserved for 
-        let mut age = HashMap::new();
-        age.insert(0, 1_f64);
-        age.insert(1, 1_f64);
-        
-        let mut siblings_spouses = HashMap::new();
-        siblings_spouses.insert(0, 1_f64);
-        siblings_spouses.insert(1, 1_f64);
-        
-        let mut parents_children = HashMap::new();
-        parents_children.insert(0, 1_f64);
-        parents_children.insert(1, 1_f64);
-        
-        let mut fare = HashMap::new();
-        fare.insert(0, 1_f64);
-        fare.insert(1, 1_f64);
-        
-        //In future versions, the strings may be categorized, so capacity may be set to a fixed number of categories. TBD
-        let mut name = HashMap::new();
-        name.insert(0, 1_f64);
-        name.insert(1, 1_f64);
-        
-        let mut ticket_id = HashMap::new();
-        ticket_id.insert(0, 1_f64);
-        ticket_id.insert(1, 1_f64);
-        
-        let mut cabin_id = HashMap::new();
-        cabin_id.insert(0, 1_f64);
-        cabin_id.insert(1, 1_f64);
-        
-        // Enums can have more than two categories in the Some option
-        let mut passenger_class = HashMap::new();
-        passenger_class.insert(0, 1_f64);
-        passenger_class.insert(1, 1_f64);
-        passenger_class.insert(2, 1_f64);
-        passenger_class.insert(3, 1_f64);
-        
-        let mut sex = HashMap::new();
-        sex.insert(0, 1_f64);
-        sex.insert(1, 1_f64);
-        sex.insert(2, 1_f64);
-                
-        let mut port_of_embarkation = HashMap::new();
-        port_of_embarkation.insert(0, 1_f64);
-        port_of_embarkation.insert(1, 1_f64);
-        port_of_embarkation.insert(2, 1_f64);
-        port_of_embarkation.insert(3, 1_f64);
-                
-        PassengerWeights {
+        PassengerWeights::from_schema(&Feature::ALL, 1_f64, || 1_f64)
+    }
+    
+    // Creates get_weight_count weights for every feature, enabled or not, drawing each from initial_weight in the order of Feature::ALL.
+    pub fn from_schema<F: FnMut() -> f64>(features: &[Feature], bias: f64, mut initial_weight: F) -> PassengerWeights {
+        let mut passenger_weights = PassengerWeights {
             bias,
-            passenger_class,
-            name,
-            sex,
-            age,
-            siblings_spouses,
-            parents_children,
-            ticket_id,
-            fare,
-            cabin_id,
-            port_of_embarkation,
+            passenger_class: BTreeMap::new(),
+            name: BTreeMap::new(),
+            sex: BTreeMap::new(),
+            age: BTreeMap::new(),
+            siblings_spouses: BTreeMap::new(),
+            parents_children: BTreeMap::new(),
+            ticket_id: BTreeMap::new(),
+            fare: BTreeMap::new(),
+            cabin_id: BTreeMap::new(),
+            port_of_embarkation: BTreeMap::new(),
+            features: features.to_vec(),
+        };
+        for feature in Feature::ALL.iter() {
+            let weights = passenger_weights.get_weights_mut(feature);
+            for index in 0..feature.get_weight_count() {
+                weights.insert(index, initial_weight());
+            }
         }
+        passenger_weights
     }
-}
-
-impl classification::LogisticBinaryClassificationTestable for TrainingPassenger {
-	type Weights = PassengerWeights;
-	
-    fn hypothesis(self: &Self, weights: &Self::Weights) -> Result<f64, String> {
-        let mut weighted_sum = 0_f64;
-        
-        weighted_sum = weighted_sum.add(weights.bias);
-        
-        match self.get_name() {
-            None => {
-                match weights.name.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: name weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(_name) => {
-                match weights.name.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: name weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-        }
-        
-        match self.get_age() {
-            None => {
-                match weights.age.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: age weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(age) => {
-                match weights.age.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: age weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight.mul(age));
-                    },
-                }
+    
+    // Random strategies draw from seed and start the bias at 0. A warm start keeps the saved weights but trains the given features.
+    pub fn initialized(features: &[Feature], initialization: &Initialization, seed: u64) -> Result<PassengerWeights, String> {
+        let mut rng = classification::random::Rng::new(seed);
+        match initialization {
+            Initialization::Constant(value) => Ok(PassengerWeights::from_schema(features, *value, || *value)),
+            Initialization::Uniform(scale) => Ok(PassengerWeights::from_schema(features, 0_f64, || rng.uniform(-scale, *scale))),
+            Initialization::Normal(std) => Ok(PassengerWeights::from_schema(features, 0_f64, || rng.normal(0_f64, *std))),
+            Initialization::WarmStart(filename) => {
+                let mut passenger_weights = Model::read(filename)?.get_weights()?.clone();
+                passenger_weights.features = features.to_vec();
+                Ok(passenger_weights)
             },
         }
-        
-        match self.get_siblings_spouses() {
-            None => {
-                match weights.siblings_spouses.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: siblings_spouses weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(siblings_spouses) => {
-                match weights.siblings_spouses.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: siblings_spouses weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight.mul(classification::quick_convert(siblings_spouses)));
-                    },
-                }
-            },
+    }
+    
+    // Only the given features contribute to the weighted sum, the weights of the others are left untouched.
+    pub fn with_features(features: &[Feature]) -> PassengerWeights {
+        let mut passenger_weights = PassengerWeights::new();
+        passenger_weights.features = features.to_vec();
+        passenger_weights
+    }
+    
+    pub fn get_features(&self) -> &Vec<Feature> {
+        &self.features
+    }
+    
+    pub fn get_bias(&self) -> &f64 {
+        &self.bias
+    }
+    
+    // Unlike Regularizable::shrink, this also multiplies the bias by factor.
+    pub fn scale(&mut self, factor: &f64) {
+        self.bias = self.bias.mul(factor);
+        classification::Regularizable::shrink(self, factor);
+    }
+    
+    fn get_weights(&self, feature: &Feature) -> &BTreeMap<usize, f64> {
+        match feature {
+            Feature::PassengerClass => &self.passenger_class,
+            Feature::Name => &self.name,
+            Feature::Sex => &self.sex,
+            Feature::Age => &self.age,
+            Feature::SiblingsSpouses => &self.siblings_spouses,
+            Feature::ParentsChildren => &self.parents_children,
+            Feature::TicketId => &self.ticket_id,
+            Feature::Fare => &self.fare,
+            Feature::CabinId => &self.cabin_id,
+            Feature::PortOfEmbarkation => &self.port_of_embarkation,
         }
-        
-        match self.get_parents_children() {
-            None => {
-                match weights.parents_children.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: parents_children weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(parents_children) => {
-                match weights.parents_children.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: parents_children weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight.mul(classification::quick_convert(parents_children)));
-                    },
-                }
-            },
+    }
+    
+    // The weights of one feature by the index encode gives its values
+    pub fn get_feature_weights(&self, feature: &Feature) -> &BTreeMap<usize, f64> {
+        self.get_weights(feature)
+    }
+    
+    fn get_weights_mut(&mut self, feature: &Feature) -> &mut BTreeMap<usize, f64> {
+        match feature {
+            Feature::PassengerClass => &mut self.passenger_class,
+            Feature::Name => &mut self.name,
+            Feature::Sex => &mut self.sex,
+            Feature::Age => &mut self.age,
+            Feature::SiblingsSpouses => &mut self.siblings_spouses,
+            Feature::ParentsChildren => &mut self.parents_children,
+            Feature::TicketId => &mut self.ticket_id,
+            Feature::Fare => &mut self.fare,
+            Feature::CabinId => &mut self.cabin_id,
+            Feature::PortOfEmbarkation => &mut self.port_of_embarkation,
         }
-        
-        match self.get_fare() {
-            None => {
-                match weights.fare.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: fare weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(fare) => {
-                match weights.fare.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: fare weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight.mul(fare));
-                    },
-                }
-            },
+    }
+    
+    // Every feature value picks one weight and the value it is multiplied by.
+    // Index 0 is reserved for None, enum variants use their category number and everything else uses index 1.
+    pub fn encode(value: &FeatureValue) -> (usize, f64) {
+        match value {
+            FeatureValue::Missing => (0, 1_f64),
+            FeatureValue::Present => (1, 1_f64),
+            FeatureValue::Category(category) => (*category, 1_f64),
+            FeatureValue::Number(number) => (1, *number),
         }
+    }
+    
+    pub fn weighted_sum<R: PassengerFeatures>(&self, passenger: &R) -> Result<f64, String> {
+        let mut weighted_sum = self.bias;
         
-        match self.get_ticket_id() {
-            None => {
-                match weights.ticket_id.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: ticket_id weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(_ticket_id) => {
-                match weights.ticket_id.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: ticket_id weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
+        for feature in &self.features {
+            let (index, value) = PassengerWeights::encode(&passenger.get_feature(feature));
+            match self.get_weights(feature).get(&index) {
+                None => {
+                    let message = format!("PassengerWeights::weighted_sum: {} weight {} was unreachable for passenger {}", feature, index, passenger.get_passenger_id());
+                    return Err(message)
+                },
+                Some(weight) => {
+                    weighted_sum = weighted_sum.add(weight.mul(value));
+                },
+            }
         }
         
-        match self.get_cabin_id() {
-            None => {
-                match weights.cabin_id.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: cabin_id weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(_cabin_id) => {
-                match weights.cabin_id.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: cabin_id weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-        }
+        Ok(weighted_sum)
+    }
+    
+    pub fn update<R: PassengerFeatures>(&mut self, passenger: &R, diff: &f64) -> Result<(), String> {
+        self.bias = self.bias.add(diff);
         
-        match self.get_passenger_class() {
-            None => {
-                match weights.passenger_class.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: passenger_class weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(passenger_class) => {
-                match passenger_class {
-                    PassengerClass::First => {
-                        match weights.passenger_class.get(&1) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: passenger_class weight 1 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                    PassengerClass::Second => {
-                        match weights.passenger_class.get(&2) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: passenger_class weight 2 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                    PassengerClass::Third => {
-                        match weights.passenger_class.get(&3) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: passenger_class weight 3 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                }
-            },
+        for feature in self.features.clone() {
+            let (index, value) = PassengerWeights::encode(&passenger.get_feature(&feature));
+            match self.get_weights_mut(&feature).get_mut(&index) {
+                None => {
+                    let message = format!("PassengerWeights::update: {} weight {} was unreachable for passenger {}", feature, index, passenger.get_passenger_id());
+                    return Err(message)
+                },
+                Some(weight) => {
+                    *weight = weight.add(diff.mul(value));
+                },
+            }
         }
         
-        match self.get_sex() {
-            None => {
-                match weights.sex.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: sex weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(sex) => {
-                match sex {
-                    Sex::Female => {
-                        match weights.sex.get(&1) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: sex weight 1 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                    Sex::Male => {
-                        match weights.sex.get(&2) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: sex weight 2 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                }
-            },
+        Ok(())
+    }
+}
+
+impl classification::Regularizable for PassengerWeights {
+    fn shrink(self: &mut Self, factor: &f64) {
+        for feature in self.features.clone() {
+            for weight in self.get_weights_mut(&feature).values_mut() {
+                *weight = weight.mul(factor);
+            }
         }
-        
-        match self.get_port_of_embarkation() {
-            None => {
-                match weights.port_of_embarkation.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: port_of_embarkation weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(port_of_embarkation) => {
-                match port_of_embarkation {
-                    PortOfEmbarkation::Cherbourg => {
-                        match weights.port_of_embarkation.get(&1) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: port_of_embarkation weight 1 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                    PortOfEmbarkation::Southampton => {
-                        match weights.port_of_embarkation.get(&2) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: port_of_embarkation weight 2 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                    PortOfEmbarkation::Queenstown => {
-                        match weights.port_of_embarkation.get(&3) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: port_of_embarkation weight 3 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                }
-            },
+    }
+    
+    fn squared_norm(self: &Self) -> f64 {
+        let mut squared_norm = 0_f64;
+        for feature in &self.features {
+            for weight in self.get_weights(feature).values() {
+                squared_norm = squared_norm.add(weight.mul(weight));
+            }
         }
+        squared_norm
+    }
+}
 
-        Ok(Self::logistic(weighted_sum))
+impl Default for PassengerWeights {
+    fn default() -> Self {
+        PassengerWeights::new()
+    }
+}
+
+impl classification::LogisticBinaryClassificationTestable for TrainingPassenger {
+	type Weights = PassengerWeights;
+	
+    fn hypothesis(self: &Self, weights: &Self::Weights) -> Result<f64, String> {
+        Ok(Self::logistic(weights.weighted_sum(self)?))
     }
 	
 	fn get_record_id(self: &Self) -> &u64 {
@@ -882,356 +1626,10 @@ impl classification::LogisticBinaryClassificationTestable for TrainingPassenger
 }
 
 impl classification::LogisticBinaryClassificationTestable for Passenger {
-    type Weights = PassengerWeights;
+	type Weights = PassengerWeights;
 	
     fn hypothesis(self: &Self, weights: &Self::Weights) -> Result<f64, String> {
-        let mut weighted_sum = 0_f64;
-        
-        weighted_sum = weighted_sum.add(weights.bias);
-        
-        match self.get_name() {
-            None => {
-                match weights.name.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: name weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(_name) => {
-                match weights.name.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: name weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-        }
-        
-        match self.get_age() {
-            None => {
-                match weights.age.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: age weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(age) => {
-                match weights.age.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: age weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight.mul(age));
-                    },
-                }
-            },
-        }
-        
-        match self.get_siblings_spouses() {
-            None => {
-                match weights.siblings_spouses.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: siblings_spouses weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(siblings_spouses) => {
-                match weights.siblings_spouses.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: siblings_spouses weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight.mul(classification::quick_convert(siblings_spouses)));
-                    },
-                }
-            },
-        }
-        
-        match self.get_parents_children() {
-            None => {
-                match weights.parents_children.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: parents_children weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(parents_children) => {
-                match weights.parents_children.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: parents_children weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight.mul(classification::quick_convert(parents_children)));
-                    },
-                }
-            },
-        }
-        
-        match self.get_fare() {
-            None => {
-                match weights.fare.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: fare weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(fare) => {
-                match weights.fare.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: fare weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight.mul(fare));
-                    },
-                }
-            },
-        }
-        
-        match self.get_ticket_id() {
-            None => {
-                match weights.ticket_id.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: ticket_id weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(_ticket_id) => {
-                match weights.ticket_id.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: ticket_id weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-        }
-        
-        match self.get_cabin_id() {
-            None => {
-                match weights.cabin_id.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: cabin_id weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(_cabin_id) => {
-                match weights.cabin_id.get(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: cabin_id weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-        }
-        
-        match self.get_passenger_class() {
-            None => {
-                match weights.passenger_class.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: passenger_class weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(passenger_class) => {
-                match passenger_class {
-                    PassengerClass::First => {
-                        match weights.passenger_class.get(&1) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: passenger_class weight 1 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                    PassengerClass::Second => {
-                        match weights.passenger_class.get(&2) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: passenger_class weight 2 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                    PassengerClass::Third => {
-                        match weights.passenger_class.get(&3) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: passenger_class weight 3 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                }
-            },
-        }
-        
-        match self.get_sex() {
-            None => {
-                match weights.sex.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: sex weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(sex) => {
-                match sex {
-                    Sex::Female => {
-                        match weights.sex.get(&1) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: sex weight 1 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                    Sex::Male => {
-                        match weights.sex.get(&2) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: sex weight 2 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                }
-            },
-        }
-        
-        match self.get_port_of_embarkation() {
-            None => {
-                match weights.port_of_embarkation.get(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTestable::hypothesis: port_of_embarkation weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        weighted_sum = weighted_sum.add(weight);
-                    },
-                }
-            },
-            Some(port_of_embarkation) => {
-                match port_of_embarkation {
-                    PortOfEmbarkation::Cherbourg => {
-                        match weights.port_of_embarkation.get(&1) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: port_of_embarkation weight 1 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                    PortOfEmbarkation::Southampton => {
-                        match weights.port_of_embarkation.get(&2) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: port_of_embarkation weight 2 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                    PortOfEmbarkation::Queenstown => {
-                        match weights.port_of_embarkation.get(&3) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTestable::hypothesis: port_of_embarkation weight 3 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                weighted_sum = weighted_sum.add(weight);
-                            },
-                        }
-                    },
-                }
-            },
-        }
-
-        Ok(Self::logistic(weighted_sum))
+        Ok(Self::logistic(weights.weighted_sum(self)?))
     }
 	
 	fn get_record_id(self: &Self) -> &u64 {
@@ -1248,357 +1646,13 @@ impl classification::LogisticBinaryClassificationTrainable for TrainingPassenger
 	}
 
 	fn update_weights(self: &Self, diff: &f64, weights: &mut Self::Weights) -> Result<(), String>{
-        weights.bias = weights.bias.add(diff);
-        
-        match self.get_name() {
-            None => {
-                match weights.name.get_mut(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: name weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff);
-                    },
-                }
-            },
-            Some(_name) => {
-                match weights.name.get_mut(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: name weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff);
-                    },
-                }
-            },
-        }
-        
-        match self.get_age() {
-            None => {
-                match weights.age.get_mut(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: age weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff);
-                    },
-                }
-            },
-            Some(age) => {
-                match weights.age.get_mut(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: age weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff.mul(age));
-                    },
-                }
-            },
-        }
-        
-        match self.get_siblings_spouses() {
-            None => {
-                match weights.siblings_spouses.get_mut(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: siblings_spouses weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff);
-                    },
-                }
-            },
-            Some(siblings_spouses) => {
-                match weights.siblings_spouses.get_mut(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: siblings_spouses weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff.mul(classification::quick_convert(siblings_spouses)));
-                    },
-                }
-            },
-        }
-        
-        match self.get_parents_children() {
-            None => {
-                match weights.parents_children.get_mut(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: parents_children weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff);
-                    },
-                }
-            },
-            Some(parents_children) => {
-                match weights.parents_children.get_mut(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: parents_children weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff.mul(classification::quick_convert(parents_children)));
-                    },
-                }
-            },
-        }
-        
-        match self.get_fare() {
-            None => {
-                match weights.fare.get_mut(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: fare weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff);
-                    },
-                }
-            },
-            Some(fare) => {
-                match weights.fare.get_mut(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: fare weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff.mul(fare));
-                    },
-                }
-            },
-        }
-        
-        match self.get_ticket_id() {
-            None => {
-                match weights.ticket_id.get_mut(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: ticket_id weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff);
-                    },
-                }
-            },
-            Some(_ticket_id) => {
-                match weights.ticket_id.get_mut(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: ticket_id weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff);
-                    },
-                }
-            },
-        }
-        
-        match self.get_cabin_id() {
-            None => {
-                match weights.cabin_id.get_mut(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: cabin_id weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff);
-                    },
-                }
-            },
-            Some(_cabin_id) => {
-                match weights.cabin_id.get_mut(&1) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: cabin_id weight 1 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff);
-                    },
-                }
-            },
-        }
-        
-        match self.get_passenger_class() {
-            None => {
-                match weights.passenger_class.get_mut(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: passenger_class weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff);
-                    },
-                }
-            },
-            Some(passenger_class) => {
-                match passenger_class {
-                    PassengerClass::First => {
-                        match weights.passenger_class.get_mut(&1) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTrainable::update_weights: passenger_class weight 1 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                *weight = weight.add(diff);
-                            },
-                        }
-                    },
-                    PassengerClass::Second => {
-                        match weights.passenger_class.get_mut(&2) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTrainable::update_weights: passenger_class weight 2 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                *weight = weight.add(diff);
-                            },
-                        }
-                    },
-                    PassengerClass::Third => {
-                        match weights.passenger_class.get_mut(&3) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTrainable::update_weights: passenger_class weight 3 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                *weight = weight.add(diff);
-                            },
-                        }
-                    },
-                }
-            },
-        }
-        
-        match self.get_sex() {
-            None => {
-                match weights.sex.get_mut(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: sex weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff);
-                    },
-                }
-            },
-            Some(sex) => {
-                match sex {
-                    Sex::Female => {
-                        match weights.sex.get_mut(&1) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTrainable::update_weights: sex weight 1 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                *weight = weight.add(diff);
-                            },
-                        }
-                    },
-                    Sex::Male => {
-                        match weights.sex.get_mut(&2) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTrainable::update_weights: sex weight 2 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                *weight = weight.add(diff);
-                            },
-                        }
-                    },
-                }
-            },
-        }
-        
-        match self.get_port_of_embarkation() {
-            None => {
-                match weights.port_of_embarkation.get_mut(&0) {
-                    None => {
-                        let passenger_id = self.get_passenger_id();
-                        let message = format!("LogisticBinaryClassificationTrainable::update_weights: port_of_embarkation weight 0 was unreachable for passenger {}", passenger_id);
-                        return Err(message)
-                    },
-                    Some(weight) => {
-                        *weight = weight.add(diff);
-                    },
-                }
-            },
-            Some(port_of_embarkation) => {
-                match port_of_embarkation {
-                    PortOfEmbarkation::Cherbourg => {
-                        match weights.port_of_embarkation.get_mut(&1) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTrainable::update_weights: port_of_embarkation weight 1 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                *weight = weight.add(diff);
-                            },
-                        }
-                    },
-                    PortOfEmbarkation::Southampton => {
-                        match weights.port_of_embarkation.get_mut(&2) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTrainable::update_weights: port_of_embarkation weight 2 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                *weight = weight.add(diff);
-                            },
-                        }
-                    },
-                    PortOfEmbarkation::Queenstown => {
-                        match weights.port_of_embarkation.get_mut(&3) {
-                            None => {
-                                let passenger_id = self.get_passenger_id();
-                                let message = format!("LogisticBinaryClassificationTrainable::update_weights: port_of_embarkation weight 3 was unreachable for passenger {}", passenger_id);
-                                return Err(message)
-                            },
-                            Some(weight) => {
-                                *weight = weight.add(diff);
-                            },
-                        }
-                    },
-                }
-            },
-        }
-
-        Ok(())
+        weights.update(self, diff)
     }
 }
 
 impl Clone for PassengerWeights {
     fn clone(&self) -> Self {
-        let bias = self.bias.clone();
+        let bias = self.bias;
         let passenger_class = self.passenger_class.clone();
         let name = self.name.clone();
         let sex = self.sex.clone();
@@ -1609,6 +1663,7 @@ impl Clone for PassengerWeights {
         let fare = self.fare.clone();
         let cabin_id = self.cabin_id.clone();
         let port_of_embarkation = self.port_of_embarkation.clone();
+        let features = self.features.clone();
         PassengerWeights {
             bias,
             passenger_class,
@@ -1621,6 +1676,7 @@ impl Clone for PassengerWeights {
             fare,
             cabin_id,
             port_of_embarkation,
+            features,
         }
     }
 }
\ No newline at end of file
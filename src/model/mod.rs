@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use crate::classification::{self, bayes::NaiveBayes, boosting::GradientBoosting, forest::RandomForest, knn::NearestNeighbours, mlp::Mlp, svm::LinearSvm, tree::DecisionTree, Classifier, LogisticRegression, Outcome};
+use crate::{Calibrator, Config, Ensemble, Passenger, PassengerFeatures, PassengerWeights, TrainingPassenger};
+
+#[cfg(test)]
+mod tests;
+
+// The kinds of learner that train can fit, chosen with --learner.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Learner {
+    Logistic,
+    Tree,
+    Forest,
+    Boosting,
+    Bayes,
+    Knn,
+    Svm,
+    Mlp,
+    Ensemble,
+}
+
+impl FromStr for Learner {
+    type Err = String;
+    
+    fn from_str(name: &str) -> Result<Learner, String> {
+        match Learner::ALL.iter().find(|learner| learner.to_string() == name) {
+            Some(learner) => Ok(*learner),
+            None => Err(format!("Learner::from_str unknown learner {}, expected one of {}", name, Learner::ALL.map(|learner| learner.to_string()).join(", "))),
+        }
+    }
+}
+
+impl fmt::Display for Learner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Learner::Logistic => write!(f, "logistic"),
+            Learner::Tree => write!(f, "tree"),
+            Learner::Forest => write!(f, "forest"),
+            Learner::Boosting => write!(f, "boosting"),
+            Learner::Bayes => write!(f, "bayes"),
+            Learner::Knn => write!(f, "knn"),
+            Learner::Svm => write!(f, "svm"),
+            Learner::Mlp => write!(f, "mlp"),
+            Learner::Ensemble => write!(f, "ensemble"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrainedLearner {
+    Logistic(PassengerWeights),
+    Tree(DecisionTree),
+    Forest(RandomForest),
+    Boosting(GradientBoosting),
+    Bayes(NaiveBayes),
+    Knn(NearestNeighbours),
+    Svm(LinearSvm),
+    Mlp(Mlp),
+    Ensemble(Ensemble),
+}
+
+impl TrainedLearner {
+    pub fn get_learner(&self) -> Learner {
+        match self {
+            TrainedLearner::Logistic(_weights) => Learner::Logistic,
+            TrainedLearner::Tree(_tree) => Learner::Tree,
+            TrainedLearner::Forest(_forest) => Learner::Forest,
+            TrainedLearner::Boosting(_boosting) => Learner::Boosting,
+            TrainedLearner::Bayes(_bayes) => Learner::Bayes,
+            TrainedLearner::Knn(_knn) => Learner::Knn,
+            TrainedLearner::Svm(_svm) => Learner::Svm,
+            TrainedLearner::Mlp(_mlp) => Learner::Mlp,
+            TrainedLearner::Ensemble(_ensemble) => Learner::Ensemble,
+        }
+    }
+    
+    // Probability of BinaryClass::Yes before any calibration
+    pub fn predict_proba<R>(&self, record: &R) -> Result<f64, String>
+    where
+        R: PassengerFeatures + classification::LogisticBinaryClassificationTestable<Weights = PassengerWeights>,
+    {
+        match self {
+            TrainedLearner::Logistic(weights) => classification::predict_proba(weights, record),
+            TrainedLearner::Tree(tree) => Ok(tree.probability(record)),
+            TrainedLearner::Forest(forest) => Ok(forest.probability(record)),
+            TrainedLearner::Boosting(boosting) => Ok(boosting.probability(record)),
+            TrainedLearner::Bayes(bayes) => Ok(bayes.probability(record)),
+            TrainedLearner::Knn(knn) => Ok(knn.probability(record)),
+            TrainedLearner::Svm(svm) => svm.probability(record),
+            TrainedLearner::Mlp(mlp) => Ok(mlp.probability(record)),
+            TrainedLearner::Ensemble(ensemble) => ensemble.predict_proba(record),
+        }
+    }
+}
+
+impl Learner {
+    pub const ALL: [Learner; 9] = [Learner::Logistic, Learner::Tree, Learner::Forest, Learner::Boosting, Learner::Bayes, Learner::Knn, Learner::Svm, Learner::Mlp, Learner::Ensemble];
+}
+
+// Fits a learner to the training passengers with the settings of a configuration.
+pub type FitLearner = Rc<dyn Fn(&Config, &[TrainingPassenger]) -> Result<TrainedLearner, String>>;
+
+#[derive(Clone)]
+pub struct RegistryEntry {
+    pub name: String,
+    pub fit: FitLearner,
+}
+
+// The learners --learner can pick, by name.
+#[derive(Clone)]
+pub struct Registry {
+    entries: Vec<RegistryEntry>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Registry { entries: Vec::new() };
+        registry.register("logistic", |config, passengers| {
+            let mut logistic = LogisticRegression::new(config.new_weights(config.get_features())?, config.get_solver_settings());
+            logistic.fit(passengers)?;
+            Ok(TrainedLearner::Logistic(logistic.into_weights()))
+        });
+        registry.register("tree", |config, passengers| {
+            Ok(TrainedLearner::Tree(DecisionTree::fit(passengers, config.get_features(), config.get_tree_settings(), *config.get_seed())?))
+        });
+        registry.register("forest", |config, passengers| {
+            Ok(TrainedLearner::Forest(RandomForest::fit(passengers, config.get_features(), config.get_tree_settings(), config.get_forest_settings(), *config.get_seed())?))
+        });
+        registry.register("boosting", |config, passengers| {
+            Ok(TrainedLearner::Boosting(GradientBoosting::fit(passengers, config.get_features(), config.get_boosting_settings(), *config.get_seed())?))
+        });
+        registry.register("bayes", |config, passengers| {
+            Ok(TrainedLearner::Bayes(NaiveBayes::fit(passengers, config.get_features(), config.get_bayes_settings())?))
+        });
+        registry.register("knn", |config, passengers| {
+            Ok(TrainedLearner::Knn(NearestNeighbours::fit(passengers, config.get_features(), config.get_knn_settings())?))
+        });
+        registry.register("svm", |config, passengers| {
+            Ok(TrainedLearner::Svm(LinearSvm::fit(passengers, config.get_features(), config.get_svm_settings(), *config.get_seed())?))
+        });
+        registry.register("mlp", |config, passengers| {
+            Ok(TrainedLearner::Mlp(Mlp::fit(passengers, config.get_features(), config.get_mlp_settings(), *config.get_seed())?))
+        });
+        registry.register("ensemble", |config, passengers| {
+            Ok(TrainedLearner::Ensemble(Ensemble::fit(config, passengers)?))
+        });
+        registry
+    }
+}
+
+impl Registry {
+    // Adds a learner under name, or replaces the way an existing name is fitted.
+    pub fn register<F>(&mut self, name: &str, fit: F)
+    where
+        F: Fn(&Config, &[TrainingPassenger]) -> Result<TrainedLearner, String> + 'static,
+    {
+        let entry = RegistryEntry { name: name.to_string(), fit: Rc::new(fit) };
+        match self.entries.iter_mut().find(|other| other.name == name) {
+            Some(other) => *other = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RegistryEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    // An unfitted Classifier for the learner registered under name, fitted with the settings of config.
+    pub fn classifier<'a>(&self, name: &str, config: &'a Config) -> Result<Estimator<'a>, String> {
+        match self.get(name) {
+            Some(entry) => Ok(Estimator { config, fit: Rc::clone(&entry.fit), fitted: None }),
+            None => Err(format!("Registry::classifier unknown learner {}, expected one of {}", name, self.names().join(", "))),
+        }
+    }
+}
+
+// A learner from the Registry as a Classifier, holding the configuration it is fitted with until fit is called.
+#[derive(Clone)]
+pub struct Estimator<'a> {
+    config: &'a Config,
+    fit: FitLearner,
+    fitted: Option<TrainedLearner>,
+}
+
+impl<'a> Estimator<'a> {
+    pub fn get_fitted(&self) -> &Option<TrainedLearner> {
+        &self.fitted
+    }
+
+    pub fn into_fitted(self) -> Result<TrainedLearner, String> {
+        self.fitted.ok_or_else(|| "Estimator::into_fitted the learner has not been fitted".to_string())
+    }
+}
+
+impl<'a> Classifier<TrainingPassenger> for Estimator<'a> {
+    fn fit(&mut self, records: &[TrainingPassenger]) -> Result<(), String> {
+        self.fitted = Some((self.fit)(self.config, records)?);
+        Ok(())
+    }
+
+    fn predict_proba(&self, record: &TrainingPassenger) -> Result<f64, String> {
+        match &self.fitted {
+            Some(learner) => learner.predict_proba(record),
+            None => Err("Estimator::predict_proba the learner has not been fitted".to_string()),
+        }
+    }
+}
+
+// Everything needed to score new passengers, together with the seed the weights were fitted with.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Model {
+    seed: u64,
+    threshold: f64,
+    calibrator: Option<Calibrator>,
+    learner: TrainedLearner,
+}
+
+impl Model {
+    pub fn new(seed: u64, threshold: f64, calibrator: Option<Calibrator>, learner: TrainedLearner) -> Model {
+        Model {
+            seed,
+            threshold,
+            calibrator,
+            learner,
+        }
+    }
+
+    pub fn get_seed(&self) -> &u64 {
+        &self.seed
+    }
+
+    pub fn get_threshold(&self) -> &f64 {
+        &self.threshold
+    }
+
+    pub fn get_calibrator(&self) -> &Option<Calibrator> {
+        &self.calibrator
+    }
+
+    pub fn get_learner(&self) -> &TrainedLearner {
+        &self.learner
+    }
+    
+    // Only logistic models have weights, e.g. to warm start from.
+    pub fn get_weights(&self) -> Result<&PassengerWeights, String> {
+        match &self.learner {
+            TrainedLearner::Logistic(weights) => Ok(weights),
+            other => Err(format!("Model::get_weights a {} model has no logistic weights", other.get_learner())),
+        }
+    }
+
+    // Probability of BinaryClass::Yes after calibration
+    pub fn probability<R>(&self, record: &R) -> Result<f64, String>
+    where
+        R: PassengerFeatures + classification::LogisticBinaryClassificationTestable<Weights = PassengerWeights>,
+    {
+        let probability = self.learner.predict_proba(record)?;
+        Ok(match &self.calibrator {
+            Some(calibrator) => calibrator.calibrate(probability),
+            None => probability,
+        })
+    }
+
+    pub fn predict(&self, passenger: &Passenger) -> Result<Outcome, String> {
+        Ok(classification::classify(*passenger.get_passenger_id(), self.probability(passenger)?, &self.threshold))
+    }
+
+    // A 64 bit FNV-1a hash of the model as JSON, in hexadecimal, naming the model that made a set of predictions. The
+    // same model, as read back from its --model file, always gets the same id.
+    pub fn id(&self) -> Result<String, String> {
+        let json = serde_json::to_string(self).map_err(|e1| format!("Model::id Failed to serialize Model. Serde: {}", e1))?;
+        let hash = json.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3));
+        Ok(format!("{:016x}", hash))
+    }
+
+    pub fn read(filename: &str) -> Result<Model, String> {
+        match fs::read_to_string(filename) {
+            Ok(contents) => match serde_json::from_str(&contents) {
+                Ok(model) => {
+                    eprintln!("Model::read Loaded model from {}", filename);
+                    Ok(model)
+                },
+                Err(e2) => Err(format!("Model::read Failed to parse {}. Serde: {}", filename, e2)),
+            },
+            Err(e1) => Err(format!("Model::read Failed to read {}. {}", filename, e1)),
+        }
+    }
+
+    pub fn write(&self, filename: &str) -> Result<(), String> {
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => {
+                if let Err(e2) = fs::write(filename, json) {
+                    let message = format!("Model::write Failed to write {}. {}", filename, e2);
+                    return Err(message)
+                }
+                eprintln!("Model::write Completed writing the model fitted with seed {} to {}", self.seed, filename);
+                Ok(())
+            },
+            Err(e1) => {
+                let message = format!("Model::write Failed to serialize Model. Serde: {}", e1);
+                Err(message)
+            },
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use super::*;
+use std::env;
+
+use crate::test_support::training_passengers;
+use crate::classification::BinaryClass;
+use crate::{fit, Config};
+
+fn config(seed: &str) -> Config {
+    let args = vec!["first".to_string(), "0.00001".to_string(), "0.01".to_string(), "fourth".to_string(), "fifth".to_string(), "sixth".to_string(),
+        "--optimizer".to_string(), "online".to_string(), "--select-threshold".to_string(), "accuracy".to_string(), "--max-iterations".to_string(), "20".to_string(),
+        "--seed".to_string(), seed.to_string()];
+    Config::new(&args).unwrap()
+}
+
+#[test]
+fn when_same_seed_then_identical_weights() {
+    let first = fit(&config("7"), training_passengers()).unwrap();
+    let second = fit(&config("7"), training_passengers()).unwrap();
+    let other = fit(&config("8"), training_passengers()).unwrap();
+
+    assert_eq!(*first.get_seed(), 7_u64);
+    assert_eq!(first, second);
+    assert_ne!(first.get_weights().unwrap(), other.get_weights().unwrap());
+}
+
+#[test]
+fn when_model_written_then_reading_it_gives_same_model() {
+    let model = fit(&config("7"), training_passengers()).unwrap();
+    let filename = env::temp_dir().join(format!("kaggle_c_titanic_{}_model.json", std::process::id())).to_string_lossy().into_owned();
+    model.write(&filename).unwrap();
+    let read = Model::read(&filename).unwrap();
+    fs::remove_file(&filename).unwrap();
+
+    assert_eq!(read, model);
+}
+
+#[test]
+fn when_tree_learner_then_model_holds_a_tree_within_limits() {
+    let args = vec!["first".to_string(), "0.00001".to_string(), "0.01".to_string(), "fourth".to_string(), "fifth".to_string(), "sixth".to_string(),
+        "--learner".to_string(), "tree".to_string(), "--criterion".to_string(), "entropy".to_string(), "--max-depth".to_string(), "2".to_string(),
+        "--calibration".to_string(), "platt".to_string()];
+    let model = fit(&Config::new(&args).unwrap(), training_passengers()).unwrap();
+
+    match model.get_learner() {
+        TrainedLearner::Tree(tree) => {
+            assert!(tree.get_depth() <= 2);
+            assert_eq!(tree.get_settings().criterion, classification::tree::Criterion::Entropy);
+        },
+        _other => panic!("model::tests::when_tree_learner_then_model_holds_a_tree_within_limits fitted logistic weights"),
+    }
+    assert!(model.get_calibrator().is_some());
+    assert!(model.get_weights().is_err());
+}
+
+#[test]
+fn when_logistic_regression_fitted_then_same_weights_as_solver() {
+    let config = config("7");
+    let passengers = training_passengers();
+    let mut weights = config.new_weights(config.get_features()).unwrap();
+    classification::solve_with_settings(&passengers, &mut weights, &config.get_solver_settings()).unwrap();
+    let mut logistic = LogisticRegression::new(config.new_weights(config.get_features()).unwrap(), config.get_solver_settings());
+    logistic.fit(&passengers).unwrap();
+
+    assert_eq!(*logistic.get_weights(), weights);
+    for passenger in &passengers {
+        let probability = logistic.predict_proba(passenger).unwrap();
+        for threshold in [0.25_f64, 0.5_f64, 0.75_f64].iter() {
+            assert_eq!(logistic.predict(passenger, threshold).unwrap() == BinaryClass::Yes, probability > *threshold);
+        }
+    }
+}
+
+fn refitted<C: Classifier<TrainingPassenger> + Clone>(classifier: &C, passengers: &[TrainingPassenger]) -> C {
+    let mut refitted = classifier.clone();
+    refitted.fit(passengers).unwrap();
+    refitted
+}
+
+#[test]
+fn when_learner_refitted_as_classifier_then_same_learner() {
+    let config = config("7");
+    let passengers = training_passengers();
+    let registry = Registry::default();
+
+    for name in ["tree", "forest", "boosting", "bayes", "knn", "svm", "mlp"].iter() {
+        let learner = (registry.get(name).unwrap().fit)(&config, &passengers).unwrap();
+        let refitted = match &learner {
+            TrainedLearner::Tree(tree) => TrainedLearner::Tree(refitted(tree, &passengers)),
+            TrainedLearner::Forest(forest) => TrainedLearner::Forest(refitted(forest, &passengers)),
+            TrainedLearner::Boosting(boosting) => TrainedLearner::Boosting(refitted(boosting, &passengers)),
+            TrainedLearner::Bayes(bayes) => TrainedLearner::Bayes(refitted(bayes, &passengers)),
+            TrainedLearner::Knn(knn) => TrainedLearner::Knn(refitted(knn, &passengers)),
+            TrainedLearner::Svm(svm) => TrainedLearner::Svm(refitted(svm, &passengers)),
+            TrainedLearner::Mlp(mlp) => TrainedLearner::Mlp(refitted(mlp, &passengers)),
+            other => panic!("{} is not a single learner", other.get_learner()),
+        };
+
+        assert_eq!(refitted, learner, "{}", name);
+    }
+}
+
+#[test]
+fn when_learner_named_then_registry_fits_it() {
+    let registry = Registry::default();
+    assert_eq!(registry.names(), vec!["logistic", "tree", "forest", "boosting", "bayes", "knn", "svm", "mlp", "ensemble"]);
+    assert!(registry.classifier("perceptron", &config("7")).is_err());
+    assert!("perceptron".parse::<Learner>().is_err());
+
+    let args = vec!["first".to_string(), "0.00001".to_string(), "0.01".to_string(), "fourth".to_string(), "fifth".to_string(), "sixth".to_string(),
+        "--learner".to_string(), "bayes".to_string()];
+    let config = Config::new(&args).unwrap();
+    let passengers = training_passengers();
+    let mut classifier = registry.classifier("bayes", &config).unwrap();
+    assert!(classifier.predict_proba(&passengers[0]).is_err());
+    classifier.fit(&passengers).unwrap();
+
+    let model = fit(&config, passengers.clone()).unwrap();
+    assert_eq!(classifier.into_fitted().unwrap(), *model.get_learner());
+}
@@ -0,0 +1,160 @@
+use csv::StringRecord;
+use std::fs;
+use std::ops::{Div, Mul};
+use std::time::Instant;
+
+use crate::data::{PredictionWriter, RowReader, STANDARD_STREAM};
+use crate::explain::{self, Explain};
+use crate::schema::Column;
+use crate::submission;
+use crate::validation::Validator;
+use crate::{Config, Model, Passenger, TestedPassenger};
+
+#[cfg(test)]
+mod tests;
+
+// Reports how far a stream has got every so many rows, as the share of the file read so far when its size is known.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    every: usize,
+    total: u64,
+    rows: usize,
+    started: Instant,
+}
+
+impl Progress {
+    // every of 0 reports nothing, and a total of 0 is unknown.
+    pub fn new(every: usize, total: u64) -> Progress {
+        Progress { every, total, rows: 0, started: Instant::now() }
+    }
+
+    pub fn get_rows(&self) -> &usize {
+        &self.rows
+    }
+
+    pub fn tick(&mut self, done: u64) {
+        self.rows += 1;
+        if self.every > 0 && self.rows.is_multiple_of(self.every) {
+            eprintln!("predict::stream {}", self.status(done));
+        }
+    }
+
+    pub fn status(&self, done: u64) -> String {
+        let seconds = self.started.elapsed().as_secs_f64();
+        // quick_convert counts up one at a time, which is too slow for counts in the millions.
+        let rate = if seconds > 0_f64 { (self.rows as f64).div(seconds) } else { 0_f64 };
+        if self.total > 0 {
+            let percent = (done as f64).div(self.total as f64).mul(100_f64);
+            format!("{} rows ({:.1}% of the file) in {:.1}s, {:.0} rows/s", self.rows, percent, seconds, rate)
+        } else {
+            format!("{} rows in {:.1}s, {:.0} rows/s", self.rows, seconds, rate)
+        }
+    }
+}
+
+// Reads, checks, scores and writes the test passengers one row at a time, so memory stays the same however long the
+// input is. The test data and output may be files of any format or the standard streams. The problems found go to the
+// validation report, after those of the training file when append is set. Strictly, the first row with a problem stops
+// the stream. Returns the number of passengers written.
+pub fn stream(config: &Config, model: &Model, append: bool) -> Result<usize, String> {
+    let filename = config.get_test_data_filename();
+    let mut reader = RowReader::open(filename, config.get_input_format())?;
+    let mut validator = Validator::new(filename, reader.get_headers(), config.get_schema(), &[Column::PassengerId], *config.get_lenient())?;
+    // A Kaggle submission is kept until it has been checked, which its 418 rows make cheap.
+    let kaggle = *config.get_kaggle();
+    if kaggle {
+        submission::check_kaggle_config(config)?;
+    }
+    let model_id = if *config.get_extended_output() { model.id()? } else { String::new() };
+    let mut writer = if kaggle { None } else { Some(PredictionWriter::create(config.get_output_filename(), config.get_output_format())?) };
+    let mut kaggle_passengers = Vec::new();
+    let mut test_ids = Vec::new();
+    let mut progress = Progress::new(*config.get_progress_every(), reader.progress().1);
+    let mut row = StringRecord::new();
+    let mut written = 0_usize;
+    let result = loop {
+        match reader.read_row(&mut row) {
+            Ok(true) => (),
+            Ok(false) => break Ok(()),
+            Err(e1) => break Err(e1),
+        }
+        if kaggle {
+            test_ids.push(validator.get_binding().value(&row, &Column::PassengerId).unwrap_or("").to_string());
+        }
+        let passenger: Passenger = match validator.check(&row) {
+            Ok(Some(passenger)) => passenger,
+            Ok(None) if *config.get_lenient() => {
+                progress.tick(reader.progress().0);
+                continue
+            },
+            Ok(None) => break Ok(()),
+            Err(e2) => break Err(e2),
+        };
+        let outcome = match model.predict(&passenger) {
+            Ok(outcome) => outcome,
+            Err(e3) => break Err(e3),
+        };
+        let mut tested_passenger = if *config.get_extended_output() {
+            let top_features = match explain::feature_effects(model, &passenger) {
+                Ok(effects) => explain::format_effects(&passenger, &effects, *config.get_top_features()),
+                Err(e4) => break Err(e4),
+            };
+            TestedPassenger::extended(outcome, top_features, model_id.clone())
+        } else if *config.get_write_probability() {
+            TestedPassenger::with_probability(outcome)
+        } else {
+            TestedPassenger::new(outcome)
+        };
+        if *config.get_write_contributions() {
+            tested_passenger = match model.explain(&passenger) {
+                Ok(explanation) => tested_passenger.with_contributions(explanation.format(&passenger)),
+                Err(e5) => break Err(e5),
+            };
+        }
+        match &mut writer {
+            Some(writer) => if let Err(e6) = writer.write(&tested_passenger) {
+                break Err(e6)
+            },
+            None => kaggle_passengers.push(tested_passenger),
+        }
+        written += 1;
+        progress.tick(reader.progress().0);
+    };
+    let result = match &mut writer {
+        Some(writer) => result.and(writer.flush()),
+        None => result,
+    };
+    let report = validator.into_report();
+    if let Some(report_filename) = config.get_validation_report_filename() {
+        report.write(report_filename, append)?;
+    }
+    // A stream stopped by an error removes what it wrote, so no truncated predictions are left behind.
+    if let Err(e7) = result.and_then(|()| report.ensure_valid(filename)) {
+        let output_filename = config.get_output_filename();
+        if writer.take().is_some() && output_filename != STANDARD_STREAM {
+            if let Err(e8) = fs::remove_file(output_filename) {
+                return Err(format!("{} predict::stream Failed to remove the incomplete {}. {}", e7, output_filename, e8))
+            }
+        }
+        return Err(e7)
+    }
+    if kaggle {
+        submission::write_kaggle(config.get_output_filename(), &kaggle_passengers, &test_ids)?;
+    }
+    eprintln!("predict::stream Completed writing {} test results to {} after {}", written, config.get_output_filename(), progress.status(reader.progress().0));
+    Ok(written)
+}
+
+// Scores the test file with a model saved by an earlier run, without training.
+pub fn run(config: &Config) -> Result<(), String> {
+    let filename = match config.get_model_filename() {
+        Some(filename) => filename,
+        None => return Err("predict::run needs the --model to predict with".to_string()),
+    };
+    if config.get_test_data_filename().is_empty() || config.get_output_filename().is_empty() {
+        return Err("predict::run needs the --test-data to score and the --output to write".to_string())
+    }
+    let model = Model::read(filename)?;
+    stream(config, &model, false)?;
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use super::*;
+use std::env;
+use std::fs;
+
+use crate::classification::BinaryClass;
+use crate::fit;
+use crate::test_support::training_passengers;
+
+fn temp_filename(name: &str) -> String {
+    env::temp_dir().join(format!("kaggle_c_titanic_predict_{}_{}", std::process::id(), name)).to_string_lossy().into_owned()
+}
+
+fn config(test_filename: &str, output_filename: &str, options: &[&str]) -> Config {
+    let mut args: Vec<String> = ["first", "0.00001", "0.01", "fourth", test_filename, output_filename, "--learner", "bayes", "--progress-every", "2"].iter().map(|arg| arg.to_string()).collect();
+    args.extend(options.iter().map(|option| option.to_string()));
+    Config::new(&args).unwrap()
+}
+
+fn test_file(name: &str, rows: usize) -> String {
+    let filename = temp_filename(name);
+    let mut contents = "PassengerId,Pclass,Sex,Age,Fare\n".to_string();
+    for index in 0..rows {
+        contents.push_str(&format!("{},{},{},{},{}\n", 100 + index, 1 + index % 3, if index % 2 == 0 { "female" } else { "male" }, 20 + index % 30, 7 + index % 11));
+    }
+    fs::write(&filename, contents).unwrap();
+    filename
+}
+
+#[test]
+fn when_streamed_then_every_test_passenger_predicted_by_model() {
+    let test_filename = test_file("stream.csv", 25);
+    let output_filename = temp_filename("stream_output.csv");
+    let config = config(&test_filename, &output_filename, &["--probability"]);
+    let model = fit(&config, training_passengers()).unwrap();
+
+    let written = stream(&config, &model, false).unwrap();
+    let test_passengers = config.get_test_passengers().unwrap();
+    let output = fs::read_to_string(&output_filename).unwrap();
+    fs::remove_file(&test_filename).unwrap();
+    fs::remove_file(&output_filename).unwrap();
+
+    assert_eq!(written, 25);
+    let mut lines = output.lines();
+    assert_eq!(lines.next(), Some("PassengerId,Survived,Probability"));
+    assert_eq!(lines.clone().count(), 25);
+    for (line, passenger) in lines.zip(&test_passengers) {
+        let outcome = model.predict(passenger).unwrap();
+        let fields: Vec<&str> = line.split(',').collect();
+        assert_eq!(fields[0], passenger.get_passenger_id().to_string());
+        assert_eq!(fields[1], if outcome.prediction == BinaryClass::Yes { "1" } else { "0" });
+        assert_abs_diff_eq!(fields[2].parse::<f64>().unwrap(), outcome.probability, epsilon = 1e-12);
+    }
+}
+
+#[test]
+fn when_streamed_as_jsonl_with_contributions_then_every_line_keeps_them() {
+    let test_filename = test_file("stream_jsonl.csv", 5);
+    let output_filename = temp_filename("stream_output.jsonl");
+    let config = config(&test_filename, &output_filename, &["--contributions"]);
+    let model = fit(&config, training_passengers()).unwrap();
+
+    let written = stream(&config, &model, false).unwrap();
+    let test_passengers = config.get_test_passengers().unwrap();
+    let output = fs::read_to_string(&output_filename).unwrap();
+    fs::remove_file(&test_filename).unwrap();
+    fs::remove_file(&output_filename).unwrap();
+
+    assert_eq!(written, 5);
+    assert_eq!(output.lines().count(), 5);
+    for (line, passenger) in output.lines().zip(&test_passengers) {
+        let prediction: serde_json::Value = serde_json::from_str(line).unwrap();
+        let explanation = model.explain(passenger).unwrap();
+        assert_eq!(prediction["PassengerId"], *passenger.get_passenger_id());
+        assert_eq!(prediction["Contributions"], explanation.format(passenger));
+    }
+}
+
+#[test]
+fn when_strict_and_bad_row_then_stream_stops_with_error() {
+    let test_filename = temp_filename("bad.csv");
+    fs::write(&test_filename, "PassengerId,Pclass,Sex\n1,1,female\n2,7,male\n3,3,male\n").unwrap();
+    let output_filename = temp_filename("bad_output.csv");
+    let model = fit(&config(&test_filename, &output_filename, &[]), training_passengers()).unwrap();
+
+    let error = stream(&config(&test_filename, &output_filename, &[]), &model, false).unwrap_err();
+    let written = stream(&config(&test_filename, &output_filename, &["--lenient"]), &model, false).unwrap();
+    let output = fs::read_to_string(&output_filename).unwrap();
+    fs::remove_file(&test_filename).unwrap();
+    fs::remove_file(&output_filename).unwrap();
+
+    assert!(error.contains("line 3 (PassengerId 2) column Pclass value '7'"), "{}", error);
+    assert_eq!(written, 3);
+    assert_eq!(output.lines().count(), 4);
+}
+
+#[test]
+fn when_strict_stream_fails_then_no_output_file_left() {
+    let test_filename = temp_filename("bad_last.csv");
+    fs::write(&test_filename, "PassengerId,Pclass,Sex\n1,1,female\n2,3,male\n3,7,male\n").unwrap();
+    let output_filename = temp_filename("bad_last_output.csv");
+    let model = fit(&config(&test_filename, &output_filename, &[]), training_passengers()).unwrap();
+
+    let error = stream(&config(&test_filename, &output_filename, &[]), &model, false).unwrap_err();
+    let left_behind = fs::metadata(&output_filename).is_ok();
+    fs::remove_file(&test_filename).unwrap();
+
+    assert!(error.contains("line 4 (PassengerId 3)"), "{}", error);
+    assert!(!left_behind);
+}
+
+#[test]
+fn when_progress_ticked_then_rows_and_share_of_file_counted() {
+    let mut progress = Progress::new(0, 200);
+    for _row in 0..5 {
+        progress.tick(40);
+    }
+
+    assert_eq!(*progress.get_rows(), 5);
+    assert!(progress.status(50).starts_with("5 rows (25.0% of the file)"), "{}", progress.status(50));
+}
@@ -0,0 +1,111 @@
+use std::ops::{Add, Div, Mul};
+
+use crate::classification::{quick_convert, BinaryClass, Labelled};
+use crate::{Config, Feature, FeatureValue, PassengerFeatures};
+
+#[cfg(test)]
+mod tests;
+
+// Age and fare are cut into this many bins holding about as many passengers each.
+pub const REPORT_BINS: usize = 5;
+
+// The passengers sharing one value of a feature, or one range of a continuous feature
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConditionalRow {
+    pub value: String,
+    pub passengers: usize,
+    pub survived: usize,
+}
+
+impl ConditionalRow {
+    // P(Survived | value)
+    pub fn survival_rate(&self) -> f64 {
+        quick_convert(&self.survived).div(quick_convert(&self.passengers))
+    }
+}
+
+// Position of a value in a table: missing first, then categories and counts in increasing order.
+fn sort_key(value: &FeatureValue) -> f64 {
+    match value {
+        FeatureValue::Missing => f64::NEG_INFINITY,
+        FeatureValue::Present => 1_f64,
+        FeatureValue::Category(category) => quick_convert(category),
+        FeatureValue::Number(number) => *number,
+    }
+}
+
+fn survived(label: &BinaryClass) -> usize {
+    match label {
+        BinaryClass::Yes => 1,
+        BinaryClass::No => 0,
+    }
+}
+
+// One row per value of a discrete feature, or per quantile bin of a continuous feature, plus a row for missing values.
+pub fn conditional_table<R: PassengerFeatures + Labelled>(records: &[R], feature: &Feature, bins: usize) -> Vec<ConditionalRow> {
+    let mut groups: Vec<(FeatureValue, usize, usize)> = Vec::new();
+    let mut numbers = Vec::new();
+    for record in records {
+        let value = record.get_feature(feature);
+        let label = record.label();
+        if let (true, FeatureValue::Number(number)) = (feature.is_continuous(), &value) {
+            numbers.push((*number, label));
+            continue
+        }
+        match groups.iter_mut().find(|(group, _passengers, _survived)| *group == value) {
+            Some(group) => {
+                group.1 = group.1.add(1);
+                group.2 = group.2.add(survived(&label));
+            },
+            None => groups.push((value, 1, survived(&label))),
+        }
+    }
+    groups.sort_by(|a, b| sort_key(&a.0).partial_cmp(&sort_key(&b.0)).unwrap_or(std::cmp::Ordering::Equal));
+    let mut rows: Vec<ConditionalRow> = groups.into_iter().map(|(value, passengers, survived)| ConditionalRow {
+        value: feature.format_value(&value),
+        passengers,
+        survived,
+    }).collect();
+
+    numbers.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    let mut start = 0;
+    for bin in 1..=bins.max(1) {
+        // Equal numbers stay in the same bin, so a bin may end up larger than its share or empty.
+        let mut end = numbers.len().mul(bin).div(bins.max(1)).max(start);
+        while end > 0 && end < numbers.len() && numbers[end].0 <= numbers[end - 1].0 {
+            end = end.add(1);
+        }
+        if end > start {
+            rows.push(ConditionalRow {
+                value: format!("{} to {}", numbers[start].0, numbers[end - 1].0),
+                passengers: end - start,
+                survived: numbers[start..end].iter().map(|(_number, label)| survived(label)).sum(),
+            });
+        }
+        start = end;
+    }
+    rows
+}
+
+fn print_table(feature: &Feature, rows: &[ConditionalRow]) {
+    println!();
+    println!("P(Survived | {})", feature.get_name());
+    println!("{:>20} {:>10} {:>10} {:>12}", "value", "passengers", "survived", "P(Survived)");
+    for row in rows {
+        println!("{:>20} {:>10} {:>10} {:>12.4}", row.value, row.passengers, row.survived, row.survival_rate());
+    }
+}
+
+// Prints the share of the training passengers that survived given each value of the configured features.
+pub fn run(config: &Config) -> Result<(), String> {
+    let training_passengers = config.get_training_passengers()?;
+    if training_passengers.is_empty() {
+        return Err("report::run needs at least one training passenger".to_string())
+    }
+    let survivors = training_passengers.iter().map(|passenger| survived(&passenger.label())).sum::<usize>();
+    println!("report::run {} of {} training passengers survived, P(Survived) = {:.4}", survivors, training_passengers.len(), quick_convert(&survivors).div(quick_convert(&training_passengers.len())));
+    for feature in config.get_features() {
+        print_table(feature, &conditional_table(&training_passengers, feature, REPORT_BINS));
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use super::*;
+use crate::test_support::passengers;
+
+fn row(value: &str, passengers: usize, survived: usize) -> ConditionalRow {
+    ConditionalRow { value: value.to_string(), passengers, survived }
+}
+
+#[test]
+fn when_discrete_feature_then_one_row_per_value_missing_first() {
+    let records = passengers(&[
+        "1,1,1,A,female,30,1,0,T,10,,S",
+        "2,1,3,B,female,40,0,0,T,10,,S",
+        "3,0,3,C,male,30,2,0,T,10,,S",
+        "4,0,,D,male,40,0,0,T,10,,S",
+        "5,1,1,E,male,40,10,0,T,10,,S",
+    ]);
+
+    assert_eq!(conditional_table(&records, &Feature::PassengerClass, REPORT_BINS), vec![row("missing", 1, 0), row("first", 2, 2), row("third", 2, 1)]);
+    assert_eq!(conditional_table(&records, &Feature::SiblingsSpouses, REPORT_BINS), vec![row("0", 2, 1), row("1", 1, 1), row("2", 1, 0), row("10", 1, 1)]);
+    assert_abs_diff_eq!(conditional_table(&records, &Feature::Sex, REPORT_BINS)[1].survival_rate(), 1_f64 / 3_f64);
+}
+
+#[test]
+fn when_continuous_feature_then_quantile_bins_keep_ties_together() {
+    let records = passengers(&[
+        "1,1,3,A,male,5,0,0,T,10,,S",
+        "2,1,3,B,male,10,0,0,T,10,,S",
+        "3,0,3,C,male,10,0,0,T,10,,S",
+        "4,0,3,D,male,30,0,0,T,10,,S",
+        "5,0,3,E,male,,0,0,T,10,,S",
+    ]);
+
+    assert_eq!(conditional_table(&records, &Feature::Age, 2), vec![row("missing", 1, 0), row("5 to 10", 3, 2), row("30 to 30", 1, 0)]);
+}
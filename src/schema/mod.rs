@@ -0,0 +1,237 @@
+use csv::{Reader, StringRecord};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests;
+
+// The fields of a passenger that can be read from a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    PassengerId,
+    Survived,
+    PassengerClass,
+    Name,
+    Sex,
+    Age,
+    SiblingsSpouses,
+    ParentsChildren,
+    Ticket,
+    Fare,
+    Cabin,
+    PortOfEmbarkation,
+}
+
+impl Column {
+    pub const ALL: [Column; 12] = [Column::PassengerId, Column::Survived, Column::PassengerClass, Column::Name, Column::Sex, Column::Age, Column::SiblingsSpouses, Column::ParentsChildren, Column::Ticket, Column::Fare, Column::Cabin, Column::PortOfEmbarkation];
+
+    // The header of the Kaggle files, which TrainingPassenger and Passenger are deserialized from
+    pub fn canonical(&self) -> &'static str {
+        match self {
+            Column::PassengerId => "PassengerId",
+            Column::Survived => "Survived",
+            Column::PassengerClass => "Pclass",
+            Column::Name => "Name",
+            Column::Sex => "Sex",
+            Column::Age => "Age",
+            Column::SiblingsSpouses => "SibSp",
+            Column::ParentsChildren => "Parch",
+            Column::Ticket => "Ticket",
+            Column::Fare => "Fare",
+            Column::Cabin => "Cabin",
+            Column::PortOfEmbarkation => "Embarked",
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Column, String> {
+        match Column::ALL.iter().find(|column| column.to_string() == name) {
+            Some(column) => Ok(*column),
+            None => Err(format!("Column::from_str unknown column {}, expected one of {}", name, Column::ALL.iter().map(|column| column.to_string()).collect::<Vec<String>>().join(", "))),
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Column::PassengerId => write!(f, "passenger_id"),
+            Column::Survived => write!(f, "survived"),
+            Column::PassengerClass => write!(f, "passenger_class"),
+            Column::Name => write!(f, "name"),
+            Column::Sex => write!(f, "sex"),
+            Column::Age => write!(f, "age"),
+            Column::SiblingsSpouses => write!(f, "siblings_spouses"),
+            Column::ParentsChildren => write!(f, "parents_children"),
+            Column::Ticket => write!(f, "ticket"),
+            Column::Fare => write!(f, "fare"),
+            Column::Cabin => write!(f, "cabin"),
+            Column::PortOfEmbarkation => write!(f, "port_of_embarkation"),
+        }
+    }
+}
+
+// The header of the column each field is read from. Missing keys keep the Kaggle header when the schema is read from a
+// configuration file.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Schema {
+    pub passenger_id: String,
+    pub survived: String,
+    pub passenger_class: String,
+    pub name: String,
+    pub sex: String,
+    pub age: String,
+    pub siblings_spouses: String,
+    pub parents_children: String,
+    pub ticket: String,
+    pub fare: String,
+    pub cabin: String,
+    pub port_of_embarkation: String,
+}
+
+impl Default for Schema {
+    fn default() -> Self {
+        Schema {
+            passenger_id: Column::PassengerId.canonical().to_string(),
+            survived: Column::Survived.canonical().to_string(),
+            passenger_class: Column::PassengerClass.canonical().to_string(),
+            name: Column::Name.canonical().to_string(),
+            sex: Column::Sex.canonical().to_string(),
+            age: Column::Age.canonical().to_string(),
+            siblings_spouses: Column::SiblingsSpouses.canonical().to_string(),
+            parents_children: Column::ParentsChildren.canonical().to_string(),
+            ticket: Column::Ticket.canonical().to_string(),
+            fare: Column::Fare.canonical().to_string(),
+            cabin: Column::Cabin.canonical().to_string(),
+            port_of_embarkation: Column::PortOfEmbarkation.canonical().to_string(),
+        }
+    }
+}
+
+impl Schema {
+    fn header_mut(&mut self, column: &Column) -> &mut String {
+        match column {
+            Column::PassengerId => &mut self.passenger_id,
+            Column::Survived => &mut self.survived,
+            Column::PassengerClass => &mut self.passenger_class,
+            Column::Name => &mut self.name,
+            Column::Sex => &mut self.sex,
+            Column::Age => &mut self.age,
+            Column::SiblingsSpouses => &mut self.siblings_spouses,
+            Column::ParentsChildren => &mut self.parents_children,
+            Column::Ticket => &mut self.ticket,
+            Column::Fare => &mut self.fare,
+            Column::Cabin => &mut self.cabin,
+            Column::PortOfEmbarkation => &mut self.port_of_embarkation,
+        }
+    }
+
+    pub fn header(&self, column: &Column) -> &str {
+        match column {
+            Column::PassengerId => &self.passenger_id,
+            Column::Survived => &self.survived,
+            Column::PassengerClass => &self.passenger_class,
+            Column::Name => &self.name,
+            Column::Sex => &self.sex,
+            Column::Age => &self.age,
+            Column::SiblingsSpouses => &self.siblings_spouses,
+            Column::ParentsChildren => &self.parents_children,
+            Column::Ticket => &self.ticket,
+            Column::Fare => &self.fare,
+            Column::Cabin => &self.cabin,
+            Column::PortOfEmbarkation => &self.port_of_embarkation,
+        }
+    }
+
+    // Sets the header of a field from "field=header", as given to --column.
+    pub fn set_header(&mut self, assignment: &str) -> Result<(), String> {
+        match assignment.split_once('=') {
+            Some((field, header)) if !header.is_empty() => {
+                *self.header_mut(&field.parse()?) = header.to_string();
+                Ok(())
+            },
+            _ => Err(format!("Schema::set_header expected field=header, got {}", assignment)),
+        }
+    }
+
+    // Finds the column of every field among headers: the one with exactly its header, or else the one that differs
+    // only in case. Other columns are ignored, and fields other than required may be left without a column.
+    pub fn bind(&self, headers: &StringRecord, required: &[Column]) -> Result<Binding, String> {
+        let mut columns = Vec::new();
+        let mut missing = Vec::new();
+        for column in Column::ALL.iter() {
+            let header = self.header(column);
+            let position = headers.iter().position(|other| other == header).or_else(|| headers.iter().position(|other| other.eq_ignore_ascii_case(header)));
+            match position {
+                Some(index) => columns.push((*column, index)),
+                None if required.contains(column) => missing.push(format!("{} ({})", header, column)),
+                None => (),
+            }
+        }
+        if !missing.is_empty() {
+            return Err(format!("Schema::bind missing the required columns {}, found columns {}", missing.join(", "), headers.iter().collect::<Vec<&str>>().join(", ")))
+        }
+        Ok(Binding {
+            canonical: columns.iter().map(|(column, _index)| column.canonical()).collect(),
+            headers: headers.clone(),
+            columns,
+        })
+    }
+}
+
+// The columns of one file that the fields are read from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    columns: Vec<(Column, usize)>,
+    // The canonical headers of the bound fields, in the order of columns
+    canonical: StringRecord,
+    // The headers of the file
+    headers: StringRecord,
+}
+
+impl Binding {
+    pub fn get_columns(&self) -> &Vec<(Column, usize)> {
+        &self.columns
+    }
+
+    // The value of the column bound to column in a row of the file, None when the file has no such column
+    pub fn value<'a>(&self, row: &'a StringRecord, column: &Column) -> Option<&'a str> {
+        self.columns.iter().find(|(other, _index)| other == column).and_then(|(_column, index)| row.get(*index))
+    }
+
+    // Deserializes a row of the file as if it had the canonical headers, naming the row and column of any error.
+    pub fn deserialize<T: DeserializeOwned>(&self, row: &StringRecord) -> Result<T, String> {
+        let canonical_row: StringRecord = self.columns.iter().map(|(_column, index)| row.get(*index).unwrap_or("")).collect();
+        canonical_row.deserialize(Some(&self.canonical)).map_err(|e1| {
+            let line = row.position().map_or(0, |position| position.line());
+            let column = match e1.kind() {
+                csv::ErrorKind::Deserialize { err, .. } => err.field().and_then(|field| self.columns.get(field as usize)).and_then(|(_column, index)| self.headers.get(*index)),
+                _ => None,
+            };
+            match column {
+                Some(header) => format!("Binding::deserialize Failed to read column {} on line {}. Csv: {}", header, line, e1),
+                None => format!("Binding::deserialize Failed to read line {}. Csv: {}", line, e1),
+            }
+        })
+    }
+}
+
+// Reads every row of a CSV file through the schema, failing on the first that cannot be read.
+pub fn read_csv<T: DeserializeOwned>(filename: &str, schema: &Schema, required: &[Column]) -> Result<Vec<T>, String> {
+    let mut reader = Reader::from_path(filename).map_err(|e1| format!("schema::read_csv Failed to read from {}. {}", filename, e1))?;
+    let headers = reader.headers().map_err(|e2| format!("schema::read_csv Failed to read the headers of {}. {}", filename, e2))?.clone();
+    let binding = schema.bind(&headers, required).map_err(|e3| format!("schema::read_csv {}: {}", filename, e3))?;
+    let mut records = Vec::new();
+    for result in reader.records() {
+        let row = result.map_err(|e4| format!("schema::read_csv Failed to read a row of {}. {}", filename, e4))?;
+        records.push(binding.deserialize(&row).map_err(|e5| format!("schema::read_csv {}: {}", filename, e5))?);
+    }
+    Ok(records)
+}
@@ -0,0 +1,55 @@
+use super::*;
+use std::env;
+use std::fs;
+
+use crate::{Feature, FeatureValue, PassengerFeatures, TrainingPassenger};
+
+fn temp_file(name: &str, contents: &str) -> String {
+    let filename = env::temp_dir().join(format!("kaggle_c_titanic_{}_{}", std::process::id(), name)).to_string_lossy().into_owned();
+    fs::write(&filename, contents).unwrap();
+    filename
+}
+
+fn read(name: &str, contents: &str, schema: &Schema) -> Result<Vec<TrainingPassenger>, String> {
+    let filename = temp_file(name, contents);
+    let result = read_csv(&filename, schema, &[Column::PassengerId, Column::Survived]);
+    fs::remove_file(&filename).unwrap();
+    result
+}
+
+#[test]
+fn when_headers_lowercase_reordered_and_extra_then_fields_bound_by_name() {
+    let passengers = read("lowercase.csv", "survived,source,passengerid,sex,pclass,age,embarked\n1,export,7,female,1,38,C\n0,export,8,male,3,,S\n", &Schema::default()).unwrap();
+
+    assert_eq!(passengers.len(), 2);
+    assert_eq!(*passengers[0].get_passenger_id(), 7_u64);
+    assert_eq!(passengers[0].get_feature(&Feature::Age), FeatureValue::Number(38_f64));
+    assert_eq!(passengers[1].get_feature(&Feature::Age), FeatureValue::Missing);
+    assert_eq!(passengers[1].get_feature(&Feature::Fare), FeatureValue::Missing);
+    assert_ne!(passengers[0].get_feature(&Feature::Sex), passengers[1].get_feature(&Feature::Sex));
+}
+
+#[test]
+fn when_columns_renamed_in_schema_then_read_from_them() {
+    let mut schema = Schema::default();
+    schema.set_header("passenger_id=id").unwrap();
+    schema.set_header("survived=label").unwrap();
+    assert!(schema.set_header("deck=Cabin").is_err());
+    assert!(schema.set_header("fare").is_err());
+
+    let passengers = read("renamed.csv", "id,label,Fare\n3,1,7.25\n", &schema).unwrap();
+    assert_eq!(*passengers[0].get_passenger_id(), 3_u64);
+    assert_eq!(passengers[0].get_feature(&Feature::Fare), FeatureValue::Number(7.25_f64));
+}
+
+#[test]
+fn when_required_column_missing_then_error_names_it() {
+    let error = read("missing.csv", "PassengerId,Pclass\n1,3\n", &Schema::default()).unwrap_err();
+    assert!(error.contains("missing the required columns Survived (survived)"), "{}", error);
+}
+
+#[test]
+fn when_value_unreadable_then_error_names_column_and_line() {
+    let error = read("unreadable.csv", "PassengerId,Survived,Age\n1,1,30\n2,1,old\n", &Schema::default()).unwrap_err();
+    assert!(error.contains("column Age on line 3"), "{}", error);
+}
@@ -0,0 +1,319 @@
+use csv::Writer;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::ops::{Add, Div, Mul, Sub};
+use std::str::FromStr;
+
+use crate::classification::{self, metrics::ConfusionMatrix, random::Rng, BinaryClass};
+use crate::{Config, Feature, Optimizer, SolverSettings, TrainingPassenger};
+
+#[cfg(test)]
+mod tests;
+
+// Candidates are trained for at most this many iterations unless --max-iterations says otherwise.
+pub const SEARCH_MAX_ITERATIONS: u64 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMode {
+    Grid,
+    Random,
+}
+
+impl FromStr for SearchMode {
+    type Err = String;
+    
+    fn from_str(name: &str) -> Result<SearchMode, String> {
+        match name {
+            "grid" => Ok(SearchMode::Grid),
+            "random" => Ok(SearchMode::Random),
+            _ => Err(format!("SearchMode::from_str unknown search mode {}, expected one of grid, random", name)),
+        }
+    }
+}
+
+impl fmt::Display for SearchMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SearchMode::Grid => write!(f, "grid"),
+            SearchMode::Random => write!(f, "random"),
+        }
+    }
+}
+
+// Grid mode tries every combination of the listed values.
+// Random mode draws learning rates and l2 log-uniformly (l2 uniformly when its smallest value is 0) and thresholds uniformly
+// between the smallest and largest listed value, and picks optimizers and feature sets from the lists.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchSpace {
+    pub mode: SearchMode,
+    pub samples: usize,
+    pub learning_rates: Vec<f64>,
+    pub l2: Vec<f64>,
+    pub optimizers: Vec<Optimizer>,
+    pub feature_sets: Vec<Vec<Feature>>,
+    pub thresholds: Vec<f64>,
+}
+
+impl Default for SearchSpace {
+    fn default() -> Self {
+        SearchSpace {
+            mode: SearchMode::Grid,
+            samples: 20,
+            learning_rates: vec![0.00001_f64, 0.0001_f64, 0.001_f64],
+            l2: vec![0_f64, 10_f64],
+            optimizers: vec![Optimizer::Batch, Optimizer::Online],
+            feature_sets: vec![Feature::ALL.to_vec()],
+            thresholds: vec![0.4_f64, 0.5_f64, 0.6_f64],
+        }
+    }
+}
+
+// Everything that changes how the weights are trained. Candidates sharing these settings share their trained folds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainingCandidate {
+    pub learning_rate: f64,
+    pub l2: f64,
+    pub optimizer: Optimizer,
+    pub features: Vec<Feature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchResult {
+    pub rank: usize,
+    pub accuracy: f64,
+    pub accuracy_std: f64,
+    pub log_loss: f64,
+    pub learning_rate: f64,
+    pub l2: f64,
+    pub optimizer: Optimizer,
+    pub features: String,
+    pub threshold: f64,
+}
+
+// The winning candidate and the data it was searched on, written with the keys of a configuration file.
+#[derive(Debug, Serialize)]
+pub struct BestConfig {
+    pub training_data_filename: String,
+    pub test_data_filename: String,
+    pub learning_rate: f64,
+    pub tolerance: f64,
+    pub max_iterations: u64,
+    pub l2: f64,
+    pub optimizer: Optimizer,
+    pub features: Vec<Feature>,
+    pub threshold: f64,
+    pub seed: u64,
+}
+
+fn bounds(values: &[f64]) -> (f64, f64) {
+    let low = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let high = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    (low, high)
+}
+
+fn draw(rng: &mut Rng, values: &[f64], logarithmic: bool) -> f64 {
+    let (low, high) = bounds(values);
+    if logarithmic && low > 0_f64 {
+        rng.uniform(low.ln(), high.ln()).exp()
+    } else {
+        rng.uniform(low, high)
+    }
+}
+
+impl SearchSpace {
+    fn validate(&self) -> Result<(), String> {
+        if self.learning_rates.is_empty() || self.l2.is_empty() || self.optimizers.is_empty() || self.feature_sets.is_empty() || self.thresholds.is_empty() {
+            return Err("SearchSpace::validate every dimension of the search space needs at least one value".to_string())
+        }
+        if self.mode == SearchMode::Random && self.samples == 0 {
+            return Err("SearchSpace::validate random search needs at least one sample".to_string())
+        }
+        if self.feature_sets.iter().any(|features| features.is_empty()) {
+            return Err("SearchSpace::validate feature sets cannot be empty".to_string())
+        }
+        Ok(())
+    }
+    
+    // Lists the training candidates with the thresholds to evaluate for each of them.
+    pub fn candidates(&self, rng: &mut Rng) -> Result<Vec<(TrainingCandidate, Vec<f64>)>, String> {
+        self.validate()?;
+        let mut candidates = Vec::new();
+        match self.mode {
+            SearchMode::Grid => {
+                for learning_rate in &self.learning_rates {
+                    for l2 in &self.l2 {
+                        for optimizer in &self.optimizers {
+                            for features in &self.feature_sets {
+                                let candidate = TrainingCandidate {
+                                    learning_rate: *learning_rate,
+                                    l2: *l2,
+                                    optimizer: *optimizer,
+                                    features: features.clone(),
+                                };
+                                candidates.push((candidate, self.thresholds.clone()));
+                            }
+                        }
+                    }
+                }
+            },
+            SearchMode::Random => {
+                for _sample in 0..self.samples {
+                    let candidate = TrainingCandidate {
+                        learning_rate: draw(rng, &self.learning_rates, true),
+                        l2: draw(rng, &self.l2, true),
+                        optimizer: self.optimizers[rng.below(self.optimizers.len())],
+                        features: self.feature_sets[rng.below(self.feature_sets.len())].clone(),
+                    };
+                    let threshold = draw(rng, &self.thresholds, false);
+                    candidates.push((candidate, vec![threshold]));
+                }
+            },
+        }
+        Ok(candidates)
+    }
+}
+
+fn mean_and_std(values: &[f64]) -> (f64, f64) {
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>().div(count);
+    let variance = values.iter().map(|value| value.sub(mean).mul(value.sub(mean))).sum::<f64>().div(count);
+    (mean, variance.sqrt())
+}
+
+// Best accuracy first, ties broken by the lower log loss. A log loss that is not a number, as a diverged candidate can
+// give, ranks after every other whatever its sign, which total_cmp alone would put first when negative.
+pub fn rank(results: &mut [SearchResult]) {
+    results.sort_by(|left, right| {
+        right.accuracy.total_cmp(&left.accuracy)
+            .then(left.log_loss.is_nan().cmp(&right.log_loss.is_nan()))
+            .then(left.log_loss.total_cmp(&right.log_loss))
+    });
+    for (index, result) in results.iter_mut().enumerate() {
+        result.rank = index.add(1);
+    }
+}
+
+pub fn evaluate(fold_scores: &[Vec<(f64, BinaryClass)>], candidate: &TrainingCandidate, thresholds: &[f64]) -> Result<Vec<SearchResult>, String> {
+    let mut log_losses = Vec::new();
+    for scores in fold_scores {
+        log_losses.push(classification::metrics::log_loss(scores)?);
+    }
+    let (log_loss, _log_loss_std) = mean_and_std(&log_losses);
+    
+    let mut results = Vec::new();
+    for threshold in thresholds {
+        let accuracies: Vec<f64> = fold_scores.iter().map(|scores| ConfusionMatrix::from_scores(scores, threshold).accuracy()).collect();
+        let (accuracy, accuracy_std) = mean_and_std(&accuracies);
+        results.push(SearchResult {
+            rank: 0,
+            accuracy,
+            accuracy_std,
+            log_loss,
+            learning_rate: candidate.learning_rate,
+            l2: candidate.l2,
+            optimizer: candidate.optimizer,
+            features: Feature::format_list(&candidate.features),
+            threshold: *threshold,
+        });
+    }
+    Ok(results)
+}
+
+// Trains the candidate on every fold but one, from the initial weights of config, and scores it on the one left out.
+pub fn cross_validate(config: &Config, passengers: &[TrainingPassenger], fold_indices: &[Vec<usize>], candidate: &TrainingCandidate, thresholds: &[f64], max_iterations: u64) -> Result<Vec<SearchResult>, String> {
+    let mut settings = SolverSettings::new(candidate.learning_rate, *config.get_tolerance());
+    settings.max_iterations = Some(max_iterations);
+    settings.l2 = candidate.l2;
+    settings.optimizer = candidate.optimizer;
+    settings.seed = *config.get_seed();
+    settings.verbose = false;
+    
+    let initial_weights = config.new_weights(&candidate.features)?;
+    let fold_scores = classification::cross_validate(passengers, fold_indices, &settings, || initial_weights.clone())?;
+    evaluate(&fold_scores, candidate, thresholds)
+}
+
+pub fn run(config: &Config) -> Result<(), String> {
+    let training_passengers = config.get_training_passengers()?;
+    let mut rng = Rng::new(*config.get_seed());
+    let candidates = config.get_search_space().candidates(&mut rng)?;
+    let fold_indices = classification::k_fold_indices_shuffled(training_passengers.len(), *config.get_folds(), &mut rng)?;
+    
+    let max_iterations = config.get_max_iterations().unwrap_or(SEARCH_MAX_ITERATIONS);
+    eprintln!("search::run Evaluating {} training candidates with {}-fold cross-validation ({} search, at most {} iterations each)", candidates.len(), config.get_folds(), config.get_search_space().mode, max_iterations);
+    
+    let mut results = Vec::new();
+    for (index, (candidate, thresholds)) in candidates.iter().enumerate() {
+        let candidate_results = cross_validate(config, &training_passengers, &fold_indices, candidate, thresholds, max_iterations)?;
+        eprintln!("search::run Candidate {}/{} learning_rate={} l2={} optimizer={} features={}: log_loss {}", index.add(1), candidates.len(), candidate.learning_rate, candidate.l2, candidate.optimizer, Feature::format_list(&candidate.features), candidate_results[0].log_loss);
+        results.extend(candidate_results);
+    }
+    rank(&mut results);
+    
+    print_table(&results);
+    write_results(config.get_search_results_filename(), &results)?;
+    
+    let best = &results[0];
+    let best_config = BestConfig {
+        training_data_filename: config.get_training_data_filename().clone(),
+        test_data_filename: config.get_test_data_filename().clone(),
+        learning_rate: best.learning_rate,
+        tolerance: *config.get_tolerance(),
+        max_iterations,
+        l2: best.l2,
+        optimizer: best.optimizer,
+        features: Feature::parse_list(&best.features)?,
+        threshold: best.threshold,
+        seed: *config.get_seed(),
+    };
+    write_best_config(config.get_best_config_filename(), &best_config)
+}
+
+fn print_table(results: &[SearchResult]) {
+    println!("{:>4}  {:>8}  {:>8}  {:>8}  {:>12}  {:>10}  {:>8}  {:>9}  features", "rank", "accuracy", "std", "log_loss", "learning_rate", "l2", "optimizer", "threshold");
+    for result in results.iter().take(10) {
+        println!("{:>4}  {:>8.4}  {:>8.4}  {:>8.4}  {:>12.3e}  {:>10.4}  {:>8}  {:>9.4}  {}", result.rank, result.accuracy, result.accuracy_std, result.log_loss, result.learning_rate, result.l2, result.optimizer.to_string(), result.threshold, result.features);
+    }
+    if results.len() > 10 {
+        println!("... {} more candidates", results.len().sub(10));
+    }
+}
+
+fn write_results(filename: &str, results: &[SearchResult]) -> Result<(), String> {
+    match Writer::from_path(filename) {
+        Ok(mut writer) => {
+            for result in results {
+                if let Err(e2) = writer.serialize(result) {
+                    let message = format!("search::write_results Failed to serialize SearchResult {}. Serde: {}", result.rank, e2);
+                    return Err(message)
+                }
+            }
+            eprintln!("search::write_results Completed writing ranked search results to {}", filename);
+            Ok(())
+        },
+        Err(e1) => {
+            let message = format!("search::write_results Failed to create writer to {}. Serde: {}", filename, e1);
+            Err(message)
+        },
+    }
+}
+
+fn write_best_config(filename: &str, best_config: &BestConfig) -> Result<(), String> {
+    match serde_json::to_string_pretty(best_config) {
+        Ok(json) => {
+            if let Err(e2) = fs::write(filename, json) {
+                let message = format!("search::write_best_config Failed to write {}. {}", filename, e2);
+                return Err(message)
+            }
+            eprintln!("search::write_best_config Completed writing the winning configuration to {}", filename);
+            Ok(())
+        },
+        Err(e1) => {
+            let message = format!("search::write_best_config Failed to serialize BestConfig. Serde: {}", e1);
+            Err(message)
+        },
+    }
+}
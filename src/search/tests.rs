@@ -0,0 +1,106 @@
+use super::*;
+
+use crate::test_support::training_passengers;
+
+#[test]
+fn when_grid_search_then_every_training_combination_listed() {
+    let space = SearchSpace::default();
+    let candidates = space.candidates(&mut Rng::new(1_u64)).unwrap();
+    
+    assert_eq!(candidates.len(), 12);
+    for (_candidate, thresholds) in &candidates {
+        assert_eq!(thresholds, &vec![0.4_f64, 0.5_f64, 0.6_f64]);
+    }
+    assert_eq!(candidates[0].0, TrainingCandidate {
+        learning_rate: 0.00001_f64,
+        l2: 0_f64,
+        optimizer: Optimizer::Batch,
+        features: Feature::ALL.to_vec(),
+    });
+}
+
+#[test]
+fn when_random_search_then_candidates_within_bounds_and_repeatable() {
+    let space = SearchSpace {
+        mode: SearchMode::Random,
+        samples: 50,
+        feature_sets: vec![Feature::ALL.to_vec(), vec![Feature::Sex, Feature::Age]],
+        ..SearchSpace::default()
+    };
+    
+    let candidates = space.candidates(&mut Rng::new(3_u64)).unwrap();
+    assert_eq!(candidates, space.candidates(&mut Rng::new(3_u64)).unwrap());
+    assert_eq!(candidates.len(), 50);
+    for (candidate, thresholds) in &candidates {
+        assert!(candidate.learning_rate >= 0.00001_f64 && candidate.learning_rate <= 0.001_f64);
+        assert!(candidate.l2 >= 0_f64 && candidate.l2 <= 10_f64);
+        assert_eq!(thresholds.len(), 1);
+        assert!(thresholds[0] >= 0.4_f64 && thresholds[0] <= 0.6_f64);
+    }
+}
+
+#[test]
+fn when_fold_scores_then_results_ranked_by_accuracy() {
+    let candidate = TrainingCandidate {
+        learning_rate: 1_f64,
+        l2: 0_f64,
+        optimizer: Optimizer::Batch,
+        features: vec![Feature::Sex],
+    };
+    let fold_scores = vec![
+        vec![(0.7_f64, BinaryClass::Yes), (0.45_f64, BinaryClass::No)],
+        vec![(0.55_f64, BinaryClass::Yes), (0.2_f64, BinaryClass::No)],
+    ];
+    
+    let mut results = evaluate(&fold_scores, &candidate, &[0.4_f64, 0.5_f64, 0.6_f64]).unwrap();
+    rank(&mut results);
+    
+    assert_eq!(results[0].threshold, 0.5_f64);
+    assert_eq!(results[0].accuracy, 1_f64);
+    assert_eq!(results[0].rank, 1);
+    assert_eq!(results[0].features, "sex");
+    assert_eq!(results[2].accuracy, 0.75_f64);
+}
+
+#[test]
+fn when_log_loss_not_a_number_then_ranked_last_among_equal_accuracy() {
+    let candidate = TrainingCandidate {
+        learning_rate: 1_f64,
+        l2: 0_f64,
+        optimizer: Optimizer::Batch,
+        features: vec![Feature::Sex],
+    };
+    let fold_scores = vec![vec![(0.7_f64, BinaryClass::Yes), (0.2_f64, BinaryClass::No)]];
+    let mut results = evaluate(&fold_scores, &candidate, &[0.5_f64]).unwrap();
+    // 0 / 0 at run time gives a NaN with the sign bit set, which total_cmp orders before every number.
+    let zero = results[0].log_loss * 0_f64;
+    for (log_loss, threshold) in [(f64::NAN, 0.4_f64), (zero / zero, 0.3_f64), (-f64::NAN, 0.2_f64)].iter() {
+        let mut diverged = results[0].clone();
+        diverged.log_loss = *log_loss;
+        diverged.threshold = *threshold;
+        results.insert(0, diverged);
+    }
+    
+    rank(&mut results);
+    
+    assert_eq!(results[0].threshold, 0.5_f64);
+    assert_eq!(results[0].rank, 1);
+    assert!(results[1..].iter().all(|result| result.log_loss.is_nan()));
+}
+
+#[test]
+fn when_default_space_searched_then_candidates_score_differently() {
+    let args: Vec<String> = ["first", "0.0001", "0.5", "fourth", "fifth", "sixth"].iter().map(|arg| arg.to_string()).collect();
+    let config = Config::new(&args).unwrap();
+    let passengers = training_passengers();
+    let mut rng = Rng::new(1_u64);
+    let candidates = SearchSpace::default().candidates(&mut rng).unwrap();
+    let fold_indices = classification::k_fold_indices_shuffled(passengers.len(), 4, &mut rng).unwrap();
+
+    let mut accuracies = Vec::new();
+    for (candidate, thresholds) in &candidates {
+        accuracies.extend(cross_validate(&config, &passengers, &fold_indices, candidate, thresholds, 50).unwrap().iter().map(|result| result.accuracy));
+    }
+
+    assert!(accuracies.iter().any(|accuracy| *accuracy != accuracies[0]), "{:?}", accuracies);
+}
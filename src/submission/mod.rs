@@ -0,0 +1,77 @@
+use csv::{Reader, Writer};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, Write};
+
+use crate::data::{DataFormat, STANDARD_STREAM};
+use crate::{Config, TestedPassenger};
+
+#[cfg(test)]
+mod tests;
+
+// Passengers in the Kaggle test file, and so rows of a submission
+pub const KAGGLE_ROWS: usize = 418;
+
+// The header of a Kaggle submission
+pub const KAGGLE_HEADERS: [&str; 2] = ["PassengerId", "Survived"];
+
+// Kaggle takes a submission as CSV with exactly the columns PassengerId and Survived.
+pub fn check_kaggle_config(config: &Config) -> Result<(), String> {
+    if *config.get_write_probability() || *config.get_extended_output() || *config.get_write_contributions() {
+        return Err("submission::check_kaggle_config --kaggle writes only PassengerId and Survived, so it cannot be combined with --probability, --extended or --contributions".to_string())
+    }
+    if DataFormat::resolve(config.get_output_format(), config.get_output_filename()) != DataFormat::Csv {
+        return Err(format!("submission::check_kaggle_config --kaggle writes CSV, not {}", DataFormat::resolve(config.get_output_format(), config.get_output_filename())))
+    }
+    Ok(())
+}
+
+// A submission as Kaggle expects it: the header PassengerId,Survived, then one row per test passenger with a label of
+// 0 or 1. test_ids are the PassengerIds of every row of the test file.
+pub fn check_kaggle(contents: &str, test_ids: &[String]) -> Result<(), String> {
+    let mut reader = Reader::from_reader(contents.as_bytes());
+    let headers = reader.headers().map_err(|e1| format!("submission::check_kaggle Failed to read the headers. {}", e1))?.clone();
+    if headers.iter().collect::<Vec<&str>>() != KAGGLE_HEADERS {
+        return Err(format!("submission::check_kaggle the headers are {}, Kaggle expects {}", headers.iter().collect::<Vec<&str>>().join(","), KAGGLE_HEADERS.join(",")))
+    }
+    let mut ids = BTreeSet::new();
+    let mut rows = 0_usize;
+    for result in reader.records() {
+        let row = result.map_err(|e2| format!("submission::check_kaggle Failed to read a row. {}", e2))?;
+        rows += 1;
+        let id = row.get(0).unwrap_or("");
+        let label = row.get(1).unwrap_or("");
+        if label != "0" && label != "1" {
+            return Err(format!("submission::check_kaggle PassengerId {} has the label '{}', Kaggle expects 0 or 1", id, label))
+        }
+        if !ids.insert(id.to_string()) {
+            return Err(format!("submission::check_kaggle PassengerId {} is predicted more than once", id))
+        }
+    }
+    if rows != KAGGLE_ROWS {
+        return Err(format!("submission::check_kaggle the submission has {} rows, Kaggle expects {}", rows, KAGGLE_ROWS))
+    }
+    let expected: BTreeSet<String> = test_ids.iter().cloned().collect();
+    if let Some(missing) = expected.difference(&ids).next() {
+        return Err(format!("submission::check_kaggle PassengerId {} of the test file has no prediction", missing))
+    }
+    if let Some(extra) = ids.difference(&expected).next() {
+        return Err(format!("submission::check_kaggle PassengerId {} is not in the test file", extra))
+    }
+    Ok(())
+}
+
+// Writes the submission only once check_kaggle passes, so a file that Kaggle would reject is never written.
+pub fn write_kaggle(filename: &str, tested_passengers: &[TestedPassenger], test_ids: &[String]) -> Result<(), String> {
+    let mut writer = Writer::from_writer(Vec::new());
+    for tested_passenger in tested_passengers {
+        writer.serialize(tested_passenger).map_err(|e1| format!("submission::write_kaggle Failed to serialize TestedPassenger {}. Serde: {}", tested_passenger.passenger_id, e1))?;
+    }
+    let bytes = writer.into_inner().map_err(|e2| format!("submission::write_kaggle Failed to write the submission. {}", e2))?;
+    let contents = String::from_utf8(bytes).map_err(|e3| format!("submission::write_kaggle Failed to write the submission. {}", e3))?;
+    check_kaggle(&contents, test_ids)?;
+    let result = if filename == STANDARD_STREAM { io::stdout().write_all(contents.as_bytes()) } else { fs::write(filename, &contents) };
+    result.map_err(|e4| format!("submission::write_kaggle Failed to write {}. {}", filename, e4))?;
+    eprintln!("submission::write_kaggle Checked and wrote a Kaggle submission of {} rows to {}", tested_passengers.len(), filename);
+    Ok(())
+}
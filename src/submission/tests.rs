@@ -0,0 +1,63 @@
+use super::*;
+use std::env;
+
+use crate::classification::classify;
+use crate::test_support::training_passengers;
+use crate::{fit, Model};
+
+fn submission(rows: usize, label: &str) -> (String, Vec<String>) {
+    let ids: Vec<String> = (0..rows).map(|index| (892 + index).to_string()).collect();
+    let contents = ids.iter().fold("PassengerId,Survived\n".to_string(), |contents, id| format!("{}{},{}\n", contents, id, label));
+    (contents, ids)
+}
+
+#[test]
+fn when_kaggle_submission_well_formed_then_accepted() {
+    let (contents, ids) = submission(KAGGLE_ROWS, "1");
+
+    assert!(check_kaggle(&contents, &ids).is_ok());
+}
+
+#[test]
+fn when_kaggle_submission_malformed_then_rejected_with_reason() {
+    let (contents, ids) = submission(KAGGLE_ROWS, "1");
+    let (probabilities, _ids) = submission(KAGGLE_ROWS, "0.7");
+    let mut other_ids = ids.clone();
+    other_ids[5] = "5".to_string();
+
+    assert!(check_kaggle(&contents.replacen("Survived", "survived", 1), &ids).unwrap_err().contains("headers"));
+    assert!(check_kaggle(&probabilities, &ids).unwrap_err().contains("'0.7'"));
+    assert!(check_kaggle(&contents, &other_ids).unwrap_err().contains("PassengerId 5 of the test file"));
+    assert!(check_kaggle(&contents.replacen("893,", "892,", 1), &ids).unwrap_err().contains("more than once"));
+}
+
+#[test]
+fn when_test_file_has_417_ids_then_submission_refused_even_if_they_match() {
+    let (short, short_ids) = submission(KAGGLE_ROWS - 1, "0");
+    let (_contents, ids) = submission(KAGGLE_ROWS, "0");
+
+    assert!(check_kaggle(&short, &short_ids).unwrap_err().contains("the submission has 417 rows, Kaggle expects 418"));
+    assert!(check_kaggle(&short, &ids).unwrap_err().contains("417 rows"));
+}
+
+#[test]
+fn when_kaggle_submission_rejected_then_nothing_written() {
+    let filename = env::temp_dir().join(format!("kaggle_c_titanic_submission_{}.csv", std::process::id())).to_string_lossy().into_owned();
+    let tested_passengers = vec![TestedPassenger::new(classify(892, 0.9_f64, &0.5_f64))];
+
+    assert!(write_kaggle(&filename, &tested_passengers, &["892".to_string()]).is_err());
+    assert!(fs::metadata(&filename).is_err());
+}
+
+#[test]
+fn when_model_read_back_then_same_id() {
+    let args: Vec<String> = ["first", "0.001", "0.01", "fourth", "fifth", "sixth", "--max-iterations", "20"].iter().map(|arg| arg.to_string()).collect();
+    let model = fit(&Config::new(&args).unwrap(), training_passengers()).unwrap();
+    let filename = env::temp_dir().join(format!("kaggle_c_titanic_submission_{}_model.json", std::process::id())).to_string_lossy().into_owned();
+    model.write(&filename).unwrap();
+    let read = Model::read(&filename).unwrap();
+    fs::remove_file(&filename).unwrap();
+
+    assert_eq!(model.id().unwrap().len(), 16);
+    assert_eq!(read.id().unwrap(), model.id().unwrap());
+}
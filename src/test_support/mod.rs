@@ -0,0 +1,30 @@
+use crate::{PassengerClass, PortOfEmbarkation, Sex, Survived, TrainingPassenger};
+
+// Training passengers read from rows of a Kaggle training file, e.g. "1,1,1,A,female,30,0,0,T,10,,S"
+pub fn passengers(rows: &[&str]) -> Vec<TrainingPassenger> {
+    let mut data = "PassengerId,Survived,Pclass,Name,Sex,Age,SibSp,Parch,Ticket,Fare,Cabin,Embarked\n".to_string();
+    for row in rows {
+        data.push_str(row);
+        data.push('\n');
+    }
+    csv::Reader::from_reader(data.as_bytes()).deserialize().map(|record| record.unwrap()).collect()
+}
+
+// Forty passengers who survive exactly when female, alternating between first and third class, with the other fields
+// cycling through a few values
+pub fn training_passengers() -> Vec<TrainingPassenger> {
+    (0..40_u64).map(|id| TrainingPassenger::new(
+        id,
+        if id % 4 < 2 { Survived::Yes } else { Survived::No },
+        if id % 2 == 0 { PassengerClass::First } else { PassengerClass::Third },
+        format!("Passenger {}", id),
+        if id % 4 < 2 { Sex::Female } else { Sex::Male },
+        20_f64 + (id % 7) as f64,
+        (id % 3) as usize,
+        (id % 2) as usize,
+        format!("Ticket {}", id),
+        10_f64 + (id % 5) as f64,
+        format!("Cabin {}", id),
+        PortOfEmbarkation::Southampton
+    )).collect()
+}
@@ -1,4 +1,6 @@
 use super::*;
+use std::ops::Div;
+use crate::classification::{LogisticBinaryClassificationTestable, LogisticBinaryClassificationTrainable};
 
 #[test]
 #[should_panic(expected = "not enough arguments")]
@@ -18,9 +20,9 @@ fn when_at_least_6_arguments_then_create_config() {
 	sum_nums = sum_nums.add(conf.get_tolerance());
 	
 	sum_strings.push_str(&conf.training_data_filename);
-	sum_strings.push_str("-");
+	sum_strings.push('-');
 	sum_strings.push_str(&conf.test_data_filename);
-	sum_strings.push_str("-");
+	sum_strings.push('-');
 	sum_strings.push_str(&conf.output_filename);
 	
 	assert_abs_diff_eq!(sum_nums, 5_f64);
@@ -45,7 +47,7 @@ fn when_new_passenger_weights_and_training_passenger_then_get_hypothesis() {
 		"1".to_string(),
 		PortOfEmbarkation::Southampton
 	);
-	assert_abs_diff_eq!(passenger_weights.hypothesis(&training_passenger).unwrap(), 1_f64.div(83_f64.exp().add(1_f64)));
+	assert_abs_diff_eq!(training_passenger.hypothesis(&passenger_weights).unwrap(), 1_f64.div(83_f64.exp().add(1_f64)));
 }
 
 #[test]
@@ -66,7 +68,7 @@ fn when_new_passenger_weights_and_training_passenger_then_get_diff_hypothesis()
 		"1".to_string(),
 		PortOfEmbarkation::Southampton
 	);
-	assert_abs_diff_eq!(passenger_weights.diff_hypothesis(&training_passenger).unwrap(), 1_f64 - 1_f64.div(83_f64.exp().add(1_f64)));
+	assert_abs_diff_eq!(training_passenger.diff_hypothesis(&passenger_weights).unwrap(), 1_f64 - 1_f64.div(83_f64.exp().add(1_f64)));
 }
 
 #[test]
@@ -87,7 +89,7 @@ fn when_new_passenger_weights_and_training_passenger_then_get_cost() {
 		"1".to_string(),
 		PortOfEmbarkation::Southampton
 	);
-	assert_abs_diff_eq!(passenger_weights.cost(&training_passenger).unwrap(), -(1_f64.div(83_f64.exp().add(1_f64))).ln());
+	assert_abs_diff_eq!(training_passenger.cost(&passenger_weights).unwrap(), -(1_f64.div(83_f64.exp().add(1_f64))).ln());
 }
 
 #[test]
@@ -108,9 +110,8 @@ fn when_new_passenger_weights_and_training_passenger_then_get_avg_cost() {
 		"1".to_string(),
 		PortOfEmbarkation::Southampton
 	);
-	let mut training_passengers = Vec::new();
-	training_passengers.push(training_passenger);
-	assert_abs_diff_eq!(passenger_weights.avg_cost(&training_passengers).unwrap(), -(1_f64.div(83_f64.exp().add(1_f64))).ln());
+	let training_passengers = vec![training_passenger];
+	assert_abs_diff_eq!(classification::avg_cost(&training_passengers, &passenger_weights).unwrap(), -(1_f64.div(83_f64.exp().add(1_f64))).ln());
 }
 
 #[test]
@@ -131,12 +132,11 @@ fn when_new_passenger_weights_and_training_passenger_and_add_1_to_all_weights_th
 		"1".to_string(),
 		PortOfEmbarkation::Southampton
 	);
-	let mut training_passengers = Vec::new();
-	training_passengers.push(training_passenger);
-	passenger_weights.gradient_descent_update(&(-1_f64), &training_passengers).unwrap();
-	match training_passengers.get(0) {
+	let training_passengers = vec![training_passenger];
+	classification::gradient_descent_update(&training_passengers, &mut passenger_weights, &(-1_f64)).unwrap();
+	match training_passengers.first() {
 		None => panic!("tests::when_new_passenger_weights_and_training_passenger_and_gradient_descent_update_then_get_hypothesis could not find item in vec"),
-		Some(training_passenger0) => assert_abs_diff_eq!(passenger_weights.hypothesis(training_passenger0).unwrap(), 1_f64.div(93_f64.exp().add(1_f64))),
+		Some(training_passenger0) => assert_abs_diff_eq!(training_passenger0.hypothesis(&passenger_weights).unwrap(), 1_f64.div(93_f64.exp().add(1_f64))),
 	}
 	;
 }
@@ -159,11 +159,125 @@ fn when_new_passenger_weights_and_training_passenger_and_gradient_descent_update
 		"1".to_string(),
 		PortOfEmbarkation::Southampton
 	);
-	let mut training_passengers = Vec::new();
-	training_passengers.push(training_passenger);
-	passenger_weights.gradient_descent_update(&(-1_f64), &training_passengers).unwrap();
-	match training_passengers.get(0) {
+	let training_passengers = vec![training_passenger];
+	classification::gradient_descent_update(&training_passengers, &mut passenger_weights, &(-1_f64)).unwrap();
+	match training_passengers.first() {
 		None => panic!("tests::when_new_passenger_weights_and_training_passenger_and_gradient_descent_update_then_get_hypothesis could not find item in vec"),
-		Some(training_passenger0) => assert_abs_diff_eq!(passenger_weights.hypothesis(training_passenger0).unwrap(), 1_f64.div((83_f64.add((1_f64 - 1_f64.div(83_f64.exp().add(1_f64))).mul(10_f64))).exp().add(1_f64))),
+		Some(training_passenger0) => assert_abs_diff_eq!(training_passenger0.hypothesis(&passenger_weights).unwrap(), 1_f64.div((83_f64.add((1_f64 - 1_f64.div(83_f64.exp().add(1_f64))).mul(10_f64))).exp().add(1_f64))),
 	}
-}
\ No newline at end of file
+}
+#[test]
+fn when_options_after_6_arguments_then_config_has_options() {
+	let args = vec!["first".to_string(), "2".to_string(), "3".to_string(), "fourth".to_string(), "fifth".to_string(), "sixth".to_string(),
+		"--threshold".to_string(), "0.3".to_string(), "--select-threshold".to_string(), "f1".to_string(), "--probability".to_string()];
+	let conf = Config::new(&args).unwrap();
+	
+	assert_abs_diff_eq!(*conf.get_threshold(), 0.3_f64);
+	assert_eq!(*conf.get_threshold_metric(), Some(ThresholdMetric::F1));
+	assert!(*conf.get_write_probability());
+}
+
+#[test]
+#[should_panic(expected = "unable to parse threshold")]
+fn when_threshold_out_of_range_then_return_error() {
+	let args = vec!["first".to_string(), "2".to_string(), "3".to_string(), "fourth".to_string(), "fifth".to_string(), "sixth".to_string(),
+		"--threshold".to_string(), "1.5".to_string()];
+	Config::new(&args).unwrap();
+}
+
+#[test]
+fn when_tested_passenger_with_probability_then_probability_column_written() {
+	let outcome = classification::classify(892_u64, 0.25_f64, &0.5_f64);
+	let mut writer = Writer::from_writer(vec![]);
+	writer.serialize(TestedPassenger::with_probability(outcome)).unwrap();
+	let written = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+	
+	assert_eq!(written, "PassengerId,Survived,Probability\n892,0,0.25\n");
+}
+
+#[test]
+fn when_calibration_options_then_config_needs_validation() {
+	let args = vec!["first".to_string(), "2".to_string(), "3".to_string(), "fourth".to_string(), "fifth".to_string(), "sixth".to_string(),
+		"--calibration".to_string(), "isotonic".to_string(), "--calibration-bins".to_string(), "5".to_string()];
+	let conf = Config::new(&args).unwrap();
+	
+	assert_eq!(*conf.get_calibration(), Some(CalibrationMethod::Isotonic));
+	assert_eq!(*conf.get_calibration_bins(), 5_usize);
+	assert!(conf.needs_validation());
+}
+
+#[test]
+fn when_search_command_then_positional_arguments_follow_it() {
+	let args = vec!["first".to_string(), "search".to_string(), "2".to_string(), "3".to_string(), "fourth".to_string(), "fifth".to_string(), "sixth".to_string(),
+		"--search-l2".to_string(), "0,1".to_string(), "--search-feature-set".to_string(), "sex,age".to_string(), "--optimizer".to_string(), "online".to_string()];
+	let conf = Config::new(&args).unwrap();
+	
+	assert_eq!(*conf.get_command(), Command::Search);
+	assert_abs_diff_eq!(*conf.get_learning_rate(), 2_f64);
+	assert_eq!(conf.get_search_space().l2, vec![0_f64, 1_f64]);
+	assert_eq!(conf.get_search_space().feature_sets, vec![vec![Feature::Sex, Feature::Age]]);
+	assert_eq!(*conf.get_optimizer(), Optimizer::Online);
+}
+
+#[test]
+fn when_feature_disabled_then_weighted_sum_ignores_it() {
+	let training_passenger = TrainingPassenger::new(
+		1_u64,
+		Survived::Yes,
+		PassengerClass::First,
+		"Lewis Webb".to_string(),
+		Sex::Male,
+		25.33_f64,
+		3_usize,
+		2_usize,
+		"Golden Ticket".to_string(),
+		45.67_f64,
+		"1".to_string(),
+		PortOfEmbarkation::Southampton
+	);
+	let mut passenger_weights = PassengerWeights::with_features(&[Feature::Sex, Feature::Age]);
+	assert_abs_diff_eq!(passenger_weights.weighted_sum(&training_passenger).unwrap(), 27.33_f64);
+	
+	passenger_weights.update(&training_passenger, &1_f64).unwrap();
+	assert_abs_diff_eq!(passenger_weights.weighted_sum(&training_passenger).unwrap(), 27.33_f64 + 1_f64 + 1_f64 + 25.33_f64 * 25.33_f64, epsilon = 1e-9);
+	assert_abs_diff_eq!(classification::Regularizable::squared_norm(&passenger_weights), 2_f64 + 2_f64 * 2_f64 + 1_f64 + 26.33_f64 * 26.33_f64, epsilon = 1e-9);
+}
+
+#[test]
+fn when_zeros_initialization_then_hypothesis_is_one_half() {
+	let training_passenger = TrainingPassenger::new(
+		1_u64,
+		Survived::Yes,
+		PassengerClass::First,
+		"Lewis Webb".to_string(),
+		Sex::Male,
+		25.33_f64,
+		3_usize,
+		2_usize,
+		"Golden Ticket".to_string(),
+		45.67_f64,
+		"1".to_string(),
+		PortOfEmbarkation::Southampton
+	);
+	let initialization = "zeros".parse::<Initialization>().unwrap();
+	let passenger_weights = PassengerWeights::initialized(&Feature::ALL, &initialization, DEFAULT_SEED).unwrap();
+	
+	assert_eq!(initialization, Initialization::Constant(0_f64));
+	assert_abs_diff_eq!(training_passenger.hypothesis(&passenger_weights).unwrap(), 0.5_f64);
+	assert_abs_diff_eq!(classification::Regularizable::squared_norm(&passenger_weights), 0_f64);
+}
+
+#[test]
+fn when_random_initialization_then_repeatable_from_seed() {
+	let initialization = "normal:0.01".parse::<Initialization>().unwrap();
+	let first = PassengerWeights::initialized(&[Feature::Sex, Feature::Age], &initialization, 3_u64).unwrap();
+	let second = PassengerWeights::initialized(&[Feature::Sex, Feature::Age], &initialization, 3_u64).unwrap();
+	let other = PassengerWeights::initialized(&[Feature::Sex, Feature::Age], &initialization, 4_u64).unwrap();
+	
+	assert_eq!(first, second);
+	assert_ne!(first, other);
+	assert_eq!(initialization.to_string(), "normal:0.01");
+	assert!(classification::Regularizable::squared_norm(&first) < 0.01_f64);
+	assert!("uniform".parse::<Initialization>().is_err());
+	assert!(PassengerWeights::initialized(&Feature::ALL, &"model:missing.json".parse::<Initialization>().unwrap(), 3_u64).is_err());
+}
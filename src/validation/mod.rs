@@ -0,0 +1,249 @@
+use csv::{StringRecord, WriterBuilder};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::OpenOptions;
+
+use crate::data::{DataFormat, RowReader};
+use crate::schema::{Binding, Column, Schema};
+
+#[cfg(test)]
+mod tests;
+
+// A strict load names this many problems in its error, the report holds them all.
+const PROBLEMS_IN_ERROR: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    // Loading failed because of the problem
+    Rejected,
+    // The row was left out
+    Skipped,
+    // The value was replaced by a missing value, which every learner handles
+    Imputed,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::Rejected => write!(f, "rejected"),
+            Action::Skipped => write!(f, "skipped"),
+            Action::Imputed => write!(f, "imputed"),
+        }
+    }
+}
+
+// One bad value of a data file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Issue {
+    pub file: String,
+    pub line: u64,
+    pub passenger_id: String,
+    pub column: String,
+    pub value: String,
+    pub problem: String,
+    pub action: Action,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {} (PassengerId {}) column {} value '{}' {}", self.line, self.passenger_id, self.column, self.value, self.problem)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationReport {
+    pub rows: usize,
+    pub kept: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    // Writes the issues as CSV, after those already in the file when append is set.
+    pub fn write(&self, filename: &str, append: bool) -> Result<(), String> {
+        let file = OpenOptions::new().write(true).create(true).append(append).truncate(!append).open(filename)
+            .map_err(|e1| format!("ValidationReport::write Failed to open {}. {}", filename, e1))?;
+        let has_header = append && file.metadata().map(|metadata| metadata.len() > 0).unwrap_or(false);
+        let mut writer = WriterBuilder::new().has_headers(false).from_writer(file);
+        if !has_header {
+            writer.write_record(["file", "line", "passenger_id", "column", "value", "problem", "action"]).map_err(|e2| format!("ValidationReport::write Failed to write {}. {}", filename, e2))?;
+        }
+        for issue in &self.issues {
+            writer.serialize(issue).map_err(|e3| format!("ValidationReport::write Failed to serialize Issue. Serde: {}", e3))?;
+        }
+        writer.flush().map_err(|e4| format!("ValidationReport::write Failed to write {}. {}", filename, e4))?;
+        eprintln!("ValidationReport::write Completed writing {} problems to {}", self.issues.len(), filename);
+        Ok(())
+    }
+
+    // Fails when a strict load found problems, naming the first few.
+    pub fn ensure_valid(&self, filename: &str) -> Result<(), String> {
+        let rejected: Vec<String> = self.issues.iter().filter(|issue| issue.action == Action::Rejected).map(|issue| issue.to_string()).collect();
+        if rejected.is_empty() {
+            return Ok(())
+        }
+        let first: Vec<String> = rejected.iter().take(PROBLEMS_IN_ERROR).cloned().collect();
+        Err(format!("ValidationReport::ensure_valid {} has {} problems, first {}. Rerun with --lenient to skip or impute them, or with --validation-report to list them all", filename, rejected.len(), first.join("; ")))
+    }
+}
+
+fn check_choice(value: &str, choices: &[&str], problem: &str) -> Option<String> {
+    if choices.contains(&value) { None } else { Some(problem.to_string()) }
+}
+
+fn check_count(value: &str) -> Option<String> {
+    match value.parse::<usize>() {
+        Ok(_count) => None,
+        Err(_) => Some("is not a whole number of at least 0".to_string()),
+    }
+}
+
+fn check_number(value: &str, low: f64, high: f64, range: &str) -> Option<String> {
+    match value.parse::<f64>() {
+        Ok(number) if number.is_nan() || number < low || number > high => Some(format!("is outside {}", range)),
+        Ok(_number) => None,
+        Err(_) => Some("is not a number".to_string()),
+    }
+}
+
+// The problem with a value, if any. Empty values are missing, which is fine for every column but the required ones.
+fn check_value(column: &Column, value: &str) -> Option<String> {
+    if value.is_empty() {
+        return match column {
+            Column::PassengerId | Column::Survived => Some("is missing".to_string()),
+            _ => None,
+        }
+    }
+    match column {
+        Column::PassengerId => match value.parse::<u64>() {
+            Ok(_id) => None,
+            Err(_) => Some("is not a whole number of at least 0".to_string()),
+        },
+        Column::Survived => check_choice(value, &["0", "1"], "is not 0 or 1"),
+        Column::PassengerClass => check_choice(value, &["1", "2", "3"], "is not 1, 2 or 3"),
+        Column::Sex => check_choice(value, &["male", "female"], "is not male or female"),
+        Column::Age => check_number(value, 0_f64, 100_f64, "0 to 100"),
+        Column::Fare => check_number(value, 0_f64, f64::INFINITY, "0 and above"),
+        Column::SiblingsSpouses | Column::ParentsChildren => check_count(value),
+        Column::PortOfEmbarkation => check_choice(value, &["C", "S", "Q"], "is not C, S or Q"),
+        Column::Name | Column::Ticket | Column::Cabin => None,
+    }
+}
+
+// Checks the rows of one file one at a time, so a file can be streamed: PassengerIds that are whole numbers and unique,
+// labels of 0 or 1, a passenger class of 1 to 3, an age of 0 to 100, a fare of at least 0, whole counts of relatives and
+// known sexes and ports. Strictly, every row with a problem is rejected. When lenient, a row with a problem in a
+// required column is skipped and any other bad value is replaced by a missing value, and each is logged.
+#[derive(Debug, Clone)]
+pub struct Validator {
+    filename: String,
+    binding: Binding,
+    headers: StringRecord,
+    required: Vec<Column>,
+    lenient: bool,
+    // The line each PassengerId was first seen on, the only state that grows with the file
+    seen: HashMap<u64, u64>,
+    report: ValidationReport,
+}
+
+impl Validator {
+    pub fn new(filename: &str, headers: &StringRecord, schema: &Schema, required: &[Column], lenient: bool) -> Result<Validator, String> {
+        let binding = schema.bind(headers, required).map_err(|e1| format!("Validator::new {}: {}", filename, e1))?;
+        Ok(Validator {
+            filename: filename.to_string(),
+            binding,
+            headers: headers.clone(),
+            required: required.to_vec(),
+            lenient,
+            seen: HashMap::new(),
+            report: ValidationReport { rows: 0, kept: 0, issues: Vec::new() },
+        })
+    }
+
+    pub fn get_binding(&self) -> &Binding {
+        &self.binding
+    }
+
+    pub fn get_report(&self) -> &ValidationReport {
+        &self.report
+    }
+
+    pub fn into_report(self) -> ValidationReport {
+        self.report
+    }
+
+    // The record of a row, or None when the row is left out, adding its problems to the report.
+    pub fn check<T: DeserializeOwned>(&mut self, row: &StringRecord) -> Result<Option<T>, String> {
+        self.report.rows += 1;
+        let line = row.position().map_or(0, |position| position.line());
+        let passenger_id = self.binding.value(row, &Column::PassengerId).unwrap_or("").to_string();
+        let mut problems = Vec::new();
+        for (column, index) in self.binding.get_columns() {
+            let value = row.get(*index).unwrap_or("");
+            let mut problem = check_value(column, value);
+            if problem.is_none() && *column == Column::PassengerId {
+                if let Ok(id) = value.parse::<u64>() {
+                    match self.seen.get(&id) {
+                        Some(first) => problem = Some(format!("repeats the PassengerId of line {}", first)),
+                        None => {
+                            self.seen.insert(id, line);
+                        },
+                    }
+                }
+            }
+            if let Some(problem) = problem {
+                problems.push((*column, *index, value.to_string(), problem));
+            }
+        }
+        let skip = problems.iter().any(|(column, _index, _value, _problem)| self.required.contains(column));
+        for (_column, index, value, problem) in &problems {
+            let action = match (self.lenient, skip) {
+                (false, _skip) => Action::Rejected,
+                (true, true) => Action::Skipped,
+                (true, false) => Action::Imputed,
+            };
+            let issue = Issue {
+                file: self.filename.clone(),
+                line,
+                passenger_id: passenger_id.clone(),
+                column: self.headers.get(*index).unwrap_or("").to_string(),
+                value: value.clone(),
+                problem: problem.clone(),
+                action,
+            };
+            if self.lenient {
+                eprintln!("Validator::check {} {}: {}", self.filename, action, issue);
+            }
+            self.report.issues.push(issue);
+        }
+        if !problems.is_empty() && (!self.lenient || skip) {
+            return Ok(None)
+        }
+        let mut cleaned: StringRecord = row.iter().enumerate().map(|(index, value)| {
+            if problems.iter().any(|(_column, other, _value, _problem)| *other == index) { "" } else { value }
+        }).collect();
+        cleaned.set_position(row.position().cloned());
+        let record = self.binding.deserialize(&cleaned).map_err(|e2| format!("Validator::check {}: {}", self.filename, e2))?;
+        self.report.kept += 1;
+        Ok(Some(record))
+    }
+}
+
+// Reads every row of a file, or of standard input, through the schema after checking its values with a Validator.
+// Strictly, rows with problems are rejected for ensure_valid to fail on once the whole file is checked.
+pub fn read_rows<T: DeserializeOwned>(filename: &str, format: &Option<DataFormat>, schema: &Schema, required: &[Column], lenient: bool) -> Result<(Vec<T>, ValidationReport), String> {
+    let mut reader = RowReader::open(filename, format)?;
+    let mut validator = Validator::new(filename, reader.get_headers(), schema, required, lenient)?;
+    let mut records = Vec::new();
+    let mut row = StringRecord::new();
+    while reader.read_row(&mut row)? {
+        if let Some(record) = validator.check(&row)? {
+            records.push(record);
+        }
+    }
+    let report = validator.into_report();
+    eprintln!("validation::read_rows {}: {} rows, {} kept, {} problems", filename, report.rows, report.kept, report.issues.len());
+    Ok((records, report))
+}
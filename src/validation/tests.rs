@@ -0,0 +1,72 @@
+use super::*;
+use std::env;
+use std::fs;
+
+use crate::{Feature, FeatureValue, PassengerFeatures, TrainingPassenger};
+
+const HEADER: &str = "PassengerId,Survived,Pclass,Sex,Age,Fare,Embarked\n";
+
+fn temp_file(name: &str, contents: &str) -> String {
+    let filename = env::temp_dir().join(format!("kaggle_c_titanic_validation_{}_{}", std::process::id(), name)).to_string_lossy().into_owned();
+    fs::write(&filename, contents).unwrap();
+    filename
+}
+
+fn read(name: &str, rows: &str, lenient: bool) -> (String, Vec<TrainingPassenger>, ValidationReport) {
+    let filename = temp_file(name, &format!("{}{}", HEADER, rows));
+    let (passengers, report) = read_rows(&filename, &None, &Schema::default(), &[Column::PassengerId, Column::Survived], lenient).unwrap();
+    fs::remove_file(&filename).unwrap();
+    (filename, passengers, report)
+}
+
+#[test]
+fn when_strict_and_value_out_of_range_then_error_names_line_and_column() {
+    let (filename, passengers, report) = read("strict.csv", "1,0,3,male,22,7.25,S\n2,1,4,female,38,71.28,C\n3,1,3,female,26,-7.9,S\n", false);
+
+    assert_eq!(passengers.len(), 1);
+    assert_eq!(report.rows, 3);
+    assert_eq!(report.kept, 1);
+    assert_eq!(report.issues.len(), 2);
+    assert!(report.issues.iter().all(|issue| issue.action == Action::Rejected));
+    let error = report.ensure_valid(&filename).unwrap_err();
+    assert!(error.contains("2 problems"), "{}", error);
+    assert!(error.contains("line 3 (PassengerId 2) column Pclass value '4' is not 1, 2 or 3"), "{}", error);
+    assert!(error.contains("line 4 (PassengerId 3) column Fare value '-7.9' is outside 0 and above"), "{}", error);
+}
+
+#[test]
+fn when_lenient_then_bad_labels_and_duplicates_skipped_and_bad_features_imputed() {
+    let (_filename, passengers, report) = read("lenient.csv", "1,0,3,male,22,7.25,S\n2,yes,1,female,38,71.28,C\n1,1,3,female,26,7.92,S\n4,1,1,female,135,53.1,X\n", true);
+
+    assert_eq!(report.rows, 4);
+    assert_eq!(report.kept, 2);
+    assert_eq!(passengers.len(), 2);
+    assert!(report.ensure_valid("lenient.csv").is_ok());
+    assert_eq!(report.issues[0].problem, "is not 0 or 1");
+    assert_eq!(report.issues[0].action, Action::Skipped);
+    assert_eq!(report.issues[1].problem, "repeats the PassengerId of line 2");
+    assert_eq!(report.issues[1].action, Action::Skipped);
+    assert_eq!(report.issues[2].column, "Age");
+    assert_eq!(report.issues[3].column, "Embarked");
+    assert!(report.issues[2..].iter().all(|issue| issue.action == Action::Imputed));
+    assert_eq!(*passengers[1].get_passenger_id(), 4_u64);
+    assert_eq!(passengers[1].get_feature(&Feature::Age), FeatureValue::Missing);
+    assert_eq!(passengers[1].get_feature(&Feature::Fare), FeatureValue::Number(53.1_f64));
+}
+
+#[test]
+fn when_report_written_then_appended_under_one_header() {
+    let (_filename, _passengers, report) = read("report.csv", "1,0,3,male,-1,7.25,S\n", true);
+    let report_filename = temp_file("report_out.csv", "");
+
+    report.write(&report_filename, false).unwrap();
+    report.write(&report_filename, true).unwrap();
+    let contents = fs::read_to_string(&report_filename).unwrap();
+    fs::remove_file(&report_filename).unwrap();
+
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "file,line,passenger_id,column,value,problem,action");
+    assert!(lines[1].ends_with(",2,1,Age,-1,is outside 0 to 100,imputed"), "{}", lines[1]);
+    assert_eq!(lines[1], lines[2]);
+}